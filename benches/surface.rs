@@ -0,0 +1,166 @@
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::layout::Position;
+use tokio::sync::mpsc::unbounded_channel;
+
+use texaform::app::App;
+use texaform::entities::Entity;
+use texaform::surface::generation::Generator;
+use texaform::surface::grid::{Grid, Terrain};
+use texaform::surface::state::{SaveSnapshot, Seed, SurfaceState};
+use texaform::surface::{Surface, generation};
+use texaform::ui::{self, Screen};
+
+// frame draws, ticks and saves at sizes a long game reaches
+//   cargo bench
+//   cargo bench -- tick   only the benchmarks with tick in their name
+
+/// a large terminal, draws scale with the number of cells
+const WIDTH: u16 = 240;
+const HEIGHT: u16 = 64;
+
+fn new_game() -> Surface {
+    generation::new_game(unbounded_channel().0, Seed::Manual(1)).expect("starting entities fit")
+}
+
+/// a new game with `agents` more agents around the center, half of them powered smelters
+fn crowded(agents: usize) -> Surface {
+    let mut surface = new_game();
+    surface.power.capacity = usize::MAX / 2;
+    surface.power.stored = usize::MAX / 2;
+    let mut added = 0;
+    'rows: for y in (20..230).step_by(3) {
+        for x in (20..230).step_by(3) {
+            if added == agents {
+                break 'rows;
+            }
+            let entity = if added % 2 == 0 {
+                Entity::Dog
+            } else {
+                Entity::Smelter
+            };
+            let agent = entity.create_agent().expect("is an agent");
+            let Ok(port) = surface.add_agent(&Position { x, y }, agent) else {
+                continue;
+            };
+            if entity == Entity::Smelter {
+                surface.send(port, "POWR");
+            }
+            added += 1;
+        }
+    }
+    surface
+}
+
+fn bench_render_grid(c: &mut Criterion) {
+    let surface = new_game();
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    c.bench_function("render_grid", |b| {
+        b.iter(|| {
+            terminal
+                .draw(|frame| surface.render_grid(frame, frame.area()))
+                .unwrap();
+        })
+    });
+}
+
+/// a screen of a large world nobody has looked at yet, generating every chunk on it
+fn bench_generate_screen(c: &mut Criterion) {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    c.bench_function("generate_screen", |b| {
+        b.iter(|| {
+            let terrain = Terrain {
+                seed: 1,
+                generator: Generator::Chunked,
+            };
+            let grid = Grid::generated(4000, terrain);
+            terminal
+                .draw(|frame| grid.render(frame, frame.area(), Position { x: 1000, y: 1000 }))
+                .unwrap();
+        })
+    });
+}
+
+fn bench_screens(c: &mut Criterion) {
+    let data_dir = std::env::temp_dir().join(format!("texaform-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&data_dir).unwrap();
+    texaform::logging::set_data_dir(data_dir.clone());
+
+    let mut group = c.benchmark_group("frame");
+    for screen in [
+        Screen::MainMenu,
+        Screen::Surface,
+        Screen::TechTree,
+        Screen::Documentation,
+        Screen::LoadGame,
+        Screen::Scenarios,
+    ] {
+        let mut app = App::new(unbounded_channel().0, WIDTH, HEIGHT);
+        app.surface = new_game();
+        app.set_screen(Screen::Surface);
+        app.set_screen(screen);
+        let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+        group.bench_function(BenchmarkId::from_parameter(screen), |b| {
+            b.iter(|| {
+                terminal.draw(|frame| ui::render(&mut app, frame)).unwrap();
+            })
+        });
+    }
+    group.finish();
+    let _ = std::fs::remove_dir_all(&data_dir);
+}
+
+fn bench_tick(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick");
+    for agents in [0, 100, 400] {
+        let mut surface = crowded(agents);
+        group.bench_function(BenchmarkId::new("agents", agents), |b| {
+            b.iter(|| surface.tick())
+        });
+    }
+    group.finish();
+}
+
+fn bench_save_load(c: &mut Criterion) {
+    let surface = crowded(100);
+    let path = std::env::temp_dir().join(format!("texaform-bench-{}.texaform", std::process::id()));
+    c.bench_function("save_load", |b| {
+        b.iter(|| {
+            SurfaceState::save_to_path(&surface, &path).unwrap();
+            black_box(SurfaceState::load(&path).unwrap())
+        })
+    });
+    let _ = std::fs::remove_file(&path);
+}
+
+/// the part of an autosave taken on the main loop, against the encoding done on its thread
+fn bench_autosave(c: &mut Criterion) {
+    let mut surface = crowded(100);
+    for _ in 0..1000 {
+        surface.tick();
+        surface.send(surface.agents.keys().nth(2).copied().unwrap(), "SCAN");
+    }
+    let mut group = c.benchmark_group("autosave");
+    group.bench_function("snapshot", |b| {
+        b.iter(|| black_box(SaveSnapshot::new(&surface).unwrap()))
+    });
+    let snapshot = SaveSnapshot::new(&surface).unwrap();
+    group.bench_function("encode", |b| {
+        b.iter(|| black_box(snapshot.encode().unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_render_grid,
+    bench_generate_screen,
+    bench_screens,
+    bench_tick,
+    bench_save_load,
+    bench_autosave
+);
+criterion_main!(benches);
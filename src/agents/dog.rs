@@ -1,4 +1,4 @@
-use crate::agents::{Agent, UpdateEnum};
+use crate::agents::{Agent, CommandError, Topic, UpdateEnum};
 use crate::entities::{Entity, EntityContainer};
 use crate::surface::Power;
 use crate::surface::grid::{Gent, Grid};
@@ -18,6 +18,8 @@ use ratatui::buffer::Buffer;
 use ratatui::widgets::{Gauge, WidgetRef};
 
 const MAX_BATTERY: usize = 10000;
+/// battery level that sends LOW_BATTERY when crossed
+const LOW_BATTERY: usize = MAX_BATTERY / 5;
 
 impl WidgetRef for Dog {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
@@ -40,6 +42,8 @@ pub struct Dog {
     payload: EntityContainer,
     battery: usize,
     charging: bool,
+    #[serde(skip)]
+    notifications: Vec<Topic>,
     // TODO we might want to do the "repository approach here too"
     #[serde(skip)]
     effects: Vec<Effect>,
@@ -98,6 +102,7 @@ impl Agent for Dog {
             payload: EntityContainer::new("Payload", 1),
             battery: MAX_BATTERY,
             charging: false,
+            notifications: vec![],
             effects: vec![],
         }
     }
@@ -111,7 +116,7 @@ impl Agent for Dog {
     ) -> UpdateEnum {
         match Self::parse_command(&msg) {
             Ok(command) => self.handle_command(pos, grid, command),
-            Err(e) => UpdateEnum::reply(format!("ERRR: {e}")),
+            Err(e) => UpdateEnum::invalid_command(e),
         }
     }
 
@@ -189,6 +194,17 @@ impl Agent for Dog {
     fn placable(&self, _entity: &Entity) -> bool {
         self.payload.placable()
     }
+
+    fn containers(&self) -> Vec<&EntityContainer> {
+        vec![&self.payload]
+    }
+    fn containers_mut(&mut self) -> Vec<&mut EntityContainer> {
+        vec![&mut self.payload]
+    }
+
+    fn notifications(&mut self) -> Vec<Topic> {
+        std::mem::take(&mut self.notifications)
+    }
 }
 
 impl Dog {
@@ -203,10 +219,10 @@ impl Dog {
         }
         let energy = command.energy_cost();
         if self.battery < energy {
-            self.battery = 0;
-            return UpdateEnum::reply(Reply::ERRR("low battery".to_string()));
+            self.drain_battery(self.battery);
+            return UpdateEnum::reply(Reply::ERRR(CommandError::LowBattery));
         }
-        self.battery = self.battery.saturating_sub(energy);
+        self.drain_battery(energy);
         match command {
             Command::TURN(Direction::L) => {
                 self.facing = self.facing.left();
@@ -226,13 +242,13 @@ impl Dog {
                 let forward = self.cordinites_forward(pos);
                 match grid.get_mut(&forward) {
                     Some(Gent::Empty) => UpdateEnum::Move(forward),
-                    Some(_gent) => UpdateEnum::reply(Reply::ERRR("crashed".to_string())),
-                    None => UpdateEnum::reply(Reply::ERRR("out of bounds".to_string())),
+                    Some(_gent) => UpdateEnum::reply(Reply::ERRR(CommandError::Crashed)),
+                    None => UpdateEnum::reply(Reply::ERRR(CommandError::OutOfBounds)),
                 }
             }
             Command::PICK(entity) => {
                 if !self.payload.placable() {
-                    UpdateEnum::reply(Reply::ERRR("already full".to_string()))
+                    UpdateEnum::reply(Reply::ERRR(CommandError::AlreadyFull))
                 } else {
                     let forward = self.cordinites_forward(pos);
                     match grid.pick(entity.character(), &forward) {
@@ -240,7 +256,7 @@ impl Dog {
                             self.payload.place(gent);
                             UpdateEnum::okay()
                         }
-                        None => UpdateEnum::reply(Reply::ERRR(format!("no {entity} to pick"))),
+                        None => UpdateEnum::reply(Reply::ERRR(CommandError::NothingToPick(entity))),
                     }
                 }
             }
@@ -250,7 +266,7 @@ impl Dog {
                     self.charging = true;
                     UpdateEnum::okay()
                 } else {
-                    UpdateEnum::reply(Reply::ERRR("not facing an accumulator".to_string()))
+                    UpdateEnum::reply(Reply::ERRR(CommandError::NotFacingAccumulator))
                 }
             }
             // TODO DESIGN: do we want ability to drop specific item?
@@ -268,20 +284,20 @@ impl Dog {
                                 UpdateEnum::okay()
                             } else {
                                 self.payload.place(first_entity);
-                                UpdateEnum::reply(Reply::ERRR("cannot drop here".to_string()))
+                                UpdateEnum::reply(Reply::ERRR(CommandError::CannotDrop))
                             }
                         }
                         Some(_) => {
                             self.payload.place(first_entity);
-                            UpdateEnum::reply(Reply::ERRR("cannot drop here".to_string()))
+                            UpdateEnum::reply(Reply::ERRR(CommandError::CannotDrop))
                         }
                         None => {
                             self.payload.place(first_entity);
-                            UpdateEnum::reply(Reply::ERRR("out of bounds".to_string()))
+                            UpdateEnum::reply(Reply::ERRR(CommandError::OutOfBounds))
                         }
                     }
                 } else {
-                    UpdateEnum::reply(Reply::ERRR("payload is empty".to_string()))
+                    UpdateEnum::reply(Reply::ERRR(CommandError::PayloadEmpty))
                 }
             }
             Command::BULD => {
@@ -314,13 +330,13 @@ impl Dog {
                                 }
                             }
                         } else {
-                            UpdateEnum::reply(Reply::ERRR("location not buildable".to_string()))
+                            UpdateEnum::reply(Reply::ERRR(CommandError::LocationNotBuildable))
                         }
                     } else {
-                        UpdateEnum::reply(Reply::ERRR(format!("{entity} is not buildable")))
+                        UpdateEnum::reply(Reply::ERRR(CommandError::NotBuildable(entity)))
                     }
                 } else {
-                    UpdateEnum::reply(Reply::ERRR("no payload to build".to_string()))
+                    UpdateEnum::reply(Reply::ERRR(CommandError::NoPayload))
                 }
             }
             // 3 forward scan
@@ -430,6 +446,14 @@ impl Dog {
         }
     }
 
+    fn drain_battery(&mut self, energy: usize) {
+        let before = self.battery;
+        self.battery = self.battery.saturating_sub(energy);
+        if before >= LOW_BATTERY && self.battery < LOW_BATTERY {
+            self.notifications.push(Topic::LOW_BATTERY);
+        }
+    }
+
     fn get_char(&self) -> char {
         match self.facing {
             CardinalDirection::North => 'ʌ',
@@ -519,9 +543,9 @@ impl Command {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum Reply {
-    ERRR(String),
+    ERRR(CommandError),
     DONE,
     BUSY,
     AREA(String),
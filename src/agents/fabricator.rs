@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::agents::{Agent, UpdateEnum};
+use crate::agents::{Agent, CommandError, Topic, UpdateEnum};
 use crate::entities::{Entity, EntityContainer, PickResult};
 use crate::surface::grid::Grid;
 use crate::surface::state::GameState;
@@ -44,6 +44,8 @@ impl WidgetRef for Fabricator {
 pub struct Fabricator {
     pub buffer_in: EntityContainer,
     pub buffer_out: EntityContainer,
+    #[serde(skip)]
+    notifications: Vec<Topic>,
 }
 
 #[typetag::serde]
@@ -52,6 +54,7 @@ impl Agent for Fabricator {
         Self {
             buffer_in: EntityContainer::new("BUFFER_IN", 20),
             buffer_out: EntityContainer::new("BUFFER_OUT", 20),
+            notifications: vec![],
         }
     }
     fn handle_message(
@@ -63,7 +66,7 @@ impl Agent for Fabricator {
     ) -> UpdateEnum {
         match Self::parse_command(&msg) {
             Ok(command) => self.handle_command(command, pos, grid, game_state),
-            Err(e) => UpdateEnum::reply(format!("ERRR: {e}")),
+            Err(e) => UpdateEnum::invalid_command(e),
         }
     }
 
@@ -85,6 +88,16 @@ impl Agent for Fabricator {
     fn place(&mut self, entity: Entity) {
         self.buffer_in.place(entity)
     }
+    fn containers(&self) -> Vec<&EntityContainer> {
+        vec![&self.buffer_in, &self.buffer_out]
+    }
+    fn containers_mut(&mut self) -> Vec<&mut EntityContainer> {
+        vec![&mut self.buffer_in, &mut self.buffer_out]
+    }
+
+    fn notifications(&mut self) -> Vec<Topic> {
+        std::mem::take(&mut self.notifications)
+    }
 }
 
 fn contains_cost(buffer: &[Entity], cost: &HashMap<Entity, u8>) -> bool {
@@ -118,10 +131,10 @@ impl Fabricator {
                         game_state.progress_current_tech();
                         UpdateEnum::Research
                     } else {
-                        UpdateEnum::reply(Reply::ERRR("insufficent materials".to_string()))
+                        UpdateEnum::reply(Reply::ERRR(CommandError::InsufficientMaterials))
                     }
                 } else {
-                    UpdateEnum::reply(Reply::ERRR("no active research".to_string()))
+                    UpdateEnum::reply(Reply::ERRR(CommandError::NoActiveResearch))
                 }
             }
             Command::MAKE(entity) => {
@@ -131,12 +144,13 @@ impl Fabricator {
                     .expect("checked existance when parsing the command");
                 let contains_materials = contains_cost(&self.buffer_in.content, cost);
                 if !kind_unlocked {
-                    UpdateEnum::reply(Reply::ERRR("not unlocked".to_string()))
+                    UpdateEnum::reply(Reply::ERRR(CommandError::NotUnlocked))
                 } else if !contains_materials {
-                    UpdateEnum::reply(Reply::ERRR("insufficent materials".to_string()))
+                    UpdateEnum::reply(Reply::ERRR(CommandError::InsufficientMaterials))
                 } else {
                     self.buffer_in.remove_content(cost).expect("CHECKED");
                     self.buffer_out.place(entity);
+                    self.notifications.push(Topic::BUFFER_OUT);
                     UpdateEnum::okay()
                 }
             }
@@ -192,9 +206,9 @@ COMMANDS
       STAT  ->  STAT _ MM     #FABRICATOR's buffer_out contains 2 MOTOR
 ";
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum Reply {
-    ERRR(String),
+    ERRR(CommandError),
     ADDR(usize),
     STAT { content: Vec<char> },
 }
@@ -7,6 +7,8 @@ use serde::{Deserialize, Serialize};
 use ratatui::buffer::Buffer;
 use ratatui::widgets::WidgetRef;
 
+use crate::agents::CommandError;
+use crate::speed::{self, Preset};
 use crate::surface::Power;
 use crate::tech_tree::{Tech, TechKind, TechStatus};
 
@@ -21,11 +23,32 @@ pub struct Hud {
 }
 
 impl Hud {
+    /// commands (valid or not) that still apply immediately while the simulation is paused
+    pub fn is_control_command(msg: &str) -> bool {
+        msg == "STAT SPED" || msg.starts_with("SPED") || msg.starts_with("STEP")
+    }
+
     pub fn parse_command(msg: &str) -> Result<Command, String> {
         match msg {
             "LIST AGNT" => Ok(Command::LIST_AGNT),
             "LIST RESR" => Ok(Command::LIST_RESR),
             "STAT POWR" => Ok(Command::STAT_POWR),
+            "STAT SPED" => Ok(Command::STAT_SPED),
+            x if x.starts_with("STEP") => {
+                let ticks = x.split_whitespace().nth(1).unwrap_or("1");
+                match ticks.parse::<u64>() {
+                    Ok(ticks) if (1..=MAX_STEP).contains(&ticks) => Ok(Command::STEP(ticks)),
+                    _ => Err(format!("invalid tick count {ticks}")),
+                }
+            }
+            x if x.starts_with("SPED") => {
+                let preset = x.split_whitespace().nth(1).unwrap_or_default();
+                if let Ok(preset) = Preset::from_str(preset) {
+                    Ok(Command::SPED(preset))
+                } else {
+                    Err(format!("unknown speed {preset}"))
+                }
+            }
             x if x.starts_with("RESR") => {
                 let kind = x.split_whitespace().nth(1).unwrap_or_default();
                 if let Ok(tech_kind) = TechKind::from_str(kind) {
@@ -39,9 +62,15 @@ impl Hud {
     }
 }
 
+/// largest number of ticks a single STEP command can advance
+pub const MAX_STEP: u64 = 10_000;
+
 #[allow(non_camel_case_types)]
 pub enum Command {
     STAT_POWR,
+    STAT_SPED,
+    SPED(Preset),
+    STEP(u64),
     LIST_AGNT,
     LIST_RESR,
     RESR(TechKind),
@@ -59,6 +88,27 @@ COMMANDS
     Usage: 
       STAT POWR  ->  100 200 300000 400000 # production, consumption, stored energy, storage capacity in joules
 
+  STAT SPED
+    return the simulation speed
+
+    Usage:
+      STAT SPED  ->  1X 250 100        # speed, milliseconds per tick, milliseconds before each reply
+
+  SPED speed={PAUSED|1X|4X|UNCAPPED}
+    set the simulation speed, UNCAPPED ticks as fast as possible and replies without delay
+
+    Usage:
+      SPED 4X  ->  OKAY
+
+  STEP n=1
+    while PAUSED, advance n ticks (at most 10000)
+    commands received while paused are held and applied in port order on the next tick
+
+    Usage:
+      STEP     ->  OKAY
+      STEP 20  ->  OKAY
+      STEP 20  ->  ERRR not paused      # only while PAUSED
+
   LIST topic={AGNT|RESR} n
     cycles through information on the topic
 
@@ -89,9 +139,9 @@ COMMANDS
 ";
 
 #[allow(non_camel_case_types)]
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum Reply {
-    ERRR(String),
+    ERRR(CommandError),
     LIST_AGNT {
         port: usize,
         kind: String,
@@ -108,10 +158,28 @@ pub enum Reply {
         stored: usize,
         capacity: usize,
     },
+    STAT_SPED {
+        speed: String,
+        tick: u64,
+        latency: u64,
+    },
+    #[serde(rename = "OKAY")]
     RESR,
+    #[serde(rename = "OKAY")]
+    SPED,
+    #[serde(rename = "OKAY")]
+    STEP,
 }
 
 impl Reply {
+    pub fn speed() -> Reply {
+        Reply::STAT_SPED {
+            speed: speed::describe(),
+            tick: speed::tick_millis(),
+            latency: speed::command_latency_millis(),
+        }
+    }
+
     pub fn from_tech(tech: &Tech, status: TechStatus) -> Reply {
         let kind = tech.kind.to_string();
         let status = status.to_string();
@@ -158,7 +226,14 @@ impl std::fmt::Display for Reply {
             } => {
                 write!(f, "{production} {consumption} {stored} {capacity}")
             }
+            Reply::STAT_SPED {
+                speed,
+                tick,
+                latency,
+            } => write!(f, "{speed} {tick} {latency}"),
             Reply::RESR => write!(f, "OKAY"),
+            Reply::SPED => write!(f, "OKAY"),
+            Reply::STEP => write!(f, "OKAY"),
         }
     }
 }
@@ -1,4 +1,4 @@
-use crate::agents::{Agent, UpdateEnum};
+use crate::agents::{Agent, CommandError, Topic, UpdateEnum};
 use crate::entities::shape::Shape;
 use crate::entities::{Entity, EntityContainer, PickResult};
 use crate::surface::Power;
@@ -80,6 +80,8 @@ pub struct LaserCutter {
     pub integrity: u8,
     pub buffer_in: EntityContainer,
     pub buffer_out: EntityContainer,
+    #[serde(skip)]
+    notifications: Vec<Topic>,
 }
 
 #[typetag::serde]
@@ -95,6 +97,7 @@ impl Agent for LaserCutter {
             integrity: 100,
             buffer_in: EntityContainer::new("BUFFER IN", 10),
             buffer_out: EntityContainer::new("BUFFER OUT", 10),
+            notifications: vec![],
         }
     }
 
@@ -107,12 +110,16 @@ impl Agent for LaserCutter {
     ) -> UpdateEnum {
         match Self::parse_command(&msg) {
             Ok(command) => self.handle_command(command),
-            Err(e) => UpdateEnum::reply(format!("ERRR: {e}")),
+            Err(e) => UpdateEnum::invalid_command(e),
         }
     }
 
     fn tick(&mut self, _power: &mut Power) {}
 
+    fn notifications(&mut self) -> Vec<Topic> {
+        std::mem::take(&mut self.notifications)
+    }
+
     fn entity(&self) -> Entity {
         Entity::LaserCutter
     }
@@ -131,6 +138,12 @@ impl Agent for LaserCutter {
     fn place(&mut self, entity: Entity) {
         self.buffer_in.place(entity);
     }
+    fn containers(&self) -> Vec<&EntityContainer> {
+        vec![&self.buffer_in, &self.buffer_out]
+    }
+    fn containers_mut(&mut self) -> Vec<&mut EntityContainer> {
+        vec![&mut self.buffer_in, &mut self.buffer_out]
+    }
 }
 
 fn tens_digit_char(i: usize) -> char {
@@ -232,14 +245,14 @@ impl LaserCutter {
                     PLATE_WIDTH,
                 );
                 if self.plate[idx] == State::Cut {
-                    return UpdateEnum::reply("ERRR no material to pick");
+                    return UpdateEnum::reply(Reply::ERRR(CommandError::NoMaterial));
                 }
                 tracing::info!("p: {p:?}");
                 let (expected_material, expected_shape) = p
                     .material_and_shape()
                     .expect("command only accepts entity with a shape");
                 if Some(expected_material) != self.plate_kind {
-                    return UpdateEnum::reply("ERRR incorrect material");
+                    return UpdateEnum::reply(Reply::ERRR(CommandError::IncorrectMaterial));
                 }
                 let found_shape = Solver::new(
                     self.plate.clone(),
@@ -260,17 +273,18 @@ impl LaserCutter {
                 );
                 if expected_shape == found_shape.clone().normalize() {
                     self.buffer_out.place(p);
+                    self.notifications.push(Topic::BUFFER_OUT);
                     for pos in found_shape.positions {
                         self.plate[pos_to_idx(&pos, PLATE_WIDTH)] = State::Cut;
                     }
                     UpdateEnum::okay()
                 } else {
-                    UpdateEnum::reply("ERRR shape does not match")
+                    UpdateEnum::reply(Reply::ERRR(CommandError::ShapeMismatch))
                 }
             }
             Command::LOAD(entity) => {
                 if self.laser_is_on {
-                    return UpdateEnum::reply("ERRR laser is on");
+                    return UpdateEnum::reply(Reply::ERRR(CommandError::LaserOn));
                 }
                 // TODO match entity
                 // TODO bug here: need to have method on buffer_in to remove entity
@@ -284,7 +298,7 @@ impl LaserCutter {
                         self.plate_kind = Some(entity);
                         UpdateEnum::okay()
                     }
-                    Err(_) => UpdateEnum::reply(format!("ERRR {entity}")),
+                    Err(_) => UpdateEnum::reply(Reply::ERRR(CommandError::MissingMaterial(entity))),
                 }
             }
             Command::MVXP => {
@@ -424,9 +438,9 @@ COMMANDS
       STAT  ->  STAT P _     #the input buffer contains one PLATE
 ";
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum Reply {
-    ERRR(String),
+    ERRR(CommandError),
     STAT {
         buffer_in: Vec<char>,
         buffer_out: Vec<char>,
@@ -6,6 +6,7 @@ pub mod smelter;
 
 use crate::AppResult;
 use crate::entities::Entity;
+use crate::entities::EntityContainer;
 use crate::entities::PickResult;
 use crate::event::Event;
 use crate::surface::Power;
@@ -22,13 +23,12 @@ use ratatui::style::Color;
 use ratatui::widgets::WidgetRef;
 use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
-use tokio::sync::mpsc;
-use tokio::sync::mpsc::Sender;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::task::AbortHandle;
 
-use std::fmt::Display;
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use ratatui::widgets::List;
 use serde_with::serde_as;
@@ -75,6 +75,14 @@ pub trait Agent: std::fmt::Debug + WidgetRef {
     }
     fn place(&mut self, _entity: Entity) {}
 
+    /// implement if the agent holds entities, e.g. to count them for scenario goals
+    fn containers(&self) -> Vec<&EntityContainer> {
+        vec![]
+    }
+    fn containers_mut(&mut self) -> Vec<&mut EntityContainer> {
+        vec![]
+    }
+
     // TODO replace with requiring Default trait
     fn new() -> Self
     where
@@ -83,6 +91,42 @@ pub trait Agent: std::fmt::Debug + WidgetRef {
     fn integrity(&self) -> usize {
         100
     }
+
+    /// topics that fired since the last call, collected after every tick and command
+    fn notifications(&mut self) -> Vec<Topic> {
+        vec![]
+    }
+}
+
+/// state changes a client can subscribe to with `SUBS`
+#[allow(non_camel_case_types)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+pub enum Topic {
+    /// SMELTER finished smelting an entity
+    SMELT_DONE,
+    /// an entity was added to an agent's BUFFER_OUT
+    BUFFER_OUT,
+    /// DOG's battery dropped below 20%
+    LOW_BATTERY,
+    /// HUD, the active research completed
+    RESEARCH_DONE,
+}
+
+/// notifications buffered per agent before slow subscribers start missing them
+const NOTIFICATION_CAPACITY: usize = 64;
+
+fn notifier() -> broadcast::Sender<Topic> {
+    broadcast::channel(NOTIFICATION_CAPACITY).0
 }
 
 pub enum UpdateEnum {
@@ -94,18 +138,154 @@ pub enum UpdateEnum {
         pos: Position,
         entity: Entity,
     },
-    Reply(String),
+    Reply(Response),
     Move(Position),
     Research,
 }
 
 impl UpdateEnum {
-    pub fn reply(reply: impl std::fmt::Display) -> UpdateEnum {
-        UpdateEnum::Reply(reply.to_string())
+    pub fn reply(reply: impl std::fmt::Display + Serialize) -> UpdateEnum {
+        UpdateEnum::Reply(Response::new(reply))
+    }
+
+    pub fn error(error: CommandError) -> UpdateEnum {
+        UpdateEnum::Reply(Response::error(error))
+    }
+
+    pub fn invalid_command(error: String) -> UpdateEnum {
+        UpdateEnum::Reply(Response::invalid_command(error))
     }
 
     pub fn okay() -> UpdateEnum {
-        UpdateEnum::Reply("OKAY".to_string())
+        UpdateEnum::Reply(Response::okay())
+    }
+}
+
+/// a reply rendered for both protocols, `text` for the semicolon protocol and `json` after a
+/// `PROT JSON` handshake
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub text: String,
+    pub json: serde_json::Value,
+}
+
+impl Response {
+    pub fn new(reply: impl std::fmt::Display + Serialize) -> Response {
+        let json = serde_json::to_value(&reply).unwrap_or_else(|e| {
+            tracing::error!("failed to serialize reply {reply}: {e}");
+            serde_json::Value::Null
+        });
+        Response {
+            text: reply.to_string(),
+            json,
+        }
+    }
+
+    pub fn okay() -> Response {
+        Response::new("OKAY")
+    }
+
+    /// pushed to subscribed connections, never a reply to a command
+    pub fn event(topic: Topic) -> Response {
+        Response {
+            text: format!("EVNT {topic}"),
+            json: serde_json::json!({ "EVNT": topic }),
+        }
+    }
+
+    pub fn error(error: CommandError) -> Response {
+        Response {
+            text: format!("ERRR {error}"),
+            json: serde_json::json!({ "ERRR": error }),
+        }
+    }
+
+    /// a command an agent could not parse, the text keeps the `ERRR: ` clients already match on
+    pub fn invalid_command(error: String) -> Response {
+        let error = CommandError::InvalidCommand(error);
+        Response {
+            text: format!("ERRR: {error}"),
+            json: serde_json::json!({ "ERRR": error }),
+        }
+    }
+}
+
+/// reasons a command can fail, the code is stable for clients to match on while the message is
+/// what the text protocol sends after `ERRR`
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, strum_macros::IntoStaticStr)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum CommandError {
+    #[error("{0}")]
+    InvalidCommand(String),
+    #[error("{0}")]
+    InvalidRequest(String),
+    #[error("unknown agent {0}")]
+    UnknownAgent(usize),
+    #[error("no reply")]
+    NoReply,
+    #[error("timeout")]
+    Timeout,
+    #[error("low battery")]
+    LowBattery,
+    #[error("crashed")]
+    Crashed,
+    #[error("out of bounds")]
+    OutOfBounds,
+    #[error("already full")]
+    AlreadyFull,
+    #[error("no {0} to pick")]
+    NothingToPick(Entity),
+    #[error("not facing an accumulator")]
+    NotFacingAccumulator,
+    #[error("cannot drop here")]
+    CannotDrop,
+    #[error("payload is empty")]
+    PayloadEmpty,
+    #[error("location not buildable")]
+    LocationNotBuildable,
+    #[error("{0} is not buildable")]
+    NotBuildable(Entity),
+    #[error("no payload to build")]
+    NoPayload,
+    #[error("insufficent materials")]
+    InsufficientMaterials,
+    #[error("no active research")]
+    NoActiveResearch,
+    #[error("not unlocked")]
+    NotUnlocked,
+    #[error("no material to pick")]
+    NoMaterial,
+    #[error("incorrect material")]
+    IncorrectMaterial,
+    #[error("shape does not match")]
+    ShapeMismatch,
+    #[error("laser is on")]
+    LaserOn,
+    #[error("{0}")]
+    MissingMaterial(Entity),
+    #[error("already researched")]
+    AlreadyResearched,
+    #[error("prerequisits not met")]
+    PrerequisitesNotMet,
+    #[error("unknown research")]
+    UnknownResearch,
+    #[error("not paused")]
+    NotPaused,
+}
+
+impl CommandError {
+    pub fn code(&self) -> &'static str {
+        self.into()
+    }
+}
+
+impl Serialize for CommandError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("CommandError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
     }
 }
 
@@ -239,46 +419,47 @@ pub struct Comms {
     #[serde(skip)]
     pub log: CommLogs,
 
+    #[serde(skip)]
     pub address: Option<SocketAddr>,
     pub text_box: TextBox,
 
-    #[serde(skip)]
-    pub reply_sender: Option<Sender<String>>,
+    #[serde(skip, default = "notifier")]
+    notifier: broadcast::Sender<Topic>,
     #[serde(skip)]
     pub(self) drop_handle: Option<DropHandle>,
 }
 
 impl Comms {
-    pub async fn init(&mut self, event_sender: &UnboundedSender<Event>) {
-        if self.reply_sender.is_none() {
-            let event_sender = event_sender.clone();
-            let (reply_sender, drop_handle): (Sender<String>, DropHandle) =
-                spawn_handler(self.port, event_sender)
-                    .await
-                    .unwrap_or_else(|_| panic!("TODO {}", self.port));
-            self.reply_sender = Some(reply_sender);
-            self.drop_handle = Some(drop_handle);
+    /// serves the agent's port in the background until the comms are dropped, clients can connect
+    /// as soon as this returns unless the port is still held by a replaced surface
+    pub fn listen(&mut self, event_sender: &UnboundedSender<Event>) {
+        if self.drop_handle.is_none() {
+            let listener = std::net::TcpListener::bind(format!("127.0.0.1:{}", self.port))
+                .and_then(|listener| listener.set_nonblocking(true).map(|_| listener));
+            let join_handle = tokio::spawn(serve(
+                self.port,
+                listener.ok(),
+                event_sender.clone(),
+                self.notifier.clone(),
+            ));
+            self.drop_handle = Some(DropHandle {
+                handle: join_handle.abort_handle(),
+            });
         }
     }
 
-    // fn sender(&mut self, surface: &Surface) -> &Sender<String> {
-    pub fn sender(&mut self) -> &Sender<String> {
-        // if self.reply_sender.is_none() {
-        //     let event_sender = surface.event_sender.clone();
-        //     let sender: Sender<String> = spawn_handler(self.port, event_sender).await.expect("TODO");
-        //     self.reply_sender = Some(sender);
-        // }
-        self.reply_sender
-            .as_ref()
-            .expect("reply_sender inited properly")
+    pub fn is_listening(&self) -> bool {
+        self.drop_handle.is_some()
+    }
+
+    /// sends `topic` to the connections subscribed to it
+    pub fn notify(&self, topic: Topic) {
+        // no connection is listening
+        let _ = self.notifier.send(topic);
     }
 
-    pub async fn new(surface: &Surface, location: Option<Rect>, agent_kind: Entity) -> Comms {
+    pub fn new(surface: &Surface, location: Option<Rect>, agent_kind: Entity) -> Comms {
         let port = surface.next_available_port();
-        tracing::info!("here: {port}");
-        let event_sender = surface.event_sender.clone();
-        let (reply_sender, drop_handle): (Sender<String>, DropHandle) =
-            spawn_handler(port, event_sender).await.expect("TODO");
         let (position, location) = match location {
             Some(location) => (Some(Position::new(location.x, location.y)), Some(location)),
             None => (None, None),
@@ -291,52 +472,74 @@ impl Comms {
             port,
             log: CommLogs::default(),
             text_box: TextBox::new().clear_on_enter(true),
-            reply_sender: Some(reply_sender),
-            drop_handle: Some(drop_handle),
+            notifier: notifier(),
+            drop_handle: None,
+        }
+    }
+}
+
+/// attempts to bind a port before giving up
+const BIND_ATTEMPTS: usize = 20;
+
+// the listener of a replaced surface can take a moment to release its port
+async fn bind(port: usize) -> AppResult<TcpListener> {
+    let mut attempt = 1;
+    loop {
+        match TcpListener::bind(format!("127.0.0.1:{port}")).await {
+            Ok(listener) => return Ok(listener),
+            Err(e) if attempt < BIND_ATTEMPTS => {
+                tracing::warn!("failed to bind port {port} (attempt {attempt}): {e}");
+                attempt += 1;
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            Err(e) => return Err(e.into()),
         }
     }
 }
 
-async fn spawn_handler<R: Display + Send + 'static>(
+async fn serve(
     port: usize,
+    listener: Option<std::net::TcpListener>,
     event_sender: UnboundedSender<Event>,
-) -> AppResult<(Sender<R>, DropHandle)> {
-    let listener = TcpListener::bind(format!("127.0.0.1:{port}")).await?;
+    notifier: broadcast::Sender<Topic>,
+) {
+    let listener = match listener.map(TcpListener::from_std) {
+        Some(Ok(listener)) => listener,
+        _ => match bind(port).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!("failed to start tcp listener on port {port}: {e}");
+                return;
+            }
+        },
+    };
     tracing::info!("started tcp listener on port {port}");
 
-    let (tx, mut rx) = mpsc::channel(32);
-
     // handling one connection at a time for now
-    let join_handle: tokio::task::JoinHandle<()> = tokio::spawn(async move {
-        loop {
-            let (socket, _) = listener.accept().await.unwrap();
-            let addr = socket.peer_addr().expect("should have peer address");
-            tracing::info!("peer address: {}", addr);
-            event_sender
-                .send(Event::AgentConnection(port, addr))
-                .expect("TODO");
-            tracing::info!("here asdf");
-            match handle_socket(port, socket, event_sender.clone(), &mut rx).await {
-                Err(e) => {
-                    tracing::warn!("error hadling socket: {e}");
-                    event_sender
-                        .send(Event::AgentDisconect(port))
-                        .expect("TODO");
-                    tracing::warn!("here");
-                }
-                Ok(_) => {
-                    event_sender
-                        .send(Event::AgentDisconect(port))
-                        .expect("TODO");
-                }
+    loop {
+        let (socket, addr) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                tracing::warn!("failed to accept connection on port {port}: {e}");
+                continue;
             }
+        };
+        tracing::info!("peer address: {}", addr);
+        // the main loop is gone once sends fail
+        if event_sender
+            .send(Event::AgentConnection(port, addr))
+            .is_err()
+        {
+            break;
         }
-    });
-
-    let drop_handle = DropHandle {
-        handle: join_handle.abort_handle(),
-    };
-    Ok((tx, drop_handle))
+        let notifications = notifier.subscribe();
+        if let Err(e) = handle_socket(port, socket, event_sender.clone(), notifications).await {
+            tracing::warn!("error hadling socket: {e}");
+        }
+        if event_sender.send(Event::AgentDisconect(port)).is_err() {
+            break;
+        }
+    }
 }
 
 pub const DOCUMENTATION: &str = r#"AGENTS
@@ -363,11 +566,14 @@ pub const DOCUMENTATION: &str = r#"AGENTS
 
     commands sent over TCP must:
       1. end in a semicolon `;`
-      2. be 32 bytes or less
+      2. be 1024 bytes or less
       3. all characters confrom to the American Standard Code for Information Interchange (ASCII)
 
     reponses received over TCP will follow the same rules
 
+    several commands can be sent at once, such as `MOVE;MOVE;TURN L;`, they are applied in order
+    and each one gets its own reply
+
 
     example python implemenataion:
 
@@ -389,5 +595,39 @@ pub const DOCUMENTATION: &str = r#"AGENTS
         response = send(dog, "MOVE")
         print(response) # OKAY
         ```
+
+
+  SUBSCRIPTIONS
+
+    instead of polling `STAT`, a connection can ask to be told when something happens to its agent
+
+    `SUBS topic` starts and `UNSB topic` stops notifications, they are sent between replies as `EVNT topic;`
+
+    topics:
+      SMELT_DONE     SMELTER finished smelting an entity
+      BUFFER_OUT     an entity was added to the agent's BUFFER_OUT (SMELTER, FABRICATOR, LASER_CUTTER)
+      LOW_BATTERY    DOG's battery dropped below 20%
+      RESEARCH_DONE  the active research completed (HUD)
+
+    Usage:
+      SUBS SMELT_DONE  ->  OKAY
+      ...              ->  EVNT SMELT_DONE
+      UNSB SMELT_DONE  ->  OKAY
+
+
+  JSON COMMUNICATION
+
+    sending `PROT JSON;` switches the connection to one JSON object per line, `PROT TEXT` switches back
+
+    commands are sent as `{"command": "MOVE"}` followed by a newline
+
+    replies are the agent's reply serialized as JSON, errors carry a code that does not change between versions
+
+    Usage:
+      PROT JSON;                     ->  OKAY;
+      {"command": "MOVE"}            ->  "OKAY"
+      {"command": "STAT"}            ->  {"STAT":{"battery":8500,"facing":"East","payload":" ","position":{"x":10,"y":20}}}
+      {"command": "MOVE"}            ->  {"ERRR":{"code":"LOW_BATTERY","message":"low battery"}}
+      {"command": "PROT TEXT"}       ->  "OKAY"
     
 "#;
@@ -1,4 +1,4 @@
-use crate::agents::{Agent, UpdateEnum};
+use crate::agents::{Agent, CommandError, Topic, UpdateEnum};
 use crate::entities::{Entity, EntityContainer, PickResult};
 use crate::surface::Power;
 use crate::surface::grid::Grid;
@@ -81,6 +81,8 @@ pub struct Smelter {
     pub hearth: Option<Entity>,
     pub buffer_in: EntityContainer,
     pub buffer_out: EntityContainer,
+    #[serde(skip)]
+    notifications: Vec<Topic>,
 }
 
 #[typetag::serde]
@@ -97,6 +99,7 @@ impl Agent for Smelter {
             integrity: 100,
             buffer_in: EntityContainer::new("BUFFER_IN", 10),
             buffer_out: EntityContainer::new("BUFFER_OUT", 10),
+            notifications: vec![],
         }
     }
 
@@ -109,7 +112,7 @@ impl Agent for Smelter {
     ) -> UpdateEnum {
         match Self::parse_command(&msg) {
             Ok(command) => self.handle_command(command),
-            Err(e) => UpdateEnum::reply(format!("ERRR: {e}")),
+            Err(e) => UpdateEnum::invalid_command(e),
         }
     }
 
@@ -139,13 +142,19 @@ impl Agent for Smelter {
             } else {
                 if let Some(p) = hearth.smelts_into() {
                     self.buffer_out.place(p);
+                    self.notifications.push(Topic::BUFFER_OUT);
                 }
                 self.hearth = None;
                 self.progress = 0;
+                self.notifications.push(Topic::SMELT_DONE);
             }
         }
     }
 
+    fn notifications(&mut self) -> Vec<Topic> {
+        std::mem::take(&mut self.notifications)
+    }
+
     fn entity(&self) -> Entity {
         Entity::Smelter
     }
@@ -179,6 +188,12 @@ impl Agent for Smelter {
     fn place(&mut self, entity: Entity) {
         self.buffer_in.place(entity);
     }
+    fn containers(&self) -> Vec<&EntityContainer> {
+        vec![&self.buffer_in, &self.buffer_out]
+    }
+    fn containers_mut(&mut self) -> Vec<&mut EntityContainer> {
+        vec![&mut self.buffer_in, &mut self.buffer_out]
+    }
 }
 
 impl Smelter {
@@ -241,9 +256,9 @@ COMMANDS
       STAT  ->  1000 _ PP   #SMELTER is 1000 degrees celsius with two PLATEs in its output
 ";
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum Reply {
-    ERRR(String),
+    ERRR(CommandError),
     STAT {
         temprature: usize,
         buffer_in: Vec<char>,
@@ -2,13 +2,23 @@ use ratatui::layout::Rect;
 use ratatui::widgets::Paragraph;
 use strum::VariantArray;
 
+use crate::agents::Response;
+use crate::autosave::{self, Autosaver};
 use crate::effects::Effects;
 use crate::event::Event;
+use crate::results::{self, Run, Scores};
+use crate::surface::journal::{self, JournalFile, Mismatch};
+use crate::surface::recording::Recording;
+use crate::surface::scenario::Scenario;
 use crate::surface::state::{Seed, SurfaceState};
 use crate::surface::{self, Surface};
+use crate::ui::console::Console;
+use crate::ui::debug::DebugOverlay;
 use crate::ui::documentation::Document;
+use crate::ui::load_game::SaveFileManager;
 use crate::ui::main_menu::MainMenu;
 use crate::ui::pause_menu::PauseMenu;
+use crate::ui::replay::Viewer;
 use crate::ui::{AppLayout, Screen};
 use crate::widgets::DoubleClickTracker;
 use crate::widgets::button::{BorderedButton, TextButton};
@@ -19,9 +29,8 @@ use crate::widgets::text_box::TextBox;
 use std::collections::HashMap;
 use std::error;
 use std::path::PathBuf;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use chrono::Local;
 use tokio::sync::mpsc::UnboundedSender;
 
 /// Application result type.
@@ -63,6 +72,8 @@ impl WorldWindow {
 pub enum InputMode {
     Normal,
     Editing,
+    /// typing into the cheat console
+    Console,
 }
 
 #[derive(Debug, Hash, PartialEq, Eq)]
@@ -89,6 +100,9 @@ pub struct App {
     // TODO for internal dev only
     // testing out storing smart pointer to the widget's area
     pub seed: Seed,
+    /// cells per side of the next new game, one of [`surface::WORLD_SIZES`] unless set on the
+    /// command line
+    pub world_size: u16,
 
     pub prev_tick: Duration,
     pub effects: Effects,
@@ -114,6 +128,8 @@ pub struct App {
     pub documentation_scroll: u16,
     pub pause_menu: ClickList<PauseMenu>,
     pub save_files: DoubleClickList<DisplayPathBuf>,
+    pub save_manager: SaveFileManager,
+    pub scenarios: DoubleClickList<Scenario>,
     pub layout: AppLayout,
     pub tech_tree_double_click_tracker: DoubleClickTracker<usize>,
 
@@ -121,6 +137,18 @@ pub struct App {
     pub save_button: BorderAttachedButton,
 
     pub event_sender: UnboundedSender<Event>,
+
+    /// applied to every surface started from this app, see [`Surface::lockstep`]
+    pub lockstep: bool,
+    /// recording path and snapshot interval for every surface started from this app
+    pub record: Option<(PathBuf, u64)>,
+    /// the recording being watched on [`Screen::Replay`]
+    pub viewer: Option<Viewer>,
+    pub autosaver: Autosaver,
+    /// how the won game compares to previous runs, shown on the victory popup
+    pub scores: Option<Scores>,
+    pub console: Console,
+    pub debug: DebugOverlay,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -201,6 +229,7 @@ impl App {
         let mut app = App {
             running: true,
             seed,
+            world_size: surface::DEFAULT_WORLD_SIZE,
             pause_menu_button,
             tutorial_previous_button,
             tutorial_next_button,
@@ -215,6 +244,8 @@ impl App {
             documentation,
             documentation_scroll: 0,
             save_files: DoubleClickList::default(),
+            save_manager: SaveFileManager::default(),
+            scenarios: DoubleClickList::default_style(vec![]),
             save_screen_text_box,
             save_button,
             screen: Screen::default(),
@@ -225,6 +256,13 @@ impl App {
             tech_tree_double_click_tracker: DoubleClickTracker::default(),
             event_sender,
             effects: Effects::new(),
+            lockstep: false,
+            record: None,
+            viewer: None,
+            autosaver: Autosaver::default(),
+            scores: None,
+            console: Console::default(),
+            debug: DebugOverlay::default(),
         };
         app.layout = AppLayout::update(width, height, &app);
         app
@@ -285,66 +323,123 @@ impl App {
         &self.previous_screen
     }
 
-    fn clean_up_autosaves(&mut self) -> Result<(), std::io::Error> {
-        let save_path = crate::logging::get_data_dir();
-        let mut autosave_paths = vec![];
-        for entry in std::fs::read_dir(save_path)? {
-            let path = entry?.path();
-            let path_is_autosave = path.to_string_lossy().ends_with("_AUTOSAVE.texaform");
-            if path.is_file() && path_is_autosave {
-                autosave_paths.push(path);
+    /// Handles the tick event of the terminal.
+    pub fn tick(&mut self) {
+        if self.screen == Screen::Replay {
+            if let Some(viewer) = self.viewer.as_mut() {
+                viewer.advance();
             }
+            return;
         }
-        if autosave_paths.len() > 10 {
-            tracing::info!("over 10 autosaves, attempting to remove oldest autosave file");
-            let oldest_file = autosave_paths
-                .iter()
-                .min_by_key(|path| {
-                    if let Ok(metadata) = path.metadata()
-                        && let Ok(sys_time) = metadata.modified()
-                    {
-                        sys_time
-                    } else {
-                        std::time::SystemTime::now()
-                    }
-                })
-                .expect("min will exist");
-            std::fs::remove_file(oldest_file)?;
-            match self.save_file_cache.remove(oldest_file) {
-                Some(_) => tracing::info!("removed {oldest_file:?} from save_file_cache"),
-                None => tracing::info!("{oldest_file:?} not in save_file_cache"),
+        let started = Instant::now();
+        self.surface.resolve_queued_commands();
+        if self.screen != Screen::SaveGame
+            && let Some(path) = self.autosaver.tick(&self.surface)
+        {
+            // the slot is being overwritten
+            self.save_file_cache.remove(&path);
+        }
+        let won = self.surface.victory_stats.is_some();
+        self.surface.tick();
+        if !won && let Some(run) = Run::new(&self.surface) {
+            match Scores::record(&results::path(), run) {
+                Ok(scores) => self.scores = Some(scores),
+                Err(e) => tracing::error!("failed to record results: {e}"),
             }
-            tracing::info!("removed {oldest_file:?}");
         }
-        Ok(())
+        let stats = &self.surface.game_state.stats;
+        self.debug.record_tick(
+            started.elapsed(),
+            stats.tcp_command_count + stats.manual_command_count,
+        );
     }
 
-    fn autosave_and_cleanup(&mut self) -> AppResult<()> {
-        tracing::info!("AUTOSAVE TIME");
-        let now = Local::now();
-        let formatted_time = now.format("%Y_%m_%d_%H:%M:%S");
-        SurfaceState::save(&self.surface, format!("{formatted_time}_AUTOSAVE"))?;
-        match self.clean_up_autosaves() {
-            Ok(_) => tracing::info!("cleaned up autosave dir"),
-            Err(e) => tracing::error!("failed to clean up autosave dir: {e}"),
+    /// generates a new surface from `self.seed` of `self.world_size` and switches to it
+    pub fn new_game(&mut self) -> Result<(), surface::AddEntityError> {
+        // make sure random seed is new
+        if matches!(self.seed, Seed::Random(_)) {
+            self.seed = Seed::default();
         }
+        self.surface = surface::generation::new_game_of_size(
+            self.event_sender.clone(),
+            self.seed,
+            self.world_size,
+        )?;
+        self.start_surface();
         Ok(())
     }
 
-    /// Handles the tick event of the terminal.
-    pub fn tick(&mut self) {
-        //TODO run in background
-        let five_minutes_of_ticks = 300_000 / crate::TICK_UPDATE_MILLS;
-        if (self.surface.game_state.stats.tick_count % five_minutes_of_ticks) == 0
-            && !self.surface.agents.is_empty()
-            && self.screen != Screen::SaveGame
-        {
-            match self.autosave_and_cleanup() {
-                Ok(_) => (),
-                Err(e) => tracing::error!("failed to autosave: {e}"),
+    pub fn load_game(&mut self, state: SurfaceState) {
+        // TODO how to force agents to be dropped?
+        self.surface = surface::generation::empty(self.event_sender.clone());
+        // TODO the following comment avoids the port in use panic
+        // so its probably a dely issue
+        // need to implement a Comms drop such that it waits till the ports are free
+        // again?
+        tracing::info!("should be dropped?: {:?}", self.surface.agents);
+        self.surface = state.into_surface(self.event_sender.clone());
+        self.start_surface();
+    }
+
+    /// starts the game a journal was recorded from and re-applies its commands
+    pub fn replay_game(&mut self, file: &JournalFile) -> AppResult<Vec<Mismatch>> {
+        let (surface, mismatches) = journal::replay(file, self.event_sender.clone())?;
+        self.seed = file.seed;
+        self.world_size = file.world_size;
+        self.surface = surface;
+        self.start_surface();
+        Ok(mismatches)
+    }
+
+    pub fn new_scenario(&mut self, scenario: &Scenario) -> AppResult<()> {
+        self.surface = scenario.new_game(self.event_sender.clone())?;
+        self.start_surface();
+        Ok(())
+    }
+
+    /// applies the app's settings to a newly started surface and switches to it
+    fn start_surface(&mut self) {
+        self.surface.lockstep = self.lockstep;
+        // a save of a won game is compared without being recorded again
+        self.scores = None;
+        if let Some(run) = Run::new(&self.surface) {
+            match Scores::load(&results::path(), run) {
+                Ok(scores) => self.scores = Some(scores),
+                Err(e) => tracing::error!("failed to load results: {e}"),
             }
         }
-        self.surface.tick();
+        if let Some((path, interval)) = &self.record
+            && let Err(e) = self.surface.start_recording(path, *interval)
+        {
+            tracing::error!("failed to start recording to {path:?}: {e}");
+        }
+        self.surface.journal_pause();
+        self.surface.listen();
+        self.set_screen(Screen::Surface);
+    }
+
+    /// watches a recording on the replay screen
+    pub fn view_recording(&mut self, recording: Recording) {
+        self.viewer = Some(Viewer::new(recording));
+        self.set_screen(Screen::Replay);
+    }
+
+    /// loads the most recently written save
+    pub fn continue_game(&mut self) -> AppResult<()> {
+        let path = autosave::newest_save().ok_or("no save files to continue")?;
+        tracing::info!("continuing from {path:?}");
+        let state = SurfaceState::load(&path)?;
+        self.load_game(state);
+        Ok(())
+    }
+
+    /// applies a cheat from the console or the cheat port, logging it to the console
+    pub fn cheat(&mut self, command: &str) -> Response {
+        let reply = self.surface.cheat(command);
+        self.console
+            .log
+            .push((command.to_string(), reply.text.clone()));
+        reply
     }
 
     /// Set running to false to quit the application.
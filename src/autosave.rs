@@ -0,0 +1,177 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::app::AppResult;
+use crate::surface::Surface;
+use crate::surface::state::{EncodedSave, SaveSnapshot};
+
+/// number of autosave files kept, the oldest is overwritten by the next autosave
+pub const SLOTS: usize = 5;
+
+/// time between autosaves, measured on the wall clock so faster speeds do not save more often
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// time between the snapshots kept for an emergency save
+pub const EMERGENCY_INTERVAL: Duration = Duration::from_secs(60);
+
+// the panic hook can not reach the surface, and a surface that panicked mid tick may not be
+// consistent anyway, so the hook writes the last snapshot taken between ticks
+static EMERGENCY_SNAPSHOT: Mutex<Option<Arc<EncodedSave>>> = Mutex::new(None);
+
+// bumped when the snapshot is cleared, so a snapshot still being encoded for the previous game is
+// dropped
+static EMERGENCY_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub fn slot_path(slot: usize) -> PathBuf {
+    crate::logging::get_data_dir().join(format!("AUTOSAVE_{slot}.texaform"))
+}
+
+pub fn emergency_path() -> PathBuf {
+    crate::logging::get_data_dir().join("EMERGENCY.texaform")
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|m| m.modified()).ok()
+}
+
+/// an unused slot, otherwise the one written longest ago
+pub fn next_slot() -> usize {
+    (1..=SLOTS)
+        .min_by_key(|slot| modified(&slot_path(*slot)))
+        .expect("SLOTS is not 0")
+}
+
+/// the most recently written save in the data directory
+pub fn newest_save() -> Option<PathBuf> {
+    std::fs::read_dir(crate::logging::get_data_dir())
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "texaform"))
+        .max_by_key(|path| modified(path))
+}
+
+/// writes the last snapshot to [`emergency_path`], called from the panic hook
+pub fn write_emergency_save() -> AppResult<Option<PathBuf>> {
+    // try_lock since the panic may have happened while the snapshot was being replaced
+    let snapshot = match EMERGENCY_SNAPSHOT.try_lock() {
+        Ok(snapshot) => snapshot.clone(),
+        Err(_) => return Err("emergency snapshot is locked".into()),
+    };
+    let Some(snapshot) = snapshot else {
+        return Ok(None);
+    };
+    let path = emergency_path();
+    snapshot.write(&path)?;
+    Ok(Some(path))
+}
+
+/// forgets the emergency snapshot, e.g. when returning to the main menu
+pub fn clear_emergency_save() {
+    if let Ok(mut snapshot) = EMERGENCY_SNAPSHOT.lock() {
+        EMERGENCY_GENERATION.fetch_add(1, Ordering::AcqRel);
+        *snapshot = None;
+    }
+}
+
+/// snapshots the surface between ticks on the main loop, then encodes and writes the snapshot on a
+/// background thread
+#[derive(Debug)]
+pub struct Autosaver {
+    /// wall-clock time between autosaves
+    pub autosave_interval: Duration,
+    /// wall-clock time between the snapshots kept for an emergency save
+    pub emergency_interval: Duration,
+    /// set while a background write is in progress
+    writing: Arc<AtomicBool>,
+    /// when and at which tick the last snapshot was taken
+    last_snapshot: Option<(Instant, u64)>,
+    last_autosave: Option<Instant>,
+}
+
+impl Default for Autosaver {
+    fn default() -> Self {
+        Autosaver {
+            autosave_interval: AUTOSAVE_INTERVAL,
+            emergency_interval: EMERGENCY_INTERVAL,
+            writing: Arc::default(),
+            last_snapshot: None,
+            last_autosave: None,
+        }
+    }
+}
+
+impl Autosaver {
+    pub fn is_writing(&self) -> bool {
+        self.writing.load(Ordering::Acquire)
+    }
+
+    /// snapshots the surface when a save is due, returning the path an autosave is being written
+    /// to
+    pub fn tick(&mut self, surface: &Surface) -> Option<PathBuf> {
+        let tick = surface.game_state.stats.tick_count;
+        if tick == 0 || surface.agents.is_empty() {
+            return None;
+        }
+        let now = Instant::now();
+        let (snapshot_at, snapshot_tick) = match self.last_snapshot {
+            // a new or loaded game starts the clocks again
+            Some((at, last_tick)) if last_tick <= tick => (at, last_tick),
+            _ => {
+                self.last_snapshot = Some((now, tick));
+                self.last_autosave = Some(now);
+                return None;
+            }
+        };
+        // nothing changed while paused
+        if snapshot_tick == tick || now.duration_since(snapshot_at) < self.emergency_interval {
+            return None;
+        }
+        if self.writing.swap(true, Ordering::AcqRel) {
+            tracing::warn!("previous snapshot is still being written, skipping snapshot");
+            return None;
+        }
+        let snapshot = match SaveSnapshot::new(surface) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                tracing::error!("failed to snapshot surface: {e}");
+                self.writing.store(false, Ordering::Release);
+                return None;
+            }
+        };
+        self.last_snapshot = Some((now, tick));
+        let autosave_due = self
+            .last_autosave
+            .is_none_or(|at| now.duration_since(at) >= self.autosave_interval);
+        let path = autosave_due.then(|| slot_path(next_slot()));
+        if autosave_due {
+            self.last_autosave = Some(now);
+        }
+        let generation = EMERGENCY_GENERATION.load(Ordering::Acquire);
+        let writing = self.writing.clone();
+        let thread_path = path.clone();
+        std::thread::spawn(move || {
+            match snapshot.encode() {
+                Ok(encoded) => {
+                    let encoded = Arc::new(encoded);
+                    if let Ok(mut emergency) = EMERGENCY_SNAPSHOT.lock()
+                        && EMERGENCY_GENERATION.load(Ordering::Acquire) == generation
+                    {
+                        *emergency = Some(encoded.clone());
+                    }
+                    if let Some(path) = thread_path {
+                        match encoded.write(&path) {
+                            Ok(()) => tracing::info!("autosaved to {path:?}"),
+                            Err(e) => tracing::error!("failed to autosave to {path:?}: {e}"),
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("failed to encode snapshot: {e}"),
+            }
+            writing.store(false, Ordering::Release);
+        });
+        path
+    }
+}
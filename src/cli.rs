@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::app::AppResult;
+use crate::surface::state::SurfaceState;
+
+/// texaform command line arguments
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// run without a terminal ui, only ticking the surface and serving agent ports
+    #[arg(long)]
+    pub headless: bool,
+
+    /// seed used to generate a new surface
+    #[arg(long, conflicts_with = "load")]
+    pub seed: Option<u64>,
+
+    /// cells per side of a new surface, defaults to 250
+    #[arg(
+        long,
+        conflicts_with_all = ["load", "replay", "scenario"],
+        value_parser = clap::value_parser!(u16).range(
+            crate::surface::MIN_WORLD_SIZE as i64..=crate::surface::MAX_WORLD_SIZE as i64
+        ),
+    )]
+    pub world_size: Option<u16>,
+
+    /// save file to load
+    #[arg(long)]
+    pub load: Option<PathBuf>,
+
+    /// journal to replay on a new game with the journal's seed, reproducing the game it was
+    /// saved with
+    #[arg(long, conflicts_with_all = ["seed", "load"])]
+    pub replay: Option<PathBuf>,
+
+    /// scenario file to start a new game from, see assets/scenarios
+    #[arg(long, conflicts_with_all = ["seed", "load", "replay"])]
+    pub scenario: Option<PathBuf>,
+
+    /// snapshot the surface to this recording every --record-interval ticks, starting another
+    /// game replaces it
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+
+    /// ticks between recorded snapshots
+    #[arg(long, default_value_t = 240)]
+    pub record_interval: u64,
+
+    /// watch a recording instead of playing
+    #[arg(long, conflicts_with_all = ["headless", "seed", "load", "replay", "scenario"])]
+    pub view: Option<PathBuf>,
+
+    /// queue agent commands and apply them in port order once per tick
+    #[arg(long)]
+    pub lockstep: bool,
+
+    /// milliseconds between ticks, 0 runs as fast as possible
+    #[arg(long)]
+    pub tick_millis: Option<u64>,
+
+    /// milliseconds an agent waits before replying to a tcp command
+    #[arg(long)]
+    pub command_latency_millis: Option<u64>,
+
+    /// port of the HUD, other agents are assigned the ports after it
+    #[arg(long, default_value_t = crate::tcp::DEFAULT_PORT_BASE)]
+    pub port_base: usize,
+
+    /// also serve every agent on this port, with commands and replies prefixed by the agent's
+    /// port, e.g. `3340 MOVE;`
+    #[arg(long)]
+    pub mux_port: Option<usize>,
+
+    /// accept cheats on this port, e.g. `TICK 1000;`, the same as the console opened with [`]
+    #[arg(long)]
+    pub cheat_port: Option<usize>,
+
+    /// directory for save files and logs, overrides TEXAFORM_DATA
+    #[arg(long)]
+    pub data_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// write a save as pretty printed json, upgraded to the current save schema
+    Export {
+        /// save file to read
+        save: PathBuf,
+        /// json file to write
+        json: PathBuf,
+    },
+    /// write json, e.g. from export, as a save that can be loaded
+    Import {
+        /// json file to read
+        json: PathBuf,
+        /// save file to write
+        save: PathBuf,
+    },
+}
+
+impl Command {
+    pub fn run(&self) -> AppResult<()> {
+        match self {
+            Command::Export { save, json } => {
+                SurfaceState::export(save, json)?;
+                println!("exported {} to {}", save.display(), json.display());
+            }
+            Command::Import { json, save } => {
+                SurfaceState::import(json, save)?;
+                println!("imported {} to {}", json.display(), save.display());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+//! blocking versions of the clients, each owns a small runtime so they must not be used from
+//! inside an async context
+
+use tokio::runtime::{Builder, Runtime};
+
+use super::{
+    AgentListing, Area, ClientResult, DogStat, FabricatorStat, LaserCutterStat, PowerStat,
+    ResearchListing, SmelterStat, SpeedStat,
+};
+use crate::agents::Topic;
+use crate::agents::dog::{CardinalDirection, Direction};
+use crate::entities::Entity;
+use crate::speed::Preset;
+use crate::tech_tree::TechKind;
+
+pub struct Connection {
+    inner: super::Connection,
+    runtime: Runtime,
+}
+
+impl Connection {
+    pub fn connect(port: usize) -> ClientResult<Connection> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let inner = runtime.block_on(super::Connection::connect(port))?;
+        Ok(Connection { inner, runtime })
+    }
+
+    pub fn port(&self) -> usize {
+        self.inner.port()
+    }
+
+    pub fn send(&mut self, command: &str) -> ClientResult<String> {
+        self.runtime.block_on(self.inner.send(command))
+    }
+
+    pub fn command(&mut self, command: &str) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.command(command))
+    }
+
+    pub fn query<T: std::str::FromStr<Err = ()>>(&mut self, command: &str) -> ClientResult<T> {
+        self.runtime.block_on(self.inner.query(command))
+    }
+
+    pub fn subscribe(&mut self, topic: Topic) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.subscribe(topic))
+    }
+
+    pub fn unsubscribe(&mut self, topic: Topic) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.unsubscribe(topic))
+    }
+
+    pub fn take_events(&mut self) -> Vec<Topic> {
+        self.inner.take_events()
+    }
+
+    pub fn next_event(&mut self) -> ClientResult<Topic> {
+        self.runtime.block_on(self.inner.next_event())
+    }
+}
+
+pub struct DogClient {
+    inner: super::DogClient,
+    runtime: Runtime,
+}
+
+impl DogClient {
+    pub fn connect(port: usize) -> ClientResult<DogClient> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let inner = runtime.block_on(super::DogClient::connect(port))?;
+        Ok(DogClient { inner, runtime })
+    }
+
+    /// sends a raw command and returns the raw reply
+    pub fn send(&mut self, command: &str) -> ClientResult<String> {
+        self.runtime.block_on(self.inner.connection.send(command))
+    }
+
+    pub fn forward(&mut self) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.forward())
+    }
+
+    pub fn turn(&mut self, direction: Direction) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.turn(direction))
+    }
+
+    pub fn pick(&mut self, entity: Entity) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.pick(entity))
+    }
+
+    pub fn drop_payload(&mut self) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.drop_payload())
+    }
+
+    pub fn build(&mut self) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.build())
+    }
+
+    pub fn charge(&mut self) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.charge())
+    }
+
+    pub fn scan(&mut self) -> ClientResult<Area> {
+        self.runtime.block_on(self.inner.scan())
+    }
+
+    pub fn stat(&mut self) -> ClientResult<DogStat> {
+        self.runtime.block_on(self.inner.stat())
+    }
+}
+
+pub struct SmelterClient {
+    inner: super::SmelterClient,
+    runtime: Runtime,
+}
+
+impl SmelterClient {
+    pub fn connect(port: usize) -> ClientResult<SmelterClient> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let inner = runtime.block_on(super::SmelterClient::connect(port))?;
+        Ok(SmelterClient { inner, runtime })
+    }
+
+    /// sends a raw command and returns the raw reply
+    pub fn send(&mut self, command: &str) -> ClientResult<String> {
+        self.runtime.block_on(self.inner.connection.send(command))
+    }
+
+    pub fn power(&mut self) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.power())
+    }
+
+    pub fn stat(&mut self) -> ClientResult<SmelterStat> {
+        self.runtime.block_on(self.inner.stat())
+    }
+}
+
+pub struct LaserCutterClient {
+    inner: super::LaserCutterClient,
+    runtime: Runtime,
+}
+
+impl LaserCutterClient {
+    pub fn connect(port: usize) -> ClientResult<LaserCutterClient> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let inner = runtime.block_on(super::LaserCutterClient::connect(port))?;
+        Ok(LaserCutterClient { inner, runtime })
+    }
+
+    /// sends a raw command and returns the raw reply
+    pub fn send(&mut self, command: &str) -> ClientResult<String> {
+        self.runtime.block_on(self.inner.connection.send(command))
+    }
+
+    pub fn power(&mut self) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.power())
+    }
+
+    pub fn move_laser(&mut self, direction: CardinalDirection) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.move_laser(direction))
+    }
+
+    pub fn load(&mut self, entity: Entity) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.load(entity))
+    }
+
+    pub fn pick(&mut self, entity: Entity) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.pick(entity))
+    }
+
+    pub fn stat(&mut self) -> ClientResult<LaserCutterStat> {
+        self.runtime.block_on(self.inner.stat())
+    }
+}
+
+pub struct FabricatorClient {
+    inner: super::FabricatorClient,
+    runtime: Runtime,
+}
+
+impl FabricatorClient {
+    pub fn connect(port: usize) -> ClientResult<FabricatorClient> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let inner = runtime.block_on(super::FabricatorClient::connect(port))?;
+        Ok(FabricatorClient { inner, runtime })
+    }
+
+    /// sends a raw command and returns the raw reply
+    pub fn send(&mut self, command: &str) -> ClientResult<String> {
+        self.runtime.block_on(self.inner.connection.send(command))
+    }
+
+    pub fn make(&mut self, entity: Entity) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.make(entity))
+    }
+
+    pub fn research(&mut self) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.research())
+    }
+
+    pub fn stat(&mut self) -> ClientResult<FabricatorStat> {
+        self.runtime.block_on(self.inner.stat())
+    }
+}
+
+pub struct HudClient {
+    inner: super::HudClient,
+    runtime: Runtime,
+}
+
+impl HudClient {
+    pub fn connect(port: usize) -> ClientResult<HudClient> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let inner = runtime.block_on(super::HudClient::connect(port))?;
+        Ok(HudClient { inner, runtime })
+    }
+
+    /// sends a raw command and returns the raw reply
+    pub fn send(&mut self, command: &str) -> ClientResult<String> {
+        self.runtime.block_on(self.inner.connection.send(command))
+    }
+
+    pub fn power(&mut self) -> ClientResult<PowerStat> {
+        self.runtime.block_on(self.inner.power())
+    }
+
+    pub fn speed(&mut self) -> ClientResult<SpeedStat> {
+        self.runtime.block_on(self.inner.speed())
+    }
+
+    pub fn set_speed(&mut self, preset: Preset) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.set_speed(preset))
+    }
+
+    pub fn step(&mut self, ticks: u64) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.step(ticks))
+    }
+
+    pub fn next_agent(&mut self) -> ClientResult<AgentListing> {
+        self.runtime.block_on(self.inner.next_agent())
+    }
+
+    pub fn agents(&mut self) -> ClientResult<Vec<AgentListing>> {
+        self.runtime.block_on(self.inner.agents())
+    }
+
+    pub fn next_research(&mut self) -> ClientResult<ResearchListing> {
+        self.runtime.block_on(self.inner.next_research())
+    }
+
+    pub fn research(&mut self, kind: TechKind) -> ClientResult<()> {
+        self.runtime.block_on(self.inner.research(kind))
+    }
+}
@@ -0,0 +1,643 @@
+//! typed clients for the agents' semicolon protocol
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), texaform::client::ClientError> {
+//! use texaform::client::DogClient;
+//!
+//! let mut dog = DogClient::connect(3335).await?;
+//! dog.forward().await?;
+//! let stat = dog.stat().await?;
+//! println!("{} {} {}%", stat.position, stat.facing, stat.battery);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`blocking`] has the same clients for bots without an async runtime
+
+pub mod blocking;
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use bytes::BytesMut;
+use ratatui::layout::Position;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::agents::Topic;
+use crate::agents::dog::{CardinalDirection, Direction};
+use crate::entities::Entity;
+use crate::speed::Preset;
+use crate::tcp::{self, MAX_COMMAND_LEN};
+use crate::tech_tree::TechKind;
+
+const HOST: &str = "127.0.0.1";
+const DELIMITER: u8 = b';';
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("connection error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("connection closed")]
+    Closed,
+    /// the agent replied with `ERRR message`
+    #[error("{0}")]
+    Agent(String),
+    #[error("unexpected reply to {command}: {reply}")]
+    UnexpectedReply { command: String, reply: String },
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
+
+/// a connection to one agent's port, replies are read in the order the commands were sent
+pub struct Connection {
+    port: usize,
+    stream: TcpStream,
+    buf: BytesMut,
+    events: VecDeque<Topic>,
+}
+
+impl Connection {
+    pub async fn connect(port: usize) -> ClientResult<Connection> {
+        let stream = TcpStream::connect(format!("{HOST}:{port}")).await?;
+        stream.set_nodelay(true)?;
+        Ok(Connection {
+            port,
+            stream,
+            buf: BytesMut::with_capacity(MAX_COMMAND_LEN),
+            events: VecDeque::new(),
+        })
+    }
+
+    pub fn port(&self) -> usize {
+        self.port
+    }
+
+    /// sends a raw command and returns the raw reply, `ERRR` replies are returned as text
+    pub async fn send(&mut self, command: &str) -> ClientResult<String> {
+        self.stream
+            .write_all(format!("{command};").as_bytes())
+            .await?;
+        loop {
+            let frame = self.read_frame().await?;
+            match parse_event(&frame) {
+                Some(topic) => self.events.push_back(topic),
+                None => return Ok(frame),
+            }
+        }
+    }
+
+    /// sends a command that replies `OKAY`
+    pub async fn command(&mut self, command: &str) -> ClientResult<()> {
+        let reply = self.send(command).await?;
+        parse_okay(command, &reply)
+    }
+
+    /// sends a command and parses its reply
+    pub async fn query<T: FromStr<Err = ()>>(&mut self, command: &str) -> ClientResult<T> {
+        let reply = self.send(command).await?;
+        parse_reply(command, &reply)
+    }
+
+    pub async fn subscribe(&mut self, topic: Topic) -> ClientResult<()> {
+        self.command(&format!("SUBS {topic}")).await
+    }
+
+    pub async fn unsubscribe(&mut self, topic: Topic) -> ClientResult<()> {
+        self.command(&format!("UNSB {topic}")).await
+    }
+
+    /// events received so far, without waiting for new ones
+    pub fn take_events(&mut self) -> Vec<Topic> {
+        self.events.drain(..).collect()
+    }
+
+    /// waits for the next event of a subscribed topic
+    pub async fn next_event(&mut self) -> ClientResult<Topic> {
+        if let Some(topic) = self.events.pop_front() {
+            return Ok(topic);
+        }
+        let frame = self.read_frame().await?;
+        parse_event(&frame).ok_or(ClientError::UnexpectedReply {
+            command: "SUBS".to_string(),
+            reply: frame,
+        })
+    }
+
+    async fn read_frame(&mut self) -> ClientResult<String> {
+        loop {
+            if let Some(frame) = tcp::split_frame(&mut self.buf, DELIMITER) {
+                return Ok(String::from_utf8_lossy(&frame).into_owned());
+            }
+            if self.stream.read_buf(&mut self.buf).await? == 0 {
+                return Err(ClientError::Closed);
+            }
+        }
+    }
+}
+
+fn parse_event(frame: &str) -> Option<Topic> {
+    let topic = frame.strip_prefix("EVNT ")?;
+    Topic::from_str(topic).ok()
+}
+
+fn agent_error(reply: &str) -> Option<ClientError> {
+    // commands an agent could not parse are `ERRR: message`
+    reply
+        .strip_prefix("ERRR")
+        .map(|msg| msg.strip_prefix(':').unwrap_or(msg))
+        .map(|msg| ClientError::Agent(msg.trim_start().to_string()))
+}
+
+fn unexpected(command: &str, reply: &str) -> ClientError {
+    agent_error(reply).unwrap_or_else(|| ClientError::UnexpectedReply {
+        command: command.to_string(),
+        reply: reply.to_string(),
+    })
+}
+
+fn parse_okay(command: &str, reply: &str) -> ClientResult<()> {
+    if reply == "OKAY" {
+        Ok(())
+    } else {
+        Err(unexpected(command, reply))
+    }
+}
+
+fn parse_reply<T: FromStr<Err = ()>>(command: &str, reply: &str) -> ClientResult<T> {
+    T::from_str(reply).map_err(|_| unexpected(command, reply))
+}
+
+/// buffer contents, `_` is an empty buffer
+fn parse_buffer(text: &str) -> Vec<char> {
+    if text == "_" {
+        vec![]
+    } else {
+        text.chars().collect()
+    }
+}
+
+fn parse_facing(text: &str) -> Option<CardinalDirection> {
+    match text {
+        "N" => Some(CardinalDirection::North),
+        "S" => Some(CardinalDirection::South),
+        "E" => Some(CardinalDirection::East),
+        "W" => Some(CardinalDirection::West),
+        _ => None,
+    }
+}
+
+/// `STAT 10 20 E 85% _`
+#[derive(Debug, Clone, Copy)]
+pub struct DogStat {
+    pub position: Position,
+    pub facing: CardinalDirection,
+    /// percent of the maximum charge
+    pub battery: usize,
+    pub payload: Option<char>,
+}
+
+impl FromStr for DogStat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<DogStat, ()> {
+        let parts: Vec<&str> = s.split(' ').collect();
+        let ["STAT", x, y, facing, battery, payload] = parts[..] else {
+            return Err(());
+        };
+        Ok(DogStat {
+            position: Position {
+                x: x.parse().map_err(|_| ())?,
+                y: y.parse().map_err(|_| ())?,
+            },
+            facing: parse_facing(facing).ok_or(())?,
+            battery: battery
+                .strip_suffix('%')
+                .and_then(|b| b.parse().ok())
+                .ok_or(())?,
+            payload: parse_buffer(payload).first().copied(),
+        })
+    }
+}
+
+/// `AREA I..`, the three cells in front of the dog nearest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Area(pub [char; 3]);
+
+impl FromStr for Area {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Area, ()> {
+        let area: Vec<char> = s.strip_prefix("AREA ").ok_or(())?.chars().collect();
+        Ok(Area(area.try_into().map_err(|_| ())?))
+    }
+}
+
+/// `STAT 250 I _`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmelterStat {
+    pub temprature: usize,
+    pub buffer_in: Vec<char>,
+    pub buffer_out: Vec<char>,
+}
+
+impl FromStr for SmelterStat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<SmelterStat, ()> {
+        let parts: Vec<&str> = s.split(' ').collect();
+        let ["STAT", temprature, buffer_in, buffer_out] = parts[..] else {
+            return Err(());
+        };
+        Ok(SmelterStat {
+            temprature: temprature.parse().map_err(|_| ())?,
+            buffer_in: parse_buffer(buffer_in),
+            buffer_out: parse_buffer(buffer_out),
+        })
+    }
+}
+
+/// `STAT F _`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaserCutterStat {
+    pub buffer_in: Vec<char>,
+    pub buffer_out: Vec<char>,
+}
+
+impl FromStr for LaserCutterStat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<LaserCutterStat, ()> {
+        let parts: Vec<&str> = s.split(' ').collect();
+        let ["STAT", buffer_in, buffer_out] = parts[..] else {
+            return Err(());
+        };
+        Ok(LaserCutterStat {
+            buffer_in: parse_buffer(buffer_in),
+            buffer_out: parse_buffer(buffer_out),
+        })
+    }
+}
+
+/// `STAT IIC`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FabricatorStat {
+    pub content: Vec<char>,
+}
+
+impl FromStr for FabricatorStat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<FabricatorStat, ()> {
+        let content = s.strip_prefix("STAT ").ok_or(())?;
+        Ok(FabricatorStat {
+            content: parse_buffer(content),
+        })
+    }
+}
+
+/// `100 200 300000 400000`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerStat {
+    pub production: usize,
+    pub consumption: usize,
+    pub stored: usize,
+    pub capacity: usize,
+}
+
+impl FromStr for PowerStat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<PowerStat, ()> {
+        let parts: Vec<usize> = s
+            .split(' ')
+            .map(|part| part.parse().map_err(|_| ()))
+            .collect::<Result<_, ()>>()?;
+        let [production, consumption, stored, capacity] = parts[..] else {
+            return Err(());
+        };
+        Ok(PowerStat {
+            production,
+            consumption,
+            stored,
+            capacity,
+        })
+    }
+}
+
+/// `1X 250 100`, speed is `None` when the settings do not match a preset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeedStat {
+    pub speed: Option<Preset>,
+    pub tick_millis: u64,
+    pub latency_millis: u64,
+}
+
+impl FromStr for SpeedStat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<SpeedStat, ()> {
+        let parts: Vec<&str> = s.split(' ').collect();
+        let [speed, tick, latency] = parts[..] else {
+            return Err(());
+        };
+        Ok(SpeedStat {
+            speed: Preset::from_str(speed).ok(),
+            tick_millis: tick.parse().map_err(|_| ())?,
+            latency_millis: latency.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+/// `3335 DOG`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentListing {
+    pub port: usize,
+    pub kind: String,
+}
+
+impl FromStr for AgentListing {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<AgentListing, ()> {
+        let (port, kind) = s.split_once(' ').ok_or(())?;
+        Ok(AgentListing {
+            port: port.parse().map_err(|_| ())?,
+            kind: kind.to_string(),
+        })
+    }
+}
+
+/// `SOLAR_PANNEL UNLOCKED 1/2`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResearchListing {
+    pub kind: TechKind,
+    pub status: String,
+    pub numerator: usize,
+    pub denominator: usize,
+}
+
+impl FromStr for ResearchListing {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<ResearchListing, ()> {
+        let parts: Vec<&str> = s.split(' ').collect();
+        let [kind, status, progress] = parts[..] else {
+            return Err(());
+        };
+        let (numerator, denominator) = progress.split_once('/').ok_or(())?;
+        Ok(ResearchListing {
+            kind: TechKind::from_str(kind).map_err(|_| ())?,
+            status: status.to_string(),
+            numerator: numerator.parse().map_err(|_| ())?,
+            denominator: denominator.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+pub struct DogClient {
+    pub connection: Connection,
+}
+
+impl DogClient {
+    pub async fn connect(port: usize) -> ClientResult<DogClient> {
+        Ok(DogClient {
+            connection: Connection::connect(port).await?,
+        })
+    }
+
+    /// `MOVE`
+    pub async fn forward(&mut self) -> ClientResult<()> {
+        self.connection.command("MOVE").await
+    }
+
+    /// `TURN L` or `TURN R`
+    pub async fn turn(&mut self, direction: Direction) -> ClientResult<()> {
+        match direction {
+            Direction::L => self.connection.command("TURN L").await,
+            Direction::R => self.connection.command("TURN R").await,
+        }
+    }
+
+    pub async fn pick(&mut self, entity: Entity) -> ClientResult<()> {
+        self.connection
+            .command(&format!("PICK {}", entity.character()))
+            .await
+    }
+
+    /// `DROP`
+    pub async fn drop_payload(&mut self) -> ClientResult<()> {
+        self.connection.command("DROP").await
+    }
+
+    /// `BULD`
+    pub async fn build(&mut self) -> ClientResult<()> {
+        self.connection.command("BULD").await
+    }
+
+    /// `CHRG`
+    pub async fn charge(&mut self) -> ClientResult<()> {
+        self.connection.command("CHRG").await
+    }
+
+    pub async fn scan(&mut self) -> ClientResult<Area> {
+        self.connection.query("SCAN").await
+    }
+
+    pub async fn stat(&mut self) -> ClientResult<DogStat> {
+        self.connection.query("STAT").await
+    }
+}
+
+pub struct SmelterClient {
+    pub connection: Connection,
+}
+
+impl SmelterClient {
+    pub async fn connect(port: usize) -> ClientResult<SmelterClient> {
+        Ok(SmelterClient {
+            connection: Connection::connect(port).await?,
+        })
+    }
+
+    /// `POWR`, toggles the smelter on and off
+    pub async fn power(&mut self) -> ClientResult<()> {
+        self.connection.command("POWR").await
+    }
+
+    pub async fn stat(&mut self) -> ClientResult<SmelterStat> {
+        self.connection.query("STAT").await
+    }
+}
+
+pub struct LaserCutterClient {
+    pub connection: Connection,
+}
+
+impl LaserCutterClient {
+    pub async fn connect(port: usize) -> ClientResult<LaserCutterClient> {
+        Ok(LaserCutterClient {
+            connection: Connection::connect(port).await?,
+        })
+    }
+
+    /// `POWR`, toggles the laser on and off
+    pub async fn power(&mut self) -> ClientResult<()> {
+        self.connection.command("POWR").await
+    }
+
+    /// `MVXP`, `MVXN`, `MVYP` or `MVYN`, the y servo counts down the screen so South is `MVYP`
+    pub async fn move_laser(&mut self, direction: CardinalDirection) -> ClientResult<()> {
+        let command = match direction {
+            CardinalDirection::East => "MVXP",
+            CardinalDirection::West => "MVXN",
+            CardinalDirection::South => "MVYP",
+            CardinalDirection::North => "MVYN",
+        };
+        self.connection.command(command).await
+    }
+
+    pub async fn load(&mut self, entity: Entity) -> ClientResult<()> {
+        self.connection
+            .command(&format!("LOAD {}", entity.character()))
+            .await
+    }
+
+    pub async fn pick(&mut self, entity: Entity) -> ClientResult<()> {
+        self.connection
+            .command(&format!("PICK {}", entity.character()))
+            .await
+    }
+
+    pub async fn stat(&mut self) -> ClientResult<LaserCutterStat> {
+        self.connection.query("STAT").await
+    }
+}
+
+pub struct FabricatorClient {
+    pub connection: Connection,
+}
+
+impl FabricatorClient {
+    pub async fn connect(port: usize) -> ClientResult<FabricatorClient> {
+        Ok(FabricatorClient {
+            connection: Connection::connect(port).await?,
+        })
+    }
+
+    pub async fn make(&mut self, entity: Entity) -> ClientResult<()> {
+        self.connection
+            .command(&format!("MAKE {}", entity.character()))
+            .await
+    }
+
+    /// `RESR`, spends the buffer on the active research
+    pub async fn research(&mut self) -> ClientResult<()> {
+        self.connection.command("RESR").await
+    }
+
+    pub async fn stat(&mut self) -> ClientResult<FabricatorStat> {
+        self.connection.query("STAT").await
+    }
+}
+
+pub struct HudClient {
+    pub connection: Connection,
+}
+
+impl HudClient {
+    pub async fn connect(port: usize) -> ClientResult<HudClient> {
+        Ok(HudClient {
+            connection: Connection::connect(port).await?,
+        })
+    }
+
+    /// `STAT POWR`
+    pub async fn power(&mut self) -> ClientResult<PowerStat> {
+        self.connection.query("STAT POWR").await
+    }
+
+    /// `STAT SPED`
+    pub async fn speed(&mut self) -> ClientResult<SpeedStat> {
+        self.connection.query("STAT SPED").await
+    }
+
+    /// `SPED 4X`
+    pub async fn set_speed(&mut self, preset: Preset) -> ClientResult<()> {
+        self.connection.command(&format!("SPED {preset}")).await
+    }
+
+    /// `STEP n`, only while paused
+    pub async fn step(&mut self, ticks: u64) -> ClientResult<()> {
+        self.connection.command(&format!("STEP {ticks}")).await
+    }
+
+    /// `LIST AGNT`, the next agent in the cycle
+    pub async fn next_agent(&mut self) -> ClientResult<AgentListing> {
+        self.connection.query("LIST AGNT").await
+    }
+
+    /// every agent, by cycling `LIST AGNT` until a port repeats
+    pub async fn agents(&mut self) -> ClientResult<Vec<AgentListing>> {
+        let mut agents: Vec<AgentListing> = vec![];
+        loop {
+            let agent = self.next_agent().await?;
+            if agents.iter().any(|a| a.port == agent.port) {
+                agents.sort_by_key(|a| a.port);
+                return Ok(agents);
+            }
+            agents.push(agent);
+        }
+    }
+
+    /// `LIST RESR`, the next research in the cycle
+    pub async fn next_research(&mut self) -> ClientResult<ResearchListing> {
+        self.connection.query("LIST RESR").await
+    }
+
+    /// `RESR SOLAR_PANNEL`, sets the active research
+    pub async fn research(&mut self, kind: TechKind) -> ClientResult<()> {
+        self.connection.command(&format!("RESR {kind}")).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_replies() {
+        let stat: DogStat = parse_reply("STAT", "STAT 10 20 E 85% _").unwrap();
+        assert_eq!(stat.position, Position { x: 10, y: 20 });
+        assert!(matches!(stat.facing, CardinalDirection::East));
+        assert_eq!(stat.battery, 85);
+        assert_eq!(stat.payload, None);
+
+        let area: Area = parse_reply("SCAN", "AREA I..").unwrap();
+        assert_eq!(area, Area(['I', '.', '.']));
+
+        let stat: SmelterStat = parse_reply("STAT", "STAT 250 I _").unwrap();
+        assert_eq!(stat.buffer_in, vec!['I']);
+        assert!(stat.buffer_out.is_empty());
+
+        let speed: SpeedStat = parse_reply("STAT SPED", "1X 250 100").unwrap();
+        assert_eq!(speed.speed, Some(Preset::Normal));
+
+        let research: ResearchListing =
+            parse_reply("LIST RESR", "SOLAR_PANNEL UNLOCKED 1/2").unwrap();
+        assert_eq!(research.kind, TechKind::SolarPannel);
+        assert_eq!(research.denominator, 2);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse_reply::<DogStat>("STAT", "ERRR low battery").unwrap_err();
+        assert!(matches!(error, ClientError::Agent(msg) if msg == "low battery"));
+        let error = parse_okay("JUMP", "ERRR: unknown command JUMP").unwrap_err();
+        assert!(matches!(error, ClientError::Agent(msg) if msg == "unknown command JUMP"));
+        let error = parse_okay("MOVE", "STAT 10 20 E 85% _").unwrap_err();
+        assert!(matches!(error, ClientError::UnexpectedReply { .. }));
+        assert_eq!(parse_event("EVNT SMELT_DONE"), Some(Topic::SMELT_DONE));
+        assert_eq!(parse_event("OKAY"), None);
+    }
+}
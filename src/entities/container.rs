@@ -121,6 +121,15 @@ impl EntityContainer {
         }
     }
 
+    /// the title in SCREAMING_SNAKE_CASE, e.g. `BUFFER_OUT` for a title of `BUFFER OUT`
+    pub fn name(&self) -> String {
+        self.title.to_uppercase().replace(' ', "_")
+    }
+
+    pub fn count(&self, entity: Entity) -> usize {
+        self.content.iter().filter(|e| **e == entity).count()
+    }
+
     pub fn placable(&self) -> bool {
         self.content.len() < self.capacity
     }
@@ -144,6 +144,8 @@ impl Shape {
     }
 
     // TODO better name than footprint
+    /// [`Shape::bordered_positions`] as grid indexes, wrapping around the east and west edges as
+    /// the dense generator always has
     pub fn grid_footprint(&self, grid_pos: &Position, grid_width: usize) -> HashSet<usize> {
         let mut footprint = HashSet::new();
         for offset in &self.positions {
@@ -169,6 +171,26 @@ impl Shape {
         footprint
     }
 
+    /// the shape's positions at `at` and the positions next to its edge
+    pub fn bordered_positions(&self, at: &Position) -> HashSet<Position> {
+        let mut bordered = HashSet::new();
+        for offset in &self.positions {
+            let pos = Position::new(at.x + offset.x, at.y + offset.y);
+            bordered.insert(pos);
+            if self.is_edge(offset) {
+                if let Some(y) = pos.y.checked_sub(1) {
+                    bordered.insert(Position::new(pos.x, y));
+                }
+                bordered.insert(Position::new(pos.x, pos.y + 1));
+                if let Some(x) = pos.x.checked_sub(1) {
+                    bordered.insert(Position::new(x, pos.y));
+                }
+                bordered.insert(Position::new(pos.x + 1, pos.y));
+            }
+        }
+        bordered
+    }
+
     pub fn jitter_edge(&mut self, rng: &mut ChaCha8Rng) {
         let edge = self.edge();
         for pos in edge {
@@ -1,15 +1,20 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use crossterm::event::{Event as CrosstermEvent, KeyEvent, MouseEvent};
 use futures::{FutureExt, StreamExt};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{Interval, MissedTickBehavior};
 
+use crate::agents::Response;
 use crate::app::AppResult;
+use crate::speed;
 use tracing::*;
 
 /// Terminal events.
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub enum Event {
     /// Terminal tick.
     Tick,
@@ -22,7 +27,10 @@ pub enum Event {
     /// dog client address
     AgentConnection(usize, SocketAddr),
     AgentDisconect(usize),
-    AgentCommand(usize, String),
+    /// command for the agent at a port and where to send its reply
+    AgentCommand(usize, String, oneshot::Sender<Response>),
+    /// command from the cheat port and where to send its reply
+    Cheat(String, oneshot::Sender<Response>),
 }
 
 /// Terminal event handler.
@@ -35,26 +43,29 @@ pub struct EventHandler {
     receiver: mpsc::UnboundedReceiver<Event>,
     /// Event handler thread.
     handler: tokio::task::JoinHandle<()>,
+    /// Set while a tick is waiting in the channel.
+    tick_pending: Arc<AtomicBool>,
 }
 
 impl EventHandler {
     /// Constructs a new instance of [`EventHandler`].
-    pub fn new(tick_rate: u64) -> Self {
-        let tick_rate = Duration::from_millis(tick_rate);
+    pub fn new() -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
+        let tick_pending = Arc::new(AtomicBool::new(false));
         let _sender = sender.clone();
+        let _tick_pending = tick_pending.clone();
         let handler = tokio::spawn(async move {
             let mut reader = crossterm::event::EventStream::new();
-            let mut tick = tokio::time::interval(tick_rate);
+            let mut ticker = Ticker::new();
             loop {
-                let tick_delay = tick.tick();
+                let tick_delay = ticker.tick();
                 let crossterm_event = reader.next().fuse();
                 tokio::select! {
                   _ = _sender.closed() => {
                     break;
                   }
                   _ = tick_delay => {
-                    _sender.send(Event::Tick).unwrap();
+                    send_tick(&_sender, &_tick_pending);
                   }
                   Some(Ok(evt)) = crossterm_event => {
                     match evt {
@@ -87,17 +98,101 @@ impl EventHandler {
             sender,
             receiver,
             handler,
+            tick_pending,
+        }
+    }
+
+    /// Constructs an [`EventHandler`] that only emits ticks, for running without a terminal.
+    pub fn headless() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let tick_pending = Arc::new(AtomicBool::new(false));
+        let _sender = sender.clone();
+        let _tick_pending = tick_pending.clone();
+        let handler = tokio::spawn(async move {
+            let mut ticker = Ticker::new();
+            loop {
+                tokio::select! {
+                  _ = _sender.closed() => {
+                    break;
+                  }
+                  _ = ticker.tick() => {
+                    send_tick(&_sender, &_tick_pending);
+                  }
+                };
+            }
+        });
+        Self {
+            sender,
+            receiver,
+            handler,
+            tick_pending,
         }
     }
 
+    /// number of events waiting to be received
+    pub fn pending(&self) -> usize {
+        self.receiver.len()
+    }
+
     /// Receive the next event from the handler thread.
     ///
     /// This function will always block the current thread if
     /// there is no data available and it's possible for more data to be sent.
     pub async fn next(&mut self) -> AppResult<Event> {
-        self.receiver
+        let event = self
+            .receiver
             .recv()
             .await
-            .ok_or(Box::new(std::io::Error::other("This is an IO error")))
+            .ok_or(Box::new(std::io::Error::other("This is an IO error")))?;
+        if matches!(event, Event::Tick) {
+            self.tick_pending.store(false, Ordering::Relaxed);
+        }
+        Ok(event)
+    }
+}
+
+impl Default for EventHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// skips a tick while the previous one is still queued, so fast tick rates can not flood the
+/// event channel
+fn send_tick(sender: &mpsc::UnboundedSender<Event>, tick_pending: &AtomicBool) {
+    if !speed::is_paused() && !tick_pending.swap(true, Ordering::Relaxed) {
+        sender.send(Event::Tick).unwrap();
+    }
+}
+
+/// tokio interval that follows changes to [`speed::tick_rate`]
+struct Ticker {
+    rate: Duration,
+    interval: Interval,
+}
+
+impl Ticker {
+    fn new() -> Ticker {
+        let rate = speed::tick_rate();
+        Ticker {
+            rate,
+            interval: Ticker::interval(rate),
+        }
+    }
+
+    fn interval(rate: Duration) -> Interval {
+        // a rate of 0 means as fast as ticks are handled, see `send_tick`
+        let mut interval = tokio::time::interval(rate.max(Duration::from_millis(1)));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        interval
+    }
+
+    async fn tick(&mut self) {
+        let rate = speed::tick_rate();
+        if rate != self.rate {
+            self.rate = rate;
+            self.interval = Ticker::interval(rate);
+        }
+        self.interval.tick().await;
     }
 }
@@ -0,0 +1,255 @@
+use std::future::Future;
+use std::time::Duration;
+
+use ratatui::layout::Position;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+use crate::agents::Response;
+use crate::app::AppResult;
+use crate::entities::Entity;
+use crate::event::Event;
+use crate::surface::scenario::Scenario;
+use crate::surface::state::Seed;
+use crate::surface::{Power, Surface, generation};
+use crate::tech_tree::{TechKind, TechStatus};
+
+// drives a surface from a seed or a scenario without a terminal, for integration tests of the
+// server and of bots. a script is a command per line, with an optional expected reply:
+//   # comments and blank lines are skipped
+//   +2 TURN L         port relative to the HUD
+//   3335 MOVE => OKAY absolute port, fails the script on any other reply
+//   tick 10           advances 10 ticks
+// bots connect over loopback tcp with [`Harness::serve`], after which the world is asserted on
+// with [`Harness::cell`], [`Harness::buffer`], [`Harness::tech`] and [`Harness::power`]
+
+/// a surface and the events its agents' listeners send
+pub struct Harness {
+    pub surface: Surface,
+    events: UnboundedReceiver<Event>,
+    /// time between ticks while serving bots
+    pub tick_rate: Duration,
+}
+
+/// a line of a script
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Tick(u64),
+    Send {
+        port: usize,
+        command: String,
+        expected: Option<String>,
+    },
+}
+
+impl Step {
+    /// parses a line of a script, with relative ports counted from the HUD on `port_base`
+    pub fn parse(line: &str, port_base: usize) -> Result<Step, String> {
+        let (line, expected) = match line.split_once("=>") {
+            Some((line, expected)) => (line, Some(expected.trim().to_string())),
+            None => (line, None),
+        };
+        let (first, rest) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        let rest = rest.trim();
+        if first == "tick" {
+            if expected.is_some() {
+                return Err("ticks have no reply".to_string());
+            }
+            return match rest {
+                "" => Ok(Step::Tick(1)),
+                n => n
+                    .parse()
+                    .map(Step::Tick)
+                    .map_err(|_| format!("expected a number of ticks, got {n}")),
+            };
+        }
+        let port = match first.strip_prefix('+') {
+            Some(offset) => offset.parse().map(|offset: usize| port_base + offset),
+            None => first.parse(),
+        }
+        .map_err(|_| format!("expected a port or tick, got {first}"))?;
+        if rest.is_empty() {
+            return Err(format!("no command for port {port}"));
+        }
+        Ok(Step::Send {
+            port,
+            command: rest.to_string(),
+            expected,
+        })
+    }
+}
+
+impl Harness {
+    fn new(
+        build: impl FnOnce(mpsc::UnboundedSender<Event>) -> AppResult<Surface>,
+    ) -> AppResult<Harness> {
+        let (event_sender, events) = mpsc::unbounded_channel();
+        Ok(Harness {
+            surface: build(event_sender)?,
+            events,
+            tick_rate: Duration::from_millis(10),
+        })
+    }
+
+    /// a new game generated from `seed`, with agents on ports from [`crate::tcp::port_base`]
+    pub fn seed(seed: u64) -> AppResult<Harness> {
+        Harness::new(|event_sender| Ok(generation::new_game(event_sender, Seed::Manual(seed))?))
+    }
+
+    /// a new game started from the scenario in `json`
+    pub fn scenario(json: &str) -> AppResult<Harness> {
+        let scenario = Scenario::parse(json)?;
+        Harness::new(|event_sender| scenario.new_game(event_sender))
+    }
+
+    /// port of the HUD, the other agents follow in the order they were added
+    pub fn port_base(&self) -> usize {
+        self.surface
+            .agents
+            .keys()
+            .next()
+            .copied()
+            .unwrap_or(crate::tcp::port_base())
+    }
+
+    pub fn send(&mut self, port: usize, command: &str) -> Response {
+        self.surface.send(port, command)
+    }
+
+    pub fn tick(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            self.surface.tick();
+        }
+    }
+
+    /// runs every line of `script`, stopping at the first line that fails to parse or replies
+    /// other than expected. returns the replies to the commands
+    pub fn run(&mut self, script: &str) -> AppResult<Vec<Response>> {
+        let mut replies = vec![];
+        for (idx, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let step = Step::parse(line, self.port_base())
+                .map_err(|e| format!("line {}: {e}", idx + 1))?;
+            match step {
+                Step::Tick(ticks) => self.tick(ticks),
+                Step::Send {
+                    port,
+                    command,
+                    expected,
+                } => {
+                    let reply = self.send(port, &command);
+                    if let Some(expected) = expected
+                        && reply.text != expected
+                    {
+                        return Err(format!(
+                            "line {}: `{command}` on port {port} replied `{}`, expected `{expected}`",
+                            idx + 1,
+                            reply.text
+                        )
+                        .into());
+                    }
+                    replies.push(reply);
+                }
+            }
+        }
+        Ok(replies)
+    }
+
+    /// listens on every agent's port and ticks every [`Harness::tick_rate`] until `bot` finishes,
+    /// call [`crate::tcp::set_port_base`] before building the harness to move off the defaults
+    pub async fn serve<F>(&mut self, bot: F) -> F::Output
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.surface.listen();
+        let mut bot = tokio::spawn(bot);
+        let mut ticker = tokio::time::interval(self.tick_rate);
+        loop {
+            let event = tokio::select! {
+                output = &mut bot => return output.expect("bot finishes"),
+                _ = ticker.tick() => Event::Tick,
+                event = self.events.recv() => event.expect("the surface holds a sender"),
+            };
+            match event {
+                Event::Tick => {
+                    self.surface.resolve_queued_commands();
+                    self.surface.tick();
+                }
+                Event::AgentConnection(port, address) => {
+                    self.surface.set_agent_address(&port, Some(address));
+                }
+                Event::AgentDisconect(port) => self.surface.set_agent_address(&port, None),
+                Event::AgentCommand(port, command, reply) => {
+                    self.surface.receive_agent_command(port, command, reply);
+                }
+                Event::Cheat(command, reply) => {
+                    let _ = reply.send(self.surface.cheat(&command));
+                }
+                Event::Key(_) | Event::Mouse(_) | Event::Resize(_, _) => (),
+            }
+        }
+    }
+
+    /// the entity at `x` `y`, `None` off the surface
+    pub fn cell(&self, x: u16, y: u16) -> Option<Entity> {
+        self.surface
+            .grid
+            .get(&Position { x, y })
+            .map(|gent| gent.entity())
+    }
+
+    /// the content of the container named `container`, e.g. `BUFFER_OUT`, of the agent on `port`
+    pub fn buffer(&self, port: usize, container: &str) -> Option<Vec<Entity>> {
+        let agent = self.surface.get_agent(&port)?;
+        agent
+            .containers()
+            .into_iter()
+            .find(|c| c.name() == container)
+            .map(|c| c.content.clone())
+    }
+
+    pub fn tech(&self, kind: TechKind) -> Option<TechStatus> {
+        let tech_tree = &self.surface.game_state.tech_tree;
+        let idx = tech_tree.tech_kind_idx(kind)?;
+        tech_tree.get_tech_and_status(idx).map(|(_, status)| status)
+    }
+
+    pub fn power(&self) -> &Power {
+        &self.surface.power
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_step() {
+        let parse = |line| Step::parse(line, 3333);
+        assert_eq!(parse("tick"), Ok(Step::Tick(1)));
+        assert_eq!(parse("tick 10"), Ok(Step::Tick(10)));
+        assert_eq!(
+            parse("+2 TURN L"),
+            Ok(Step::Send {
+                port: 3335,
+                command: "TURN L".to_string(),
+                expected: None
+            })
+        );
+        assert_eq!(
+            parse("4000 MOVE => ERRR no power"),
+            Ok(Step::Send {
+                port: 4000,
+                command: "MOVE".to_string(),
+                expected: Some("ERRR no power".to_string())
+            })
+        );
+        assert!(parse("tick x").is_err());
+        assert!(parse("tick => OKAY").is_err());
+        assert!(parse("MOVE").is_err());
+        assert!(parse("+1").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::app::AppResult;
+use crate::cli::Cli;
+use crate::event::{Event, EventHandler};
+use crate::results::{self, Run, Scores};
+use crate::surface::journal::{self, Journal};
+use crate::surface::scenario::Scenario;
+use crate::surface::state::{Seed, SurfaceState};
+use crate::surface::{self, Surface};
+
+use tokio::sync::mpsc::UnboundedSender;
+
+/// builds the surface requested on the command line, defaulting to a new game with a random seed
+pub fn build_surface(cli: &Cli, event_sender: UnboundedSender<Event>) -> AppResult<Surface> {
+    if let Some(path) = &cli.load {
+        let state = SurfaceState::load(path)?;
+        Ok(state.into_surface(event_sender))
+    } else if let Some(path) = &cli.replay {
+        let file = Journal::load(path)?;
+        let (surface, mismatches) = journal::replay(&file, event_sender)?;
+        println!(
+            "replayed {} commands, {} replies differed",
+            file.entries.len(),
+            mismatches.len()
+        );
+        for mismatch in mismatches {
+            println!("  {mismatch}");
+        }
+        Ok(surface)
+    } else if let Some(path) = &cli.scenario {
+        Scenario::load(path)?.new_game(event_sender)
+    } else {
+        let seed = cli.seed.map(Seed::Manual).unwrap_or_default();
+        let world_size = cli.world_size.unwrap_or(surface::DEFAULT_WORLD_SIZE);
+        Ok(surface::generation::new_game_of_size(
+            event_sender,
+            seed,
+            world_size,
+        )?)
+    }
+}
+
+fn print_scores(scores: &Scores) {
+    println!("won {} after {} runs", scores.run.puzzle, scores.runs);
+    for histogram in scores.histograms.iter() {
+        println!(
+            "  {}: {}, #{} of {}",
+            histogram.metric,
+            histogram.metric.format(histogram.value),
+            histogram.rank,
+            histogram.runs
+        );
+    }
+}
+
+/// runs the surface without a terminal until interrupted
+pub async fn run(cli: &Cli) -> AppResult<()> {
+    let mut events = EventHandler::headless();
+    let mut surface = build_surface(cli, events.sender.clone())?;
+    surface.lockstep = cli.lockstep;
+    if let Some(path) = &cli.record {
+        surface.start_recording(path, cli.record_interval)?;
+    }
+    surface.listen();
+    tracing::info!(
+        "headless surface ready, seed: {}",
+        surface.game_state.stats.seed.value()
+    );
+    for (port, comms) in surface.agents.iter() {
+        println!("{} listening on port {port}", comms.entity);
+    }
+    if let Some(port) = cli.mux_port {
+        crate::mux::listen(port, events.sender.clone()).await?;
+        println!("multiplexed control listening on port {port}");
+    }
+    if let Some(port) = cli.cheat_port {
+        surface::cheat::listen(port, events.sender.clone()).await?;
+        println!("cheats listening on port {port}");
+    }
+
+    loop {
+        let event = tokio::select! {
+            event = events.next() => event?,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        match event {
+            Event::Tick => {
+                surface.resolve_queued_commands();
+                let won = surface.victory_stats.is_some();
+                surface.tick();
+                if !won && let Some(run) = Run::new(&surface) {
+                    match Scores::record(&results::path(), run) {
+                        Ok(scores) => print_scores(&scores),
+                        Err(e) => tracing::error!("failed to record results: {e}"),
+                    }
+                }
+            }
+            Event::AgentConnection(port, address) => {
+                surface.set_agent_address(&port, Some(address));
+            }
+            Event::AgentDisconect(port) => surface.set_agent_address(&port, None),
+            Event::AgentCommand(port, command, reply) => {
+                surface.receive_agent_command(port, command, reply);
+            }
+            Event::Cheat(command, reply) => {
+                let _ = reply.send(surface.cheat(&command));
+            }
+            Event::Key(_) | Event::Mouse(_) | Event::Resize(_, _) => (),
+        }
+    }
+    tracing::info!(
+        "headless shutdown at tick {}",
+        surface.game_state.stats.tick_count
+    );
+    Ok(())
+}
@@ -1,15 +1,16 @@
-use std::path::PathBuf;
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
 
-use crate::app::{App, AppResult, LoadingState};
-use crate::surface;
+use crate::app::{App, AppResult, DisplayPathBuf, LoadingState};
+use crate::surface::schema::SaveStats;
 use crate::surface::state::SurfaceState;
+use crate::ui::load_game::{SaveFileAction, SaveSort};
 use crate::widgets::HandleInput;
-use crate::widgets::list::Action;
+use crate::widgets::list::{Action, DoubleClickList};
+use crate::widgets::text_box::{Action as TextBoxAction, TextBox};
 use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
 use ratatui::layout::{Position, Margin};
 
-use crate::ui::Screen;
-
 pub fn load_selected_save_file(app: &mut App) {
     if let Some(path) = &app.save_files.selected().map(|x| x.inner.clone()) {
         load_save_file_cached(app, path)
@@ -41,22 +42,170 @@ pub fn load_save_file(app: &mut App, path: &PathBuf) {
     tracing::info!("post load");
 }
 
+/// orders `app.save_files` by `app.save_manager.sort`, keeping the selected save selected
+pub fn sort_save_files(app: &mut App) {
+    let selected = app.save_files.selected().cloned();
+    let mut paths = app.save_files.items().to_vec();
+    match app.save_manager.sort {
+        SaveSort::Name => paths.sort(),
+        SaveSort::Date => paths.sort_by_cached_key(|path| {
+            Reverse(path.inner.metadata().and_then(|m| m.modified()).ok())
+        }),
+        SaveSort::Playtime | SaveSort::Version => {
+            // saves not loaded yet only have their stats read, loading every save would block
+            // the screen
+            let stats = |path: &DisplayPathBuf| match app.save_file_cache.get(&path.inner) {
+                Some(LoadingState::Loaded(state)) => Some(SaveStats {
+                    version: state.game_state.stats.version,
+                    tick_count: state.game_state.stats.tick_count,
+                }),
+                _ => SurfaceState::load_stats(&path.inner).ok(),
+            };
+            if app.save_manager.sort == SaveSort::Playtime {
+                paths.sort_by_cached_key(|path| Reverse(stats(path).map(|s| s.tick_count)));
+            } else {
+                paths.sort_by_cached_key(|path| Reverse(stats(path).map(|s| s.version)));
+            }
+        }
+    }
+    app.save_files = DoubleClickList::default_style(paths);
+    if app.save_files.items().is_empty() {
+        return;
+    }
+    match selected {
+        Some(selected) if app.save_files.select_item(&selected) => (),
+        _ => app.save_files.select(0),
+    }
+}
+
+/// reads the save files from disk again, selecting `path`
+fn refresh_save_files(app: &mut App, path: Option<&Path>) {
+    app.fetch_save_files();
+    sort_save_files(app);
+    if let Some(path) = path {
+        app.save_files
+            .select_item(&DisplayPathBuf::from(path.to_path_buf()));
+    }
+    load_selected_save_file(app);
+}
+
+/// the first free `NAME_COPY`, `NAME_COPY_2`, ... next to `path`
+fn duplicate_path(path: &Path) -> PathBuf {
+    let name = DisplayPathBuf::from(path.to_path_buf()).to_string();
+    (1..)
+        .map(|n| match n {
+            1 => SurfaceState::path(&format!("{name}_COPY")),
+            n => SurfaceState::path(&format!("{name}_COPY_{n}")),
+        })
+        .find(|path| !path.exists())
+        .expect("some copy name is free")
+}
+
+/// a rename may not leave the data directory or hide the save
+fn check_save_name(name: &str) -> AppResult<()> {
+    if name.trim().is_empty() {
+        return Err("save name is empty".into());
+    }
+    if name.contains(['/', '\\']) || name.contains("..") {
+        return Err(format!("save name {name:?} may not contain '/', '\\' or '..'").into());
+    }
+    Ok(())
+}
+
+fn report(app: &mut App, result: AppResult<()>, done: String) {
+    app.save_manager.message = Some(match result {
+        Ok(()) => done,
+        Err(e) => {
+            tracing::error!("save file operation failed: {e}");
+            e.to_string()
+        }
+    });
+}
+
+fn handle_action_key_event(key_event: KeyEvent, app: &mut App, action: SaveFileAction) {
+    match action {
+        SaveFileAction::ConfirmDelete(path) => match key_event.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                let result = SurfaceState::delete(&path);
+                app.save_file_cache.remove(&path);
+                let name = DisplayPathBuf::from(path).to_string();
+                report(app, result, format!("deleted {name}"));
+                refresh_save_files(app, None);
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => (),
+            _ => app.save_manager.action = Some(SaveFileAction::ConfirmDelete(path)),
+        },
+        SaveFileAction::Rename(path, mut text_box) => {
+            if key_event.code == KeyCode::Esc {
+                return;
+            }
+            match text_box.handle_key_event(key_event) {
+                Some(TextBoxAction::Submit(name)) => {
+                    if let Err(e) = check_save_name(&name) {
+                        report(app, Err(e), String::new());
+                        app.save_manager.action = Some(SaveFileAction::Rename(path, text_box));
+                        return;
+                    }
+                    let new_path = SurfaceState::path(&name);
+                    let result = SurfaceState::rename(&path, &new_path);
+                    if result.is_ok()
+                        && let Some(state) = app.save_file_cache.remove(&path)
+                    {
+                        app.save_file_cache.insert(new_path.clone(), state);
+                    }
+                    report(app, result, format!("renamed to {name}"));
+                    refresh_save_files(app, Some(&new_path));
+                }
+                _ => app.save_manager.action = Some(SaveFileAction::Rename(path, text_box)),
+            }
+        }
+    }
+}
+
 pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    if let Some(action) = app.save_manager.action.take() {
+        handle_action_key_event(key_event, app, action);
+        return Ok(());
+    }
     match app.save_files.handle_key_event(key_event) {
         Some(Action::Select(path)) => load_save_file_cached(app, &path.inner),
         Some(Action::Choose(path)) => load(app, &path.inner).await,
         None => (),
     }
-    match key_event.code {
-        KeyCode::Esc => {
+    let selected = app.save_files.selected().map(|path| path.inner.clone());
+    match (key_event.code, selected) {
+        (KeyCode::Esc, _) => {
+            app.save_manager.message = None;
             app.set_screen(*app.previous_screen());
         }
+        (KeyCode::Char('d') | KeyCode::Char('D') | KeyCode::Delete, Some(path)) => {
+            app.save_manager.action = Some(SaveFileAction::ConfirmDelete(path));
+        }
+        (KeyCode::Char('r') | KeyCode::Char('R'), Some(path)) => {
+            let mut text_box = TextBox::new();
+            text_box.set_content(DisplayPathBuf::from(path.clone()).to_string());
+            app.save_manager.action = Some(SaveFileAction::Rename(path, text_box));
+        }
+        (KeyCode::Char('c') | KeyCode::Char('C'), Some(path)) => {
+            let copy = duplicate_path(&path);
+            let result = SurfaceState::duplicate(&path, &copy);
+            let name = DisplayPathBuf::from(copy.clone()).to_string();
+            report(app, result, format!("copied to {name}"));
+            refresh_save_files(app, Some(&copy));
+        }
+        (KeyCode::Char('s') | KeyCode::Char('S'), _) => {
+            app.save_manager.sort = app.save_manager.sort.next();
+            sort_save_files(app);
+        }
         _ => {}
     }
     Ok(())
 }
 
 pub async fn handle_mouse_events(event: MouseEvent, app: &mut App) -> AppResult<()> {
+    if app.save_manager.action.is_some() {
+        return Ok(());
+    }
     let pos = Position {
         x: event.column,
         y: event.row,
@@ -78,19 +227,7 @@ pub async fn handle_mouse_events(event: MouseEvent, app: &mut App) -> AppResult<
 async fn load(app: &mut App, path: &PathBuf) {
     if let Some(loading_state) = app.save_file_cache.remove(path) {
         match loading_state {
-            LoadingState::Loaded(state) => {
-                tracing::info!("HERE");
-                // TODO how to force agents to be dropped?
-                app.surface = surface::generation::empty(app.event_sender.clone());
-                // TODO the following comment avoids the port in use panic
-                // so its probably a dely issue
-                // need to implement a Comms drop such that it waits till the ports are free
-                // again?
-                tracing::info!("should be dropped?: {:?}", app.surface.agents);
-                app.surface = state.into_surface(app.event_sender.clone()).await;
-                //tracing::info!("surface: {:?}", app.surface);
-                app.set_screen(Screen::Surface);
-            }
+            LoadingState::Loaded(state) => app.load_game(*state),
             LoadingState::Failed(state) => {
                 app.save_file_cache
                     .insert(path.clone(), LoadingState::Failed(state));
@@ -1,7 +1,6 @@
 use crate::app::{App, AppResult};
 use crate::input::Screen;
-use crate::surface::state::Seed;
-use crate::surface::{self, AddEntityError};
+use crate::surface::{AddEntityError, WORLD_SIZES};
 use crate::widgets::HandleInput;
 use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
 use ratatui::layout::Position;
@@ -11,15 +10,13 @@ use crate::ui::main_menu::MainMenu;
 //async fn on_select(app: &mut App) -> AppResult<()> {
 async fn on_select(app: &mut App, screen: MainMenu) -> Result<(), AddEntityError> {
     match screen {
-        MainMenu::NewGame => {
-            // make sure random seed is new
-            if matches!(app.seed, Seed::Random(_)) {
-                app.seed = Seed::default();
+        MainMenu::Continue => {
+            if let Err(e) = app.continue_game() {
+                tracing::warn!("failed to continue: {e}");
             }
-            app.surface = surface::generation::manual(app.event_sender.clone(), app.seed).await;
-            surface::generation::init_starting_entities(&mut app.surface).await?;
-            app.set_screen(Screen::Surface)
         }
+        MainMenu::NewGame => app.new_game()?,
+        MainMenu::Scenarios => app.set_screen(Screen::Scenarios),
         MainMenu::LoadGame => app.set_screen(Screen::LoadGame),
         MainMenu::Settings => app.set_screen(Screen::Settings),
         MainMenu::Exit => app.quit(),
@@ -27,6 +24,16 @@ async fn on_select(app: &mut App, screen: MainMenu) -> Result<(), AddEntityError
     Ok(())
 }
 
+/// the closest of [`WORLD_SIZES`] that is larger, or smaller, than `current`
+fn next_world_size(current: u16, larger: bool) -> u16 {
+    let next = if larger {
+        WORLD_SIZES.into_iter().find(|size| current < *size)
+    } else {
+        WORLD_SIZES.into_iter().rev().find(|size| *size < current)
+    };
+    next.unwrap_or(current)
+}
+
 pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
     if let Some(screen) = app.main_menu.handle_key_event(key_event) {
         on_select(app, screen).await?;
@@ -38,6 +45,8 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<
             }
         }
         KeyCode::Delete | KeyCode::Backspace => app.seed.backspace(),
+        KeyCode::Right => app.world_size = next_world_size(app.world_size, true),
+        KeyCode::Left => app.world_size = next_world_size(app.world_size, false),
         _ => {}
     }
     Ok(())
@@ -56,3 +65,19 @@ pub async fn handle_mouse_events(event: MouseEvent, app: &mut App) -> AppResult<
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_world_size() {
+        assert_eq!(next_world_size(250, true), 500);
+        assert_eq!(next_world_size(500, false), 250);
+        assert_eq!(next_world_size(250, false), 250);
+        assert_eq!(next_world_size(4000, true), 4000);
+        // a size from the command line moves to the presets around it
+        assert_eq!(next_world_size(700, true), 1000);
+        assert_eq!(next_world_size(700, false), 500);
+    }
+}
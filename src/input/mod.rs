@@ -2,15 +2,21 @@ use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent,
 use ratatui::layout::Position;
 
 use crate::app::{App, AppResult};
-use crate::input::load_game::load_selected_save_file;
+use crate::autosave;
+use crate::input::load_game::{load_selected_save_file, sort_save_files};
 use crate::surface::generation;
+use crate::surface::scenario::Scenario;
 use crate::ui::Screen;
+use crate::ui::pause_menu::PauseMenu;
+use crate::widgets::list::DoubleClickList;
 
 mod documentation;
 mod load_game;
 mod main_menu;
 mod pause_menu;
+mod replay;
 mod save_game;
+mod scenarios;
 mod settings;
 mod surface;
 mod tech_tree;
@@ -24,7 +30,12 @@ impl Screen {
         app.previous_screen_button.button.is_hovered = false;
         match self {
             Screen::SaveGame | Screen::LoadGame => {
+                app.save_manager.action = None;
                 app.fetch_save_files();
+                sort_save_files(app);
+                if !app.save_files.items().is_empty() {
+                    app.save_files.select(0);
+                }
                 load_selected_save_file(app);
                 if let Some(file) = app.save_files.selected() {
                     app.save_screen_text_box.set_content(file.to_string());
@@ -32,6 +43,14 @@ impl Screen {
             }
             Screen::MainMenu => {
                 app.surface = generation::empty(app.event_sender.clone());
+                autosave::clear_emergency_save();
+            }
+            Screen::PauseMenu => PauseMenu::refresh_speed(&mut app.pause_menu),
+            Screen::Scenarios => {
+                app.scenarios = DoubleClickList::default_style(Scenario::available());
+                if !app.scenarios.items().is_empty() {
+                    app.scenarios.select(0);
+                }
             }
             _ => (),
         }
@@ -45,6 +64,10 @@ pub async fn handle_key_events(event: KeyEvent, app: &mut App) -> AppResult<()>
     {
         app.quit();
     }
+    if event.code == KeyCode::F(3) {
+        app.debug.visible = !app.debug.visible;
+        return Ok(());
+    }
     match app.screen() {
         Screen::MainMenu => main_menu::handle_key_events(event, app).await,
         Screen::PauseMenu => pause_menu::handle_key_events(event, app).await,
@@ -54,6 +77,8 @@ pub async fn handle_key_events(event: KeyEvent, app: &mut App) -> AppResult<()>
         Screen::Surface => surface::handle_key_events(event, app).await,
         Screen::Documentation => documentation::handle_key_events(event, app).await,
         Screen::TechTree => tech_tree::handle_key_events(event, app).await,
+        Screen::Replay => replay::handle_key_events(event, app).await,
+        Screen::Scenarios => scenarios::handle_key_events(event, app).await,
     }
 }
 
@@ -71,7 +96,8 @@ pub async fn handle_mouse_events(event: MouseEvent, app: &mut App) -> AppResult<
         | Screen::LoadGame
         | Screen::SaveGame
         | Screen::Documentation
-        | Screen::TechTree => match event.kind {
+        | Screen::TechTree
+        | Screen::Scenarios => match event.kind {
             Kind::Moved => {
                 app.previous_screen_button.button.is_hovered =
                     app.layout.previous_screen_button.contains(pos);
@@ -86,7 +112,7 @@ pub async fn handle_mouse_events(event: MouseEvent, app: &mut App) -> AppResult<
             }
             _ => (),
         },
-        Screen::MainMenu | Screen::Surface => (),
+        Screen::MainMenu | Screen::Surface | Screen::Replay => (),
     }
     match app.screen() {
         Screen::MainMenu => main_menu::handle_mouse_events(event, app).await,
@@ -97,5 +123,7 @@ pub async fn handle_mouse_events(event: MouseEvent, app: &mut App) -> AppResult<
         Screen::Surface => surface::handle_mouse_events(event, app).await,
         Screen::Documentation => documentation::handle_mouse_events(event, app).await,
         Screen::TechTree => tech_tree::handle_mouse_events(event, app).await,
+        Screen::Replay => replay::handle_mouse_events(event, app).await,
+        Screen::Scenarios => scenarios::handle_mouse_events(event, app).await,
     }
 }
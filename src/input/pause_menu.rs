@@ -1,4 +1,5 @@
 use crate::app::{App, AppResult};
+use crate::speed;
 use crate::ui::Screen;
 use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
 use ratatui::layout::Position;
@@ -13,11 +14,20 @@ fn on_select(app: &mut App, screen: PauseMenu) {
         PauseMenu::SaveGame => app.set_screen(Screen::SaveGame),
         PauseMenu::Documentation => app.set_screen(Screen::Documentation),
         PauseMenu::TechnologyTree => app.set_screen(Screen::TechTree),
+        PauseMenu::Speed => cycle_speed(app),
         PauseMenu::Settings => app.set_screen(Screen::Settings),
         PauseMenu::MainMenu => app.set_screen(Screen::MainMenu),
     }
 }
 
+fn cycle_speed(app: &mut App) {
+    let next = speed::Preset::current()
+        .unwrap_or(speed::Preset::Paused)
+        .next();
+    app.surface.set_speed(next);
+    PauseMenu::refresh_speed(&mut app.pause_menu);
+}
+
 pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
     if let Some(screen) = app.pause_menu.handle_key_event(key_event) {
         on_select(app, screen);
@@ -30,6 +40,7 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<
         KeyCode::Char('T') | KeyCode::Char('t') => {
             app.set_screen(Screen::TechTree);
         }
+        KeyCode::Char('S') | KeyCode::Char('s') => cycle_speed(app),
         _ => {}
     }
     Ok(())
@@ -0,0 +1,73 @@
+use crate::app::{App, AppResult};
+use crate::ui::Screen;
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Position;
+
+const PAN_STEP: i32 = 5;
+
+pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    if key_event.code == KeyCode::Esc {
+        app.viewer = None;
+        app.set_screen(Screen::MainMenu);
+        return Ok(());
+    }
+    let Some(viewer) = app.viewer.as_mut() else {
+        return Ok(());
+    };
+    match key_event.code {
+        KeyCode::Char(' ') => viewer.playing = !viewer.playing,
+        KeyCode::Char('+') | KeyCode::Char('=') => viewer.faster(),
+        KeyCode::Char('-') => viewer.slower(),
+        KeyCode::Char(',') | KeyCode::Char('<') => {
+            viewer.playing = false;
+            viewer.previous_snapshot();
+        }
+        KeyCode::Char('.') | KeyCode::Char('>') => {
+            viewer.playing = false;
+            viewer.next_snapshot();
+        }
+        KeyCode::Char('[') => {
+            viewer.playing = false;
+            viewer.previous_milestone();
+        }
+        KeyCode::Char(']') => {
+            viewer.playing = false;
+            viewer.next_milestone();
+        }
+        KeyCode::Char(x) if x.is_ascii_digit() => viewer.seek.push(x),
+        KeyCode::Backspace | KeyCode::Delete => {
+            viewer.seek.pop();
+        }
+        KeyCode::Enter => viewer.seek_typed(),
+        KeyCode::Home => viewer.seek(0),
+        KeyCode::End => viewer.seek(u64::MAX),
+        KeyCode::Left => viewer.pan(-PAN_STEP, 0),
+        KeyCode::Right => viewer.pan(PAN_STEP, 0),
+        KeyCode::Up => viewer.pan(0, -PAN_STEP),
+        KeyCode::Down => viewer.pan(0, PAN_STEP),
+        _ => (),
+    }
+    Ok(())
+}
+
+pub async fn handle_mouse_events(event: MouseEvent, app: &mut App) -> AppResult<()> {
+    let pos = Position {
+        x: event.column,
+        y: event.row,
+    };
+    let bar = app.layout.replay.bar;
+    if let Some(viewer) = app.viewer.as_mut() {
+        match event.kind {
+            // clicking or dragging along the timeline scrubs to that tick
+            MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left)
+                if bar.contains(pos) =>
+            {
+                viewer.seek(viewer.tick_at(bar, pos.x));
+            }
+            MouseEventKind::ScrollUp => viewer.pan(0, -1),
+            MouseEventKind::ScrollDown => viewer.pan(0, 1),
+            _ => (),
+        }
+    }
+    Ok(())
+}
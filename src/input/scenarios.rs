@@ -0,0 +1,39 @@
+use crate::app::{App, AppResult};
+use crate::surface::scenario::Scenario;
+use crate::widgets::HandleInput;
+use crate::widgets::list::Action;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::layout::{Margin, Position};
+
+fn start(app: &mut App, scenario: Scenario) {
+    if let Err(e) = app.new_scenario(&scenario) {
+        tracing::warn!("failed to start scenario {scenario}: {e}");
+    }
+}
+
+pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    if key_event.code == KeyCode::Esc {
+        app.set_screen(*app.previous_screen());
+        return Ok(());
+    }
+    if let Some(Action::Choose(scenario)) = app.scenarios.handle_key_event(key_event) {
+        start(app, scenario);
+    }
+    Ok(())
+}
+
+pub async fn handle_mouse_events(event: MouseEvent, app: &mut App) -> AppResult<()> {
+    let pos = Position {
+        x: event.column,
+        y: event.row,
+    };
+    if app.layout.previous_screen_button.contains(pos) {
+        app.scenarios.hover(None);
+        return Ok(());
+    }
+    let list = app.layout.scenarios.scenarios.inner(Margin::new(1, 1));
+    if let Some(Action::Choose(scenario)) = app.scenarios.handle_mouse(list, pos, event) {
+        start(app, scenario);
+    }
+    Ok(())
+}
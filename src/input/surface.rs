@@ -42,7 +42,32 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<
                     && let Some(Action::Submit(msg)) = comms.text_box.handle_key_event(key_event)
                 {
                     let port = comms.port;
-                    app.surface.update_agent_manual(&port, msg).await;
+                    app.surface.update_agent_manual(&port, msg);
+                }
+            }
+        },
+        InputMode::Console => match key_event.code {
+            KeyCode::Esc => app.input_mode = InputMode::Normal,
+            KeyCode::Up => {
+                let pcc = app.console.previous_command_counter.wrapping_add(1);
+                let command = app.console.log.previous_command(pcc);
+                if !command.is_empty() {
+                    app.console.text_box.set_content(command);
+                    app.console.previous_command_counter = pcc;
+                }
+            }
+            KeyCode::Down => {
+                let pcc = app.console.previous_command_counter.saturating_sub(1);
+                let command = app.console.log.previous_command(pcc);
+                app.console.text_box.set_content(command);
+                app.console.previous_command_counter = pcc;
+            }
+            _ => {
+                if let Some(Action::Submit(command)) =
+                    app.console.text_box.handle_key_event(key_event)
+                {
+                    app.cheat(&command);
+                    app.console.previous_command_counter = 0;
                 }
             }
         },
@@ -77,6 +102,14 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<
             KeyCode::Char('P') | KeyCode::Char('p') => {
                 app.surface.game_state.tutorial_state.previous();
             }
+            KeyCode::Char('`') => app.input_mode = InputMode::Console,
+            KeyCode::Char(' ') => app.surface.toggle_pause(),
+            KeyCode::Char('.') => {
+                app.surface.step(1);
+            }
+            KeyCode::Char('>') => {
+                app.surface.step(10);
+            }
             _ => {}
         },
     }
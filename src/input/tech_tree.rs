@@ -8,9 +8,9 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<
             app.set_screen(*app.previous_screen());
         }
         KeyCode::Enter => {
-            let tech_tree = &mut app.surface.game_state.tech_tree;
+            let selected = app.surface.game_state.tech_tree.selected_node;
             // TODO report error in UI
-            let _todo = tech_tree.set_research(tech_tree.selected_node);
+            let _todo = app.surface.select_research(selected);
         }
         _ => {}
     }
@@ -34,11 +34,10 @@ pub async fn handle_mouse_events(event: MouseEvent, app: &mut App) -> AppResult<
     {
         // TODO move selected_node elsewhere when we refactor/optimze rendering
         //let tech_tree = &mut app.surface.game_state.write().expect("TODO").tech_tree;
-        let tech_tree = &mut app.surface.game_state.tech_tree;
-        tech_tree.selected_node = idx;
+        app.surface.game_state.tech_tree.selected_node = idx;
         if app.tech_tree_double_click_tracker.clicked(idx) {
             // TODO report error in UI
-            let _todo = tech_tree.set_research(tech_tree.selected_node);
+            let _todo = app.surface.select_research(idx);
         }
     }
     Ok(())
@@ -0,0 +1,59 @@
+//! the texaform simulation and terminal ui
+//!
+//! the simulation can be driven without sockets or a terminal through [`surface::Surface::send`]
+
+/// Application.
+pub mod app;
+
+/// Periodic and emergency saves.
+pub mod autosave;
+
+/// Command line arguments.
+pub mod cli;
+
+/// Typed clients for the agents' tcp protocol.
+pub mod client;
+
+/// Terminal events handler.
+pub mod event;
+
+pub mod effects;
+/// Scripted games for integration tests.
+pub mod harness;
+/// Running without a terminal.
+pub mod headless;
+/// Widget renderer.
+pub mod ui;
+
+/// Terminal user interface.
+pub mod tui;
+
+/// Event handler.
+pub mod input;
+pub mod logging;
+
+//pub mod grid;
+pub mod surface;
+//pub mod generation;
+
+pub mod mux;
+/// Scores of won games, compared on the victory popup.
+pub mod results;
+pub mod speed;
+pub mod tcp;
+pub mod utils;
+
+pub mod agents;
+pub mod entities;
+
+pub mod puzzles;
+pub mod tech_tree;
+
+pub mod draw;
+pub mod theme;
+pub mod widgets;
+
+pub use app::AppResult;
+
+/// length of a tick at 1X speed, playtime and autosaves are counted in these
+pub const TICK_UPDATE_MILLS: u64 = 250;
@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
 use directories::ProjectDirs;
 use lazy_static::lazy_static;
@@ -17,12 +18,23 @@ lazy_static! {
     pub static ref LOG_FILE: String = format!("{}.log", env!("CARGO_PKG_NAME"));
 }
 
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// overrides the data directory, must be called before the first [`get_data_dir`] to take effect
+pub fn set_data_dir(path: PathBuf) {
+    if DATA_DIR_OVERRIDE.set(path).is_err() {
+        tracing::warn!("data directory already set");
+    }
+}
+
 fn project_directory() -> Option<ProjectDirs> {
     ProjectDirs::from("com", "jpostel", env!("CARGO_PKG_NAME"))
 }
 
 pub fn get_data_dir() -> PathBuf {
-    if let Some(s) = DATA_FOLDER.clone() {
+    if let Some(s) = DATA_DIR_OVERRIDE.get() {
+        s.clone()
+    } else if let Some(s) = DATA_FOLDER.clone() {
         s
     } else if let Some(proj_dirs) = project_directory() {
         proj_dirs.data_local_dir().to_path_buf()
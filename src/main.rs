@@ -12,62 +12,64 @@ use ratatui::{
 use std::{
     io::{self, stdout},
     panic::{set_hook, take_hook},
+    time::Duration,
 };
 
-/// Application.
-pub mod app;
-
-/// Terminal events handler.
-pub mod event;
-
-pub mod effects;
-/// Widget renderer.
-pub mod ui;
-
-/// Terminal user interface.
-pub mod tui;
-
-/// Event handler.
-pub mod input;
-pub mod logging;
-
-//pub mod grid;
-pub mod surface;
-//pub mod generation;
-
-pub mod tcp;
-pub mod utils;
-
-pub mod agents;
-pub mod entities;
-
-pub mod puzzles;
-pub mod tech_tree;
-
-pub mod draw;
-pub mod theme;
-pub mod widgets;
-
-use app::{App, AppResult, InputMode};
-use event::{Event, EventHandler};
-use input::{handle_key_events, handle_mouse_events};
-use logging::initialize_logging;
-use tui::Tui;
-use ui::AppLayout;
-
-const TICK_UPDATE_MILLS: u64 = 250;
+use clap::Parser;
+use texaform::app::{App, AppResult, InputMode};
+use texaform::cli::Cli;
+use texaform::event::{Event, EventHandler};
+use texaform::input::{handle_key_events, handle_mouse_events};
+use texaform::logging::{self, initialize_logging};
+use texaform::surface::cheat;
+use texaform::surface::journal::Journal;
+use texaform::surface::recording::Recording;
+use texaform::surface::scenario::Scenario;
+use texaform::surface::state::{Seed, SurfaceState};
+use texaform::tui::Tui;
+use texaform::ui::AppLayout;
+use texaform::{autosave, headless, mux, speed, tcp};
+
+// just over 60 fps
+const FRAME_MILLS: u64 = 16;
 
 #[tokio::main]
 async fn main() -> AppResult<()> {
-    init_panic_hook();
+    let cli = Cli::parse();
+    if let Some(data_dir) = &cli.data_dir {
+        logging::set_data_dir(data_dir.clone());
+    }
+    tcp::set_port_base(cli.port_base);
+    if let Some(millis) = cli.tick_millis {
+        speed::set_tick_millis(millis);
+    }
+    if let Some(millis) = cli.command_latency_millis {
+        speed::set_command_latency_millis(millis);
+    }
     initialize_logging()?;
+    if let Some(command) = &cli.command {
+        return command.run();
+    }
+    if cli.headless {
+        return headless::run(&cli).await;
+    }
+    // load before touching the terminal so a bad path is reported plainly
+    let save_state = cli.load.as_deref().map(SurfaceState::load).transpose()?;
+    let journal = cli.replay.as_deref().map(Journal::load).transpose()?;
+    let recording = cli.view.as_deref().map(Recording::load).transpose()?;
+    let scenario = cli.scenario.as_deref().map(Scenario::load).transpose()?;
+    init_panic_hook();
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stderr());
     let terminal = Terminal::new(backend)?;
-    // just over 60 fps
-    // tick() to understand how many ticks per second
-    let events = EventHandler::new(TICK_UPDATE_MILLS);
+    let events = EventHandler::new();
+    if let Some(port) = cli.mux_port {
+        mux::listen(port, events.sender.clone()).await?;
+    }
+    if let Some(port) = cli.cheat_port {
+        cheat::listen(port, events.sender.clone()).await?;
+    }
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
     // initalize ui locations
@@ -76,16 +78,44 @@ async fn main() -> AppResult<()> {
     // Create an application.
     let (width, height) = crossterm::terminal::size()?;
     let mut app = App::new(event_sender.clone(), width, height);
+    app.lockstep = cli.lockstep;
+    if let Some(world_size) = cli.world_size {
+        app.world_size = world_size;
+    }
+    app.record = cli.record.clone().map(|path| (path, cli.record_interval));
+    if let Some(recording) = recording {
+        app.view_recording(recording);
+    } else if let Some(state) = save_state {
+        app.load_game(state);
+    } else if let Some(file) = journal {
+        for mismatch in app.replay_game(&file)? {
+            tracing::warn!("replay mismatch: {mismatch}");
+        }
+    } else if let Some(scenario) = scenario {
+        app.new_scenario(&scenario)?;
+    } else if let Some(seed) = cli.seed {
+        app.seed = Seed::Manual(seed);
+        app.new_game()?;
+    }
 
     let mut last_frame_instant = std::time::Instant::now();
+    let mut redraw = true;
     // Start the main loop.
     while app.running {
-        app.prev_tick = last_frame_instant.elapsed();
-        last_frame_instant = std::time::Instant::now();
-        // Render the user interface.
-        tui.draw(&mut app)?;
+        // at fast tick rates only draw every FRAME_MILLS
+        if redraw || last_frame_instant.elapsed() >= Duration::from_millis(FRAME_MILLS) {
+            app.prev_tick = last_frame_instant.elapsed();
+            last_frame_instant = std::time::Instant::now();
+            // Render the user interface.
+            let started = std::time::Instant::now();
+            tui.draw(&mut app)?;
+            app.debug.record_frame(started.elapsed());
+        }
         // Handle events.
-        match tui.events.next().await? {
+        let event = tui.events.next().await?;
+        app.debug.queue_depth = tui.events.pending();
+        redraw = !matches!(event, Event::Tick);
+        match event {
             Event::Tick => app.tick(),
             Event::Key(event) => handle_key_events(event, &mut app).await?,
             Event::Mouse(event) => handle_mouse_events(event, &mut app).await?,
@@ -95,26 +125,19 @@ async fn main() -> AppResult<()> {
             // TODO seprate thread and update loop for agents?
             // currntly it would tui.draw for each event from each agent
             Event::AgentConnection(port, address) => {
-                if let Some(comms) = app.surface.agents.get_mut(&port) {
-                    comms.address = Some(address);
-                    if let Some(focused_port) = app.surface.focused_agent_port()
-                        && focused_port == port
-                    {
-                        app.input_mode = InputMode::Normal;
-                    }
-                } else {
-                    tracing::warn!("expected agent at port {port}");
+                app.surface.set_agent_address(&port, Some(address));
+                if app.surface.focused_agent_port() == Some(port)
+                    && matches!(app.input_mode, InputMode::Editing)
+                {
+                    app.input_mode = InputMode::Normal;
                 }
             }
-            Event::AgentDisconect(port) => {
-                if let Some(comms) = app.surface.agents.get_mut(&port) {
-                    comms.address = None;
-                } else {
-                    tracing::warn!("expected agent at port {port}");
-                }
+            Event::AgentDisconect(port) => app.surface.set_agent_address(&port, None),
+            Event::AgentCommand(port, command, reply) => {
+                app.surface.receive_agent_command(port, command, reply);
             }
-            Event::AgentCommand(port, command) => {
-                app.surface.update_agent_remote(&port, command).await;
+            Event::Cheat(command, reply) => {
+                let _ = reply.send(app.cheat(&command));
             }
         }
     }
@@ -130,6 +153,11 @@ fn init_panic_hook() {
     set_hook(Box::new(move |panic_info| {
         // intentionally ignore errors here since we're already in a panic
         let _ = restore_tui();
+        match autosave::write_emergency_save() {
+            Ok(Some(path)) => eprintln!("emergency save written to {}", path.display()),
+            Ok(None) => (),
+            Err(e) => eprintln!("failed to write emergency save: {e}"),
+        }
         original_hook(panic_info);
     }));
 }
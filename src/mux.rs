@@ -0,0 +1,206 @@
+use crate::AppResult;
+use crate::agents::{CommandError, Response};
+use crate::event::Event;
+use crate::tcp::{self, Connection, Incoming, Protocol, Read, Request};
+
+use bytes::BytesMut;
+use std::collections::HashMap;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+// multiplexed control port, every command is prefixed with the port of the agent it is for:
+//   `3340 MOVE;` -> `3340 OKAY;`
+//   `{"port": 3340, "command": "MOVE"}` -> `{"port": 3340, "reply": "OKAY"}` after `PROT JSON;`
+// commands for the same agent are applied in order, commands for different agents run concurrently
+
+/// binds the multiplexed control port and serves connections in the background
+pub async fn listen(port: usize, event_sender: UnboundedSender<Event>) -> AppResult<()> {
+    let listener = TcpListener::bind(format!("127.0.0.1:{port}")).await?;
+    tracing::info!("started multiplexed tcp listener on port {port}");
+    tokio::spawn(async move {
+        loop {
+            let (socket, addr) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    tracing::warn!("failed to accept connection on port {port}: {e}");
+                    continue;
+                }
+            };
+            tracing::info!("multiplexed peer address: {addr}");
+            let event_sender = event_sender.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_socket(socket, event_sender).await {
+                    tracing::warn!("error handling multiplexed socket: {e}");
+                }
+                tracing::info!("multiplexed peer disconnected: {addr}");
+            });
+        }
+    });
+    Ok(())
+}
+
+/// splits `3340 MOVE` into the port and the command
+fn parse_line(line: &str) -> Option<(usize, &str)> {
+    let (port, command) = line.trim().split_once(' ')?;
+    Some((port.parse().ok()?, command.trim_start()))
+}
+
+/// the agent's port and command, only `PROT` handshakes are valid without a port
+fn parse_request(protocol: Protocol, line: &str) -> Result<Request, CommandError> {
+    match (protocol, parse_line(line)) {
+        (Protocol::Text, Some((port, command))) => Ok(Request {
+            port: Some(port),
+            command: command.to_string(),
+        }),
+        (Protocol::Text, None) => protocol.parse_request(line),
+        (Protocol::Json, _) => protocol.parse_request(line),
+    }
+}
+
+/// a connection to the multiplexed port, replies from the agents' workers are written as events
+struct MuxConnection {
+    reader: OwnedReadHalf,
+    event_sender: UnboundedSender<Event>,
+    // one worker per agent so each agent's commands stay in order
+    workers: HashMap<usize, UnboundedSender<(String, Protocol)>>,
+    reply_sender: UnboundedSender<String>,
+    reply_receiver: UnboundedReceiver<String>,
+}
+
+impl Connection for MuxConnection {
+    type Event = String;
+
+    fn parse(&self, protocol: Protocol, line: &str) -> Result<Request, CommandError> {
+        parse_request(protocol, line)
+    }
+
+    async fn read(&mut self, buf: &mut BytesMut) -> AppResult<Read<String>> {
+        tokio::select! {
+            n = self.reader.read_buf(buf) => Ok(if n? == 0 { Read::Closed } else { Read::Bytes }),
+            // never closed, the connection keeps a sender
+            Some(reply) = self.reply_receiver.recv() => Ok(Read::Event(reply)),
+        }
+    }
+
+    async fn handle(
+        &mut self,
+        incoming: Incoming<String>,
+        protocol: Protocol,
+    ) -> AppResult<Option<String>> {
+        let request = match incoming {
+            Incoming::Request(request) => request,
+            Incoming::Event(reply) => return Ok(Some(reply)),
+        };
+        let Some(port) = request.port else {
+            let error = CommandError::InvalidRequest("expected `<port> <command>`".to_string());
+            return Ok(Some(protocol.encode(&Response::error(error), None)));
+        };
+        let worker = self.workers.entry(port).or_insert_with(|| {
+            spawn_worker(port, self.event_sender.clone(), self.reply_sender.clone())
+        });
+        let _ = worker.send((request.command, protocol));
+        Ok(None)
+    }
+}
+
+async fn handle_socket(socket: TcpStream, event_sender: UnboundedSender<Event>) -> AppResult<()> {
+    let (reader, mut writer) = socket.into_split();
+    let (reply_sender, reply_receiver) = mpsc::unbounded_channel::<String>();
+    let mut connection = MuxConnection {
+        reader,
+        event_sender,
+        workers: HashMap::new(),
+        reply_sender,
+        reply_receiver,
+    };
+    tcp::serve_frames(&mut connection, &mut writer).await?;
+    // workers finish their queued commands before the writer closes
+    let MuxConnection {
+        workers,
+        reply_sender,
+        mut reply_receiver,
+        ..
+    } = connection;
+    drop(workers);
+    drop(reply_sender);
+    while let Some(reply) = reply_receiver.recv().await {
+        writer.write_all(reply.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+fn spawn_worker(
+    port: usize,
+    event_sender: UnboundedSender<Event>,
+    reply_sender: UnboundedSender<String>,
+) -> UnboundedSender<(String, Protocol)> {
+    let (command_sender, mut command_receiver) = mpsc::unbounded_channel::<(String, Protocol)>();
+    tokio::spawn(async move {
+        while let Some((command, protocol)) = command_receiver.recv().await {
+            // rejected here so the reply stays in order with the agent's other replies
+            let response = if tcp::subscription(&command).is_some() {
+                let error =
+                    CommandError::InvalidCommand("subscribe on the agent's own port".to_string());
+                Response::error(error)
+            } else {
+                match tcp::request(port, command, &event_sender).await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        tracing::warn!("multiplexed request to {port} failed: {e}");
+                        break;
+                    }
+                }
+            };
+            if reply_sender
+                .send(protocol.encode(&response, Some(port)))
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+    command_sender
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(parse_line("3340 MOVE"), Some((3340, "MOVE")));
+        assert_eq!(parse_line(" 3340  TURN L "), Some((3340, "TURN L")));
+        assert_eq!(parse_line("MOVE"), None);
+        assert_eq!(parse_line("PROT JSON"), None);
+        assert_eq!(parse_line("-1 MOVE"), None);
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let request = parse_request(Protocol::Text, "3340 MOVE").unwrap();
+        assert_eq!(
+            (request.port, request.command.as_str()),
+            (Some(3340), "MOVE")
+        );
+        let request = parse_request(Protocol::Text, "PROT JSON").unwrap();
+        assert_eq!(
+            (request.port, request.command.as_str()),
+            (None, "PROT JSON")
+        );
+
+        let json = r#"{"port": 3340, "command": "TURN L"}"#;
+        let request = parse_request(Protocol::Json, json).unwrap();
+        assert_eq!(
+            (request.port, request.command.as_str()),
+            (Some(3340), "TURN L")
+        );
+        let request = parse_request(Protocol::Json, r#"{"command": "PROT TEXT"}"#).unwrap();
+        assert_eq!(
+            (request.port, request.command.as_str()),
+            (None, "PROT TEXT")
+        );
+        assert!(parse_request(Protocol::Json, "3340 MOVE").is_err());
+    }
+}
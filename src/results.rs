@@ -0,0 +1,295 @@
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+use crate::app::AppResult;
+use crate::surface::Surface;
+use crate::surface::generation::Generator;
+use crate::surface::state::Version;
+
+// every won game appends a line of json to results.jsonl in the data directory. runs of the same
+// scenario, or of the same seed outside of a scenario, are compared on the victory popup in the
+// style of zachtronics histograms, where lower is better for every metric
+
+/// number of bars in a histogram
+pub const BINS: usize = 10;
+
+/// what a run was solving, only runs of the same puzzle are compared
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Puzzle {
+    Scenario(String),
+    /// the same seed is a different map on another size or generator
+    Seed {
+        seed: u64,
+        world_size: u16,
+        generator: Generator,
+    },
+}
+
+impl std::fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Puzzle::Scenario(name) => write!(f, "scenario {name}"),
+            Puzzle::Seed {
+                seed, world_size, ..
+            } => write!(f, "seed {seed} {world_size}x{world_size}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum Metric {
+    Ticks,
+    Commands,
+    Power,
+    Agents,
+}
+
+impl Metric {
+    pub fn format(&self, value: u64) -> String {
+        match self {
+            Metric::Power => format!("{}Kj", value / 1000),
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// the metrics of a won game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    pub puzzle: Puzzle,
+    pub version: Version,
+    pub ticks: u64,
+    /// automated and manual commands
+    pub commands: u64,
+    /// joules consumed
+    pub power: u64,
+    pub agents: u64,
+}
+
+impl Run {
+    /// the run as it stood when the surface was won, `None` until then and for runs won with cheats
+    pub fn new(surface: &Surface) -> Option<Run> {
+        let stats = &surface.victory_stats.as_ref()?.stats;
+        if stats.cheated {
+            return None;
+        }
+        let puzzle = match &surface.scenario {
+            Some(progress) => Puzzle::Scenario(progress.scenario.name.clone()),
+            None => Puzzle::Seed {
+                seed: stats.seed.value(),
+                world_size: surface.grid.size(),
+                generator: surface
+                    .grid
+                    .terrain()
+                    .map(|terrain| terrain.generator)
+                    .unwrap_or_default(),
+            },
+        };
+        Some(Run {
+            puzzle,
+            version: stats.version,
+            ticks: stats.tick_count,
+            commands: stats.tcp_command_count + stats.manual_command_count,
+            power: stats.power_consumed,
+            agents: stats.agent_count.values().sum(),
+        })
+    }
+
+    pub fn metric(&self, metric: Metric) -> u64 {
+        match metric {
+            Metric::Ticks => self.ticks,
+            Metric::Commands => self.commands,
+            Metric::Power => self.power,
+            Metric::Agents => self.agents,
+        }
+    }
+}
+
+pub fn path() -> PathBuf {
+    crate::logging::get_data_dir().join("results.jsonl")
+}
+
+pub fn append(path: &Path, run: &Run) -> AppResult<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut line = serde_json::to_vec(run)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    Ok(())
+}
+
+/// every run of `puzzle` in the results file, skipping lines that are not a run
+pub fn load(path: &Path, puzzle: &Puzzle) -> AppResult<Vec<Run>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    let mut runs = vec![];
+    for (idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Run>(&line) {
+            Ok(run) if run.puzzle == *puzzle => runs.push(run),
+            Ok(_) => (),
+            Err(e) => tracing::warn!("skipping line {} of {path:?}: {e}", idx + 1),
+        }
+    }
+    Ok(runs)
+}
+
+/// how one metric of a run compares to every run of the same puzzle
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub metric: Metric,
+    pub value: u64,
+    pub min: u64,
+    pub max: u64,
+    /// number of runs in each bin, evenly splitting `min..=max`
+    pub bins: Vec<u64>,
+    /// 1 for the best run, runs that tie share a rank
+    pub rank: usize,
+    pub runs: usize,
+}
+
+impl Histogram {
+    /// `runs` are expected to include the run with `value`
+    pub fn new(metric: Metric, value: u64, runs: &[Run]) -> Histogram {
+        let values: Vec<u64> = runs.iter().map(|run| run.metric(metric)).collect();
+        let min = values.iter().copied().chain([value]).min().unwrap_or(value);
+        let max = values.iter().copied().chain([value]).max().unwrap_or(value);
+        let mut histogram = Histogram {
+            metric,
+            value,
+            min,
+            max,
+            bins: vec![0; BINS],
+            rank: values.iter().filter(|v| **v < value).count() + 1,
+            runs: values.len(),
+        };
+        for v in values {
+            let bin = histogram.bin(v);
+            histogram.bins[bin] += 1;
+        }
+        histogram
+    }
+
+    pub fn bin(&self, value: u64) -> usize {
+        let span = self.max - self.min + 1;
+        let bin = (value.saturating_sub(self.min) as u128 * BINS as u128) / span as u128;
+        (bin as usize).min(BINS - 1)
+    }
+}
+
+/// a won run and how it compares to the previous runs of its puzzle
+#[derive(Debug, Clone)]
+pub struct Scores {
+    pub run: Run,
+    /// number of runs of the puzzle, including this one once it is recorded
+    pub runs: usize,
+    pub histograms: Vec<Histogram>,
+}
+
+impl Scores {
+    /// compares `run` to the runs of its puzzle in the results file at `path`
+    pub fn load(path: &Path, run: Run) -> AppResult<Scores> {
+        let runs = load(path, &run.puzzle)?;
+        let histograms = Metric::iter()
+            .map(|metric| Histogram::new(metric, run.metric(metric), &runs))
+            .collect();
+        Ok(Scores {
+            run,
+            runs: runs.len(),
+            histograms,
+        })
+    }
+
+    /// appends a newly won `run` to the results file at `path` and compares it
+    pub fn record(path: &Path, run: Run) -> AppResult<Scores> {
+        append(path, &run)?;
+        tracing::info!("recorded {} run to {path:?}", run.puzzle);
+        Scores::load(path, run)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: Puzzle = Puzzle::Seed {
+        seed: 1,
+        world_size: 250,
+        generator: Generator::Dense,
+    };
+
+    fn run(ticks: u64) -> Run {
+        Run {
+            puzzle: SEED,
+            version: Version::default(),
+            ticks,
+            commands: 10,
+            power: 0,
+            agents: 3,
+        }
+    }
+
+    #[test]
+    fn test_histogram() {
+        let runs: Vec<Run> = [100, 100, 150, 200, 199].into_iter().map(run).collect();
+        let histogram = Histogram::new(Metric::Ticks, 150, &runs);
+        assert_eq!(histogram.bins, vec![2, 0, 0, 0, 1, 0, 0, 0, 0, 2]);
+        assert_eq!(histogram.bin(150), 4);
+        assert_eq!(histogram.rank, 3);
+        assert_eq!(histogram.runs, 5);
+
+        // every run tied
+        let histogram = Histogram::new(Metric::Commands, 10, &runs);
+        assert_eq!(histogram.bins[0], 5);
+        assert_eq!(histogram.rank, 1);
+    }
+
+    #[test]
+    fn test_record_and_load() {
+        let path =
+            std::env::temp_dir().join(format!("texaform-results-{}.jsonl", std::process::id()));
+        let mut other = run(50);
+        other.puzzle = Puzzle::Scenario("OTHER".to_string());
+        append(&path, &other).unwrap();
+        // the same seed on a larger world is another map
+        let mut larger = run(50);
+        larger.puzzle = Puzzle::Seed {
+            seed: 1,
+            world_size: 500,
+            generator: Generator::Chunked,
+        };
+        append(&path, &larger).unwrap();
+        append(&path, &run(300)).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not a run\n")
+            .unwrap();
+        let scores = Scores::record(&path, run(100)).unwrap();
+        let runs = load(&path, &SEED).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(runs, vec![run(300), run(100)]);
+        assert_eq!(scores.runs, 2);
+        let ticks = &scores.histograms[0];
+        assert_eq!(ticks.metric, Metric::Ticks);
+        assert_eq!(
+            (ticks.min, ticks.max, ticks.rank, ticks.runs),
+            (100, 300, 1, 2)
+        );
+        assert_eq!(load(&path, &SEED).unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::TICK_UPDATE_MILLS;
+
+// runtime settings shared by the tick timer and the tcp handlers
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+static TICK_MILLS: AtomicU64 = AtomicU64::new(TICK_UPDATE_MILLS);
+static COMMAND_LATENCY_MILLS: AtomicU64 = AtomicU64::new(100);
+
+/// common combinations of tick rate and command latency
+#[derive(Debug, Copy, Clone, PartialEq, Eq, strum_macros::Display, strum_macros::EnumString)]
+pub enum Preset {
+    #[strum(serialize = "PAUSED")]
+    Paused,
+    #[strum(serialize = "1X")]
+    Normal,
+    #[strum(serialize = "4X")]
+    Fast,
+    #[strum(serialize = "UNCAPPED")]
+    Uncapped,
+}
+
+impl Preset {
+    /// (tick, command latency) in milliseconds, `None` when paused
+    fn rates(&self) -> Option<(u64, u64)> {
+        match self {
+            Preset::Paused => None,
+            Preset::Normal => Some((TICK_UPDATE_MILLS, 100)),
+            Preset::Fast => Some((TICK_UPDATE_MILLS / 4, 25)),
+            Preset::Uncapped => Some((0, 0)),
+        }
+    }
+
+    pub fn apply(&self) {
+        match self.rates() {
+            Some((tick, latency)) => {
+                set_tick_millis(tick);
+                set_command_latency_millis(latency);
+                set_paused(false);
+            }
+            None => set_paused(true),
+        }
+    }
+
+    /// the next preset, for cycling through presets with a single button
+    pub fn next(&self) -> Preset {
+        match self {
+            Preset::Paused => Preset::Normal,
+            Preset::Normal => Preset::Fast,
+            Preset::Fast => Preset::Uncapped,
+            Preset::Uncapped => Preset::Paused,
+        }
+    }
+
+    /// the preset matching the current settings, if any
+    pub fn current() -> Option<Preset> {
+        if is_paused() {
+            return Some(Preset::Paused);
+        }
+        Preset::running()
+    }
+
+    /// the preset matching the current rates, whether paused or not
+    pub fn running() -> Option<Preset> {
+        let rates = Some((tick_millis(), command_latency_millis()));
+        [Preset::Normal, Preset::Fast, Preset::Uncapped]
+            .into_iter()
+            .find(|preset| preset.rates() == rates)
+    }
+}
+
+/// name of the current preset or the raw settings
+pub fn describe() -> String {
+    match Preset::current() {
+        Some(preset) => preset.to_string(),
+        None => format!("{}ms/{}ms", tick_millis(), command_latency_millis()),
+    }
+}
+
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::Relaxed)
+}
+
+/// time between ticks, 0 ticks as fast as they are handled
+pub fn tick_millis() -> u64 {
+    TICK_MILLS.load(Ordering::Relaxed)
+}
+
+pub fn set_tick_millis(millis: u64) {
+    TICK_MILLS.store(millis, Ordering::Relaxed)
+}
+
+pub fn tick_rate() -> Duration {
+    Duration::from_millis(tick_millis())
+}
+
+/// delay before an agent replies to a tcp command
+pub fn command_latency_millis() -> u64 {
+    COMMAND_LATENCY_MILLS.load(Ordering::Relaxed)
+}
+
+pub fn set_command_latency_millis(millis: u64) {
+    COMMAND_LATENCY_MILLS.store(millis, Ordering::Relaxed)
+}
+
+pub fn command_latency() -> Duration {
+    Duration::from_millis(command_latency_millis())
+}
@@ -0,0 +1,390 @@
+use std::convert::Infallible;
+use std::str::FromStr;
+
+use bytes::BytesMut;
+use ratatui::layout::{Position, Rect};
+use strum::IntoEnumIterator;
+use tokio::io::AsyncReadExt;
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::oneshot;
+
+use crate::agents::{CommandError, Response};
+use crate::app::AppResult;
+use crate::entities::Entity;
+use crate::event::Event;
+use crate::surface::grid::Gent;
+use crate::surface::journal::Entry;
+use crate::surface::{AddEntityError, MAX_WORLD_SIZE, Surface};
+use crate::tcp::{self, Connection, Incoming, Protocol, Read};
+
+// developer commands that change the surface directly, for debugging and setting up test cases.
+// they are typed into the console opened with [`] or sent to the port given by `--cheat-port`:
+//   SPAWN IRON 120 130     places an entity or agent at a grid position
+//   GIVE 3335 MOTOR 3      fills an agent's containers, first container first
+//   UNLOCK ALL             unlocks every entity, or UNLOCK MOTOR for one
+//   TP 3335 100 100        moves an agent
+//   SETPOWER STORED 50000  sets the STORED joules, CAPACITY joules or solar PANELS
+//   TICK 1000              runs ticks immediately
+// a cheat that succeeds marks the game's stats, so its results are not recorded
+
+/// most ticks a single `TICK` runs, about 7 hours of play
+const MAX_TICKS: u64 = 100_000;
+
+const USAGE: [&str; 6] = [
+    "SPAWN <entity> <x> <y>",
+    "GIVE <port> <entity> [count]",
+    "UNLOCK <ALL|entity>",
+    "TP <port> <x> <y>",
+    "SETPOWER <STORED|CAPACITY|PANELS> <amount>",
+    "TICK <ticks>",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerField {
+    Stored,
+    Capacity,
+    Panels,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cheat {
+    Spawn(Entity, Position),
+    Give {
+        port: usize,
+        entity: Entity,
+        count: usize,
+    },
+    /// every entity when `None`
+    Unlock(Option<Entity>),
+    Tp(usize, Position),
+    SetPower(PowerField, usize),
+    Tick(u64),
+}
+
+fn invalid(msg: String) -> CommandError {
+    CommandError::InvalidCommand(msg)
+}
+
+fn number<T: FromStr>(arg: &str) -> Result<T, CommandError> {
+    arg.parse()
+        .map_err(|_| invalid(format!("expected a number, got {arg}")))
+}
+
+fn entity(arg: &str) -> Result<Entity, CommandError> {
+    match Entity::from_str(arg) {
+        Ok(Entity::HUD | Entity::Empty) | Err(_) => Err(invalid(format!("unknown entity {arg}"))),
+        Ok(entity) => Ok(entity),
+    }
+}
+
+fn position(x: &str, y: &str) -> Result<Position, CommandError> {
+    let position = Position {
+        x: number(x)?,
+        y: number(y)?,
+    };
+    // checked against the size of the world when applied
+    if MAX_WORLD_SIZE <= position.x || MAX_WORLD_SIZE <= position.y {
+        return Err(CommandError::OutOfBounds);
+    }
+    Ok(position)
+}
+
+impl FromStr for Cheat {
+    type Err = CommandError;
+
+    fn from_str(command: &str) -> Result<Cheat, CommandError> {
+        let command = command.to_uppercase();
+        let words: Vec<&str> = command.split_whitespace().collect();
+        match words[..] {
+            ["SPAWN", kind, x, y] => Ok(Cheat::Spawn(entity(kind)?, position(x, y)?)),
+            ["GIVE", port, kind] => Ok(Cheat::Give {
+                port: number(port)?,
+                entity: entity(kind)?,
+                count: 1,
+            }),
+            ["GIVE", port, kind, count] => Ok(Cheat::Give {
+                port: number(port)?,
+                entity: entity(kind)?,
+                count: number(count)?,
+            }),
+            ["UNLOCK", "ALL"] => Ok(Cheat::Unlock(None)),
+            ["UNLOCK", kind] => Ok(Cheat::Unlock(Some(entity(kind)?))),
+            ["TP", port, x, y] => Ok(Cheat::Tp(number(port)?, position(x, y)?)),
+            ["SETPOWER", field, amount] => {
+                let field = match field {
+                    "STORED" => PowerField::Stored,
+                    "CAPACITY" => PowerField::Capacity,
+                    "PANELS" => PowerField::Panels,
+                    _ => return Err(invalid(format!("unknown power field {field}"))),
+                };
+                Ok(Cheat::SetPower(field, number(amount)?))
+            }
+            ["TICK", ticks] => match number(ticks)? {
+                ticks if ticks <= MAX_TICKS => Ok(Cheat::Tick(ticks)),
+                _ => Err(invalid(format!("at most {MAX_TICKS} ticks at once"))),
+            },
+            [name, ..] => match USAGE
+                .iter()
+                .find(|usage| usage.split(' ').next() == Some(name))
+            {
+                Some(usage) => Err(invalid(format!("usage: {usage}"))),
+                None => Err(invalid(format!("unknown cheat {name}"))),
+            },
+            [] => Err(invalid("empty cheat".to_string())),
+        }
+    }
+}
+
+/// `command` with the agent port it names moved from `saved_base` to `port_base`, so a journaled
+/// cheat targets the same agent when replayed on another port base
+pub fn rebase_command(command: &str, saved_base: usize, port_base: usize) -> String {
+    let mut words: Vec<String> = command.split_whitespace().map(str::to_string).collect();
+    if let [name, port, ..] = &mut words[..]
+        && matches!(name.to_uppercase().as_str(), "GIVE" | "TP")
+        && let Some(offset) = port
+            .parse::<usize>()
+            .ok()
+            .and_then(|p| p.checked_sub(saved_base))
+    {
+        *port = (port_base + offset).to_string();
+        return words.join(" ");
+    }
+    command.to_string()
+}
+
+impl From<AddEntityError> for CommandError {
+    fn from(error: AddEntityError) -> CommandError {
+        match error {
+            AddEntityError::Occupied => CommandError::LocationNotBuildable,
+            AddEntityError::OutOfBounds => CommandError::OutOfBounds,
+        }
+    }
+}
+
+impl Surface {
+    /// applies a cheat and journals it so the game still replays exactly
+    pub fn cheat(&mut self, command: &str) -> Response {
+        let tick = self.game_state.stats.tick_count;
+        // flagged before applying so a victory during `TICK` is flagged too
+        let cheated = std::mem::replace(&mut self.game_state.stats.cheated, true);
+        let reply = match command.parse().and_then(|cheat| self.apply_cheat(cheat)) {
+            Ok(()) => Response::okay(),
+            Err(e) => {
+                self.game_state.stats.cheated = cheated;
+                Response::error(e)
+            }
+        };
+        tracing::info!("cheat `{command}`: {}", reply.text);
+        self.journal.push(Entry {
+            tick,
+            port: tcp::port_base(),
+            command: command.to_string(),
+            reply: reply.text.clone(),
+            manual: false,
+            cheat: true,
+        });
+        reply
+    }
+
+    fn apply_cheat(&mut self, cheat: Cheat) -> Result<(), CommandError> {
+        if let Cheat::Spawn(_, pos) | Cheat::Tp(_, pos) = &cheat
+            && self.grid.get_direct(pos).is_none()
+        {
+            return Err(CommandError::OutOfBounds);
+        }
+        match cheat {
+            Cheat::Spawn(entity, pos) if entity.is_agent() => {
+                let agent = entity.create_agent().map_err(|e| invalid(e.to_string()))?;
+                self.add_agent(&pos, agent)?;
+            }
+            Cheat::Spawn(entity, pos) => self.add_entity(&pos, entity)?,
+            Cheat::Give {
+                port,
+                entity,
+                count,
+            } => {
+                let agent = self
+                    .get_mut_agent(&port)
+                    .ok_or(CommandError::UnknownAgent(port))?;
+                let mut containers = agent.containers_mut();
+                let room: usize = containers
+                    .iter()
+                    .map(|container| container.capacity - container.content.len())
+                    .sum();
+                if room < count {
+                    return Err(CommandError::AlreadyFull);
+                }
+                for _ in 0..count {
+                    if let Some(container) = containers.iter_mut().find(|c| c.placable()) {
+                        container.place(entity);
+                    }
+                }
+            }
+            Cheat::Unlock(Some(entity)) => {
+                self.game_state.unlocked_entities.insert(entity);
+            }
+            Cheat::Unlock(None) => {
+                let entities = Entity::iter().filter(|e| !matches!(e, Entity::HUD | Entity::Empty));
+                self.game_state.unlocked_entities.extend(entities);
+            }
+            Cheat::Tp(port, to) => self.teleport(port, to)?,
+            Cheat::SetPower(PowerField::Stored, joules) => {
+                self.power.capacity = self.power.capacity.max(joules);
+                self.power.stored = joules;
+            }
+            Cheat::SetPower(PowerField::Capacity, joules) => {
+                self.power.capacity = joules;
+                self.power.stored = self.power.stored.min(joules);
+            }
+            Cheat::SetPower(PowerField::Panels, count) => self.power.solar_pannel_count = count,
+            Cheat::Tick(ticks) => {
+                for _ in 0..ticks {
+                    self.tick();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn teleport(&mut self, port: usize, to: Position) -> Result<(), CommandError> {
+        let comms = self
+            .agents
+            .get(&port)
+            .ok_or(CommandError::UnknownAgent(port))?;
+        let (Some(from), Some(location)) = (comms.position, comms.location) else {
+            return Err(invalid(format!("{} can not be moved", comms.entity)));
+        };
+        let Some(Gent::Age(agent)) = self.grid.pop(&from) else {
+            tracing::error!("expected agent at {from:?}");
+            return Err(CommandError::NoReply);
+        };
+        for pos in location.positions() {
+            self.grid.insert(&pos, Gent::Empty);
+        }
+        let target = Rect {
+            x: to.x,
+            y: to.y,
+            ..location
+        };
+        let (root, rect) = if self.grid.buildable(target) {
+            (to, target)
+        } else {
+            (from, location)
+        };
+        self.grid.insert(&root, Gent::Age(agent));
+        for pos in rect.positions().skip(1) {
+            self.grid.insert(&pos, Gent::Large(root));
+        }
+        if root != to {
+            return Err(CommandError::LocationNotBuildable);
+        }
+        if let Some(comms) = self.agents.get_mut(&port) {
+            comms.position = Some(to);
+            comms.location = Some(target);
+        }
+        Ok(())
+    }
+}
+
+/// binds the cheat port and serves connections in the background, every command is a cheat
+pub async fn listen(port: usize, event_sender: UnboundedSender<Event>) -> AppResult<()> {
+    let listener = TcpListener::bind(format!("127.0.0.1:{port}")).await?;
+    tracing::info!("started cheat listener on port {port}");
+    tokio::spawn(async move {
+        loop {
+            let (socket, addr) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    tracing::warn!("failed to accept connection on port {port}: {e}");
+                    continue;
+                }
+            };
+            tracing::info!("cheat peer address: {addr}");
+            let event_sender = event_sender.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_socket(socket, event_sender).await {
+                    tracing::warn!("error handling cheat socket: {e}");
+                }
+                tracing::info!("cheat peer disconnected: {addr}");
+            });
+        }
+    });
+    Ok(())
+}
+
+/// a connection to the cheat port
+struct CheatConnection {
+    reader: OwnedReadHalf,
+    event_sender: UnboundedSender<Event>,
+}
+
+impl Connection for CheatConnection {
+    type Event = Infallible;
+
+    async fn read(&mut self, buf: &mut BytesMut) -> AppResult<Read<Infallible>> {
+        if self.reader.read_buf(buf).await? == 0 {
+            return Ok(Read::Closed);
+        }
+        Ok(Read::Bytes)
+    }
+
+    async fn handle(
+        &mut self,
+        incoming: Incoming<Infallible>,
+        protocol: Protocol,
+    ) -> AppResult<Option<String>> {
+        let Incoming::Request(request) = incoming;
+        let (reply_sender, reply_receiver) = oneshot::channel();
+        self.event_sender
+            .send(Event::Cheat(request.command, reply_sender))?;
+        let response = reply_receiver
+            .await
+            .unwrap_or_else(|_| Response::error(CommandError::NoReply));
+        Ok(Some(protocol.encode(&response, None)))
+    }
+}
+
+async fn handle_socket(socket: TcpStream, event_sender: UnboundedSender<Event>) -> AppResult<()> {
+    let (reader, mut writer) = socket.into_split();
+    let mut connection = CheatConnection {
+        reader,
+        event_sender,
+    };
+    tcp::serve_frames(&mut connection, &mut writer).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "spawn iron 120 130".parse::<Cheat>().unwrap(),
+            Cheat::Spawn(Entity::Iron, Position { x: 120, y: 130 })
+        );
+        assert_eq!(
+            "GIVE 3335 MOTOR".parse::<Cheat>().unwrap(),
+            Cheat::Give {
+                port: 3335,
+                entity: Entity::Motor,
+                count: 1
+            }
+        );
+        assert_eq!(
+            "SETPOWER stored 50000".parse::<Cheat>().unwrap(),
+            Cheat::SetPower(PowerField::Stored, 50_000)
+        );
+        assert_eq!("UNLOCK ALL".parse::<Cheat>().unwrap(), Cheat::Unlock(None));
+
+        let error = |command: &str| command.parse::<Cheat>().unwrap_err().to_string();
+        assert_eq!(error("TP 3335 100"), "usage: TP <port> <x> <y>");
+        assert_eq!(error("GIVE 3335 HUD"), "unknown entity HUD");
+        assert_eq!(error("SPAWN IRON 16000 1"), "out of bounds");
+        assert_eq!(error("TICK many"), "expected a number, got MANY");
+        assert_eq!(error("TICK 1000000"), "at most 100000 ticks at once");
+        assert_eq!(error("FLY"), "unknown cheat FLY");
+    }
+}
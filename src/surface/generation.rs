@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, HashSet};
+use std::sync::LazyLock;
 
 use noise::utils::{NoiseMapBuilder, PlaneMapBuilder};
 use noise::{self};
@@ -6,6 +7,7 @@ use rand::Rng;
 use rand::SeedableRng;
 use rand::distr::{Distribution, Uniform};
 use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -13,7 +15,7 @@ use crate::agents::Agent;
 use crate::entities::Entity;
 use crate::event::Event;
 
-use crate::utils::{idx_to_pos, pos_to_idx};
+use crate::utils::{idx_to_pos, pos_to_idx, xy_to_idx};
 
 use ratatui::widgets::Gauge;
 
@@ -26,70 +28,98 @@ use crate::entities::shape::Shape;
 use crate::surface::Seed;
 use crate::widgets::button::BorderedButton;
 
-use crate::surface::grid::{Gent, Grid};
+use crate::surface::grid::{CHUNK_CELLS, CHUNK_SIZE, Gent, Grid, Terrain};
+use crate::surface::journal::Journal;
 use crate::surface::state::{GameState, VERSION};
-use crate::surface::{AddEntityError, Focus, GRID_SIZE, Power, Surface};
+use crate::surface::{AddEntityError, DEFAULT_WORLD_SIZE, Focus, Power, Surface};
 
 use ratatui::layout::Position;
 
-fn insert_shape(
+/// the generator a world's terrain comes from, kept with saves and journals so chunks that were
+/// never changed, and replays, are generated the same way again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Generator {
+    /// the whole world at once from one stream of the seed, as every world was before worlds could
+    /// be resized
+    #[default]
+    Dense,
+    /// each chunk from its own streams of the seed, see [`chunk`]
+    Chunked,
+}
+
+impl Generator {
+    /// worlds of the default size keep the maps their seeds always had, larger worlds are too big
+    /// to generate at once
+    pub fn for_size(size: u16) -> Generator {
+        if size == DEFAULT_WORLD_SIZE {
+            Generator::Dense
+        } else {
+            Generator::Chunked
+        }
+    }
+}
+
+fn insert_dense_shape(
     shape: Shape,
     entity: Entity,
     grid: &mut [Gent],
     idx: usize,
+    size: usize,
     unbuildable_idx: &mut HashSet<usize>,
 ) {
-    let grid_pos = idx_to_pos(idx, GRID_SIZE);
+    let grid_pos = idx_to_pos(idx, size);
     let mut idxs = HashSet::new();
     for offset in &shape.positions {
         let adj_x = grid_pos.x + offset.x;
         // avoids inserting shapes that wrapp around east/west boarder
-        if adj_x == 0 || adj_x as usize == GRID_SIZE {
+        if adj_x == 0 || adj_x as usize == size {
             return;
         }
         let adj_pos = Position::new(adj_x, grid_pos.y + offset.y);
-        let grid_idx = pos_to_idx(&adj_pos, GRID_SIZE);
+        let grid_idx = pos_to_idx(&adj_pos, size);
         idxs.insert(grid_idx);
     }
-    if unbuildable_idx.intersection(&idxs).count() > 0
-        || idxs.iter().any(|i| *i > GRID_SIZE * GRID_SIZE)
-    {
+    if unbuildable_idx.intersection(&idxs).count() > 0 || idxs.iter().any(|i| *i >= grid.len()) {
         return;
     }
     for grid_idx in idxs {
         grid[grid_idx] = Gent::Intmd(entity);
     }
-    let footprint = shape.grid_footprint(&grid_pos, GRID_SIZE);
+    let footprint = shape.grid_footprint(&grid_pos, size);
     unbuildable_idx.extend(footprint);
 }
 
-pub async fn manual(event_sender: UnboundedSender<Event>, seed: Seed) -> Surface {
-    let mut rng = ChaCha8Rng::seed_from_u64(seed.value());
+/// every cell of a world `size` cells per side generated from `seed` by [`Generator::Dense`], row
+/// major
+pub fn dense(seed: u64, size: u16) -> Vec<Gent> {
+    let size = size as usize;
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
     let mut grid: Vec<Gent> = vec![];
-    for _ in 0..(GRID_SIZE * GRID_SIZE) {
+    for _ in 0..(size * size) {
         grid.push(Gent::Empty)
     }
 
     let mut unbuildable = HashSet::<usize>::new();
     let starting_area = Shape::circle(10);
-    let center_adj = ((GRID_SIZE / 2) - 5) as u16;
+    let center_adj = ((size / 2) - 5) as u16;
     for offset in starting_area.positions {
         let pos = Position::new(offset.x + center_adj, offset.y + center_adj);
-        unbuildable.insert(pos_to_idx(&pos, GRID_SIZE));
+        unbuildable.insert(pos_to_idx(&pos, size));
     }
 
     let rand_radius = Uniform::new(4, 7).expect("valid range");
     let rand_iters = Uniform::new(2, 5).expect("valid range");
-    for idx in 0..(GRID_SIZE * GRID_SIZE) {
+    for idx in 0..(size * size) {
         if rng.random::<f32>() < 0.003 {
             let radius = rand_radius.sample(&mut rng);
             let iters = rand_iters.sample(&mut rng);
             let copper_vein = Shape::jittered_circle(&mut rng, radius, iters);
-            insert_shape(
+            insert_dense_shape(
                 copper_vein,
                 Entity::Copper,
                 &mut grid,
                 idx,
+                size,
                 &mut unbuildable,
             );
         }
@@ -97,23 +127,24 @@ pub async fn manual(event_sender: UnboundedSender<Event>, seed: Seed) -> Surface
 
     let rand_length = Uniform::new(4, 10).expect("valid range");
     let rand_bend_chance = Uniform::new(0.4, 0.6).expect("valid range");
-    for idx in 0..(GRID_SIZE * GRID_SIZE) {
+    for idx in 0..(size * size) {
         if rng.random::<f32>() < 0.002 {
             let length = rand_length.sample(&mut rng);
             let bend_chance = rand_bend_chance.sample(&mut rng);
             let horizontal = rng.random();
             let wf = Shape::waffle_fry(&mut rng, length, bend_chance, horizontal).translate(0, 1);
-            insert_shape(wf, Entity::Silicate, &mut grid, idx, &mut unbuildable);
+            insert_dense_shape(wf, Entity::Silicate, &mut grid, idx, size, &mut unbuildable);
         }
     }
 
-    for idx in 0..(GRID_SIZE * GRID_SIZE) {
+    for idx in 0..(size * size) {
         if rng.random::<f32>() < 0.005 {
-            insert_shape(
+            insert_dense_shape(
                 Shape::diamond(),
                 Entity::Sulfer,
                 &mut grid,
                 idx,
+                size,
                 &mut unbuildable,
             );
         }
@@ -124,12 +155,233 @@ pub async fn manual(event_sender: UnboundedSender<Event>, seed: Seed) -> Surface
             *cell = Gent::Intmd(Entity::Iron);
         }
     }
+    grid
+}
+
+/// resources placed together, with the cells kept clear of other resources around them
+#[derive(Debug)]
+pub struct Vein {
+    entity: Entity,
+    cells: Vec<Position>,
+    kept_clear: HashSet<Position>,
+}
+
+/// kept clear of resources for the starting agents, its top left is just above the center
+static STARTING_AREA: LazyLock<Shape> = LazyLock::new(|| Shape::circle(10));
+
+fn in_starting_area(pos: &Position, size: u16) -> bool {
+    let center_adj = (size / 2).saturating_sub(5);
+    match (pos.x.checked_sub(center_adj), pos.y.checked_sub(center_adj)) {
+        (Some(x), Some(y)) => STARTING_AREA.positions.contains(&Position::new(x, y)),
+        _ => false,
+    }
+}
+
+/// places `shape` at `at` unless it leaves a world of `size` or overlaps the `unbuildable` cells
+fn insert_shape(
+    shape: Shape,
+    entity: Entity,
+    at: Position,
+    size: u16,
+    unbuildable: &mut HashSet<Position>,
+    veins: &mut Vec<Vein>,
+) {
+    let cells: Vec<Position> = shape
+        .positions
+        .iter()
+        .map(|offset| Position::new(at.x + offset.x, at.y + offset.y))
+        .collect();
+    if cells.iter().any(|pos| {
+        size <= pos.x || size <= pos.y || unbuildable.contains(pos) || in_starting_area(pos, size)
+    }) {
+        return;
+    }
+    let kept_clear = shape.bordered_positions(&at);
+    unbuildable.extend(kept_clear.iter().copied());
+    veins.push(Vein {
+        entity,
+        cells,
+        kept_clear,
+    });
+}
+
+/// positions of the chunk with its top left at `origin` that are inside a world of `size`, in
+/// row order
+fn chunk_positions(origin: Position, size: u16) -> impl Iterator<Item = Position> {
+    let right = origin.x.saturating_add(CHUNK_SIZE).min(size);
+    let bottom = origin.y.saturating_add(CHUNK_SIZE).min(size);
+    (origin.y..bottom).flat_map(move |y| (origin.x..right).map(move |x| Position::new(x, y)))
+}
+
+/// every chunk draws from its own streams of `seed`, so chunks can be generated in any order
+fn chunk_rng(seed: u64, origin: Position, stream: u64) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream((stream << 32) | ((origin.y as u64) << 16) | origin.x as u64);
+    rng
+}
+
+/// the veins starting in the chunk at `origin` of a world of `size` generated from `seed`, they
+/// can reach into the chunks right and below
+pub fn veins(seed: u64, size: u16, origin: Position) -> Vec<Vein> {
+    let mut rng = chunk_rng(seed, origin, 0);
+    let mut unbuildable = HashSet::new();
+    let mut veins = vec![];
+
+    let rand_radius = Uniform::new(4, 7).expect("valid range");
+    let rand_iters = Uniform::new(2, 5).expect("valid range");
+    for pos in chunk_positions(origin, size) {
+        if rng.random::<f32>() < 0.003 {
+            let radius = rand_radius.sample(&mut rng);
+            let iters = rand_iters.sample(&mut rng);
+            let copper_vein = Shape::jittered_circle(&mut rng, radius, iters);
+            insert_shape(
+                copper_vein,
+                Entity::Copper,
+                pos,
+                size,
+                &mut unbuildable,
+                &mut veins,
+            );
+        }
+    }
+
+    let rand_length = Uniform::new(4, 10).expect("valid range");
+    let rand_bend_chance = Uniform::new(0.4, 0.6).expect("valid range");
+    for pos in chunk_positions(origin, size) {
+        if rng.random::<f32>() < 0.002 {
+            let length = rand_length.sample(&mut rng);
+            let bend_chance = rand_bend_chance.sample(&mut rng);
+            let horizontal = rng.random();
+            let wf = Shape::waffle_fry(&mut rng, length, bend_chance, horizontal).translate(0, 1);
+            insert_shape(
+                wf,
+                Entity::Silicate,
+                pos,
+                size,
+                &mut unbuildable,
+                &mut veins,
+            );
+        }
+    }
+
+    for pos in chunk_positions(origin, size) {
+        if rng.random::<f32>() < 0.005 {
+            insert_shape(
+                Shape::diamond(),
+                Entity::Sulfer,
+                pos,
+                size,
+                &mut unbuildable,
+                &mut veins,
+            );
+        }
+    }
+    veins
+}
+
+/// the cells of the chunk with its top left at `origin` in a world of `size` generated from
+/// `seed`, the same whichever chunks were generated before it. `veins` gives the [`veins`] of a
+/// chunk by its origin
+pub fn chunk<'a>(
+    seed: u64,
+    size: u16,
+    origin: Position,
+    veins: impl Fn(Position) -> &'a [Vein],
+) -> Vec<Gent> {
+    let mut cells: Vec<Gent> = (0..CHUNK_CELLS).map(|_| Gent::Empty).collect();
+    let local_idx = |pos: &Position| {
+        let x = pos.x.checked_sub(origin.x).filter(|x| *x < CHUNK_SIZE)?;
+        let y = pos.y.checked_sub(origin.y).filter(|y| *y < CHUNK_SIZE)?;
+        Some(xy_to_idx(x.into(), y.into(), CHUNK_SIZE.into()))
+    };
+
+    // veins are smaller than a chunk, only the ones starting in this chunk or the chunks above
+    // and left of it reach into it. the chunks right and below keep iron away from this chunk's
+    // edge, as veins keep it away from theirs
+    let mut unbuildable = vec![false; CHUNK_CELLS];
+    for dy in [-1, 0, 1] {
+        for dx in [-1, 0, 1] {
+            let (Some(x), Some(y)) = (
+                origin.x.checked_add_signed(dx * CHUNK_SIZE as i16),
+                origin.y.checked_add_signed(dy * CHUNK_SIZE as i16),
+            ) else {
+                continue;
+            };
+            if size <= x || size <= y {
+                continue;
+            }
+            for vein in veins(Position::new(x, y)) {
+                for pos in vein.cells.iter() {
+                    if let Some(idx) = local_idx(pos) {
+                        cells[idx] = Gent::Intmd(vein.entity);
+                    }
+                }
+                for pos in vein.kept_clear.iter() {
+                    if let Some(idx) = local_idx(pos) {
+                        unbuildable[idx] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut rng = chunk_rng(seed, origin, 1);
+    for pos in chunk_positions(origin, size) {
+        let idx = local_idx(&pos).expect("position is in the chunk");
+        if rng.random::<f32>() < 0.1 && !unbuildable[idx] && !in_starting_area(&pos, size) {
+            cells[idx] = Gent::Intmd(Entity::Iron);
+        }
+    }
+    cells
+}
 
-    let grid = Grid::new(grid);
+/// a surface generated from `seed` with the starting agents and entities
+pub fn new_game(
+    event_sender: UnboundedSender<Event>,
+    seed: Seed,
+) -> Result<Surface, AddEntityError> {
+    new_game_of_size(event_sender, seed, DEFAULT_WORLD_SIZE)
+}
+
+/// [`new_game`] on a world `size` cells per side
+pub fn new_game_of_size(
+    event_sender: UnboundedSender<Event>,
+    seed: Seed,
+    size: u16,
+) -> Result<Surface, AddEntityError> {
+    new_game_generated_by(event_sender, seed, size, Generator::for_size(size))
+}
 
-    let x = (GRID_SIZE / 2) - 30;
-    let y = (GRID_SIZE / 2) - 10;
-    Surface::new(grid, x, y, seed, event_sender).await
+/// [`new_game_of_size`] with the terrain from `generator`, for rebuilding a recorded world
+pub fn new_game_generated_by(
+    event_sender: UnboundedSender<Event>,
+    seed: Seed,
+    size: u16,
+    generator: Generator,
+) -> Result<Surface, AddEntityError> {
+    let mut surface = manual(event_sender, seed, size, generator);
+    init_starting_entities(&mut surface)?;
+    Ok(surface)
+}
+
+/// a surface `size` cells per side generated from `seed` by `generator`, its chunks are generated
+/// as they are used
+pub fn manual(
+    event_sender: UnboundedSender<Event>,
+    seed: Seed,
+    size: u16,
+    generator: Generator,
+) -> Surface {
+    let grid = Grid::generated(
+        size,
+        Terrain {
+            seed: seed.value(),
+            generator,
+        },
+    );
+    let x = (size as usize / 2).saturating_sub(30);
+    let y = (size as usize / 2).saturating_sub(10);
+    Surface::new(grid, x, y, seed, event_sender)
 }
 
 pub fn perlin(event_sender: UnboundedSender<Event>) -> Surface {
@@ -142,8 +394,9 @@ pub fn perlin(event_sender: UnboundedSender<Event>) -> Surface {
     // TODO try using 10x or 100x larger plane map builder and then sample from it every 10/100
     // places (should hopfully "scale down"/"make smaller" the pattern)
     tracing::info!("start plane");
+    let size = DEFAULT_WORLD_SIZE;
     let plane = PlaneMapBuilder::new(noise)
-        .set_size(GRID_SIZE, GRID_SIZE)
+        .set_size(size.into(), size.into())
         .build();
     tracing::info!("end plane");
 
@@ -151,44 +404,42 @@ pub fn perlin(event_sender: UnboundedSender<Event>) -> Surface {
         tracing::info!("HERE: {val}");
     }
 
-    let mut grid: Vec<Gent> = vec![];
-    for val in plane.iter() {
-        if *val > 0.25 {
-            grid.push(Gent::Intmd(Entity::Iron));
-        } else {
-            grid.push(Gent::Empty)
-        }
-    }
-    grid[GRID_SIZE] = Gent::Empty;
-    grid[GRID_SIZE + 1] = Gent::Empty;
-
-    for x in 0..15 {
-        for y in 1..15 {
-            grid[GRID_SIZE * y + x] = Gent::Empty;
+    let mut grid = Grid::empty(size);
+    for (idx, val) in plane.iter().enumerate() {
+        let pos = idx_to_pos(idx, size.into());
+        // keep the top left corner clear
+        if *val > 0.25 && !(pos.x < 15 && (1..15).contains(&pos.y)) {
+            grid.insert(&pos, Gent::Intmd(Entity::Iron));
         }
     }
 
     Surface {
         version: VERSION,
-        grid: Grid::new(grid),
+        grid,
         x: 0,
         y: 0,
         agents: BTreeMap::new(),
         event_sender,
         game_state: GameState::default(),
         victory_stats: None,
+        scenario: None,
         power: Power::default(),
         effects: vec![],
         focus: None,
         previous_command_counter: 0,
+        lockstep: false,
+        listening: false,
+        command_queue: BTreeMap::new(),
+        journal: Journal::default(),
+        replay_paused: None,
+        recorder: None,
         hud: Hud::default(),
         current_research_button: BorderedButton::new(Gauge::default()),
     }
 }
 
 pub fn empty(event_sender: UnboundedSender<Event>) -> Surface {
-    let grid: Vec<Gent> = vec![];
-    let grid = Grid::new(grid);
+    let grid = Grid::empty(0);
     Surface {
         version: VERSION,
         grid,
@@ -198,16 +449,23 @@ pub fn empty(event_sender: UnboundedSender<Event>) -> Surface {
         event_sender,
         game_state: GameState::default(),
         victory_stats: None,
+        scenario: None,
         power: Power::default(),
         effects: vec![],
         focus: None,
         hud: Hud::default(),
         previous_command_counter: 0,
+        lockstep: false,
+        listening: false,
+        command_queue: BTreeMap::new(),
+        journal: Journal::default(),
+        replay_paused: None,
+        recorder: None,
         current_research_button: BorderedButton::new(Gauge::default()),
     }
 }
 
-pub async fn init_starting_entities(surface: &mut Surface) -> Result<(), AddEntityError> {
+pub fn init_starting_entities(surface: &mut Surface) -> Result<(), AddEntityError> {
     let mut fab = Fabricator::new();
 
     // push items needed to make smelter
@@ -225,20 +483,14 @@ pub async fn init_starting_entities(surface: &mut Surface) -> Result<(), AddEnti
         }
     }
 
-    let center = GRID_SIZE as u16 / 2;
-    surface
-        .add_agent(&Position::new(center, center), Box::new(fab))
-        .await?;
+    let center = surface.grid.size() / 2;
+    surface.add_agent(&Position::new(center, center), Box::new(fab))?;
 
     let dog = Dog::new();
-    surface
-        .add_agent(&Position::new(center - 2, center - 2), Box::new(dog))
-        .await?;
-    surface.focus = Some(Focus::Agent(3335));
+    surface.add_agent(&Position::new(center - 2, center - 2), Box::new(dog))?;
+    surface.focus = Some(Focus::Agent(crate::tcp::port_base() + 2));
     let dog = Dog::new();
-    surface
-        .add_agent(&Position::new(center - 2, center), Box::new(dog))
-        .await?;
+    surface.add_agent(&Position::new(center - 2, center), Box::new(dog))?;
 
     surface.add_entity(&Position::new(center + 1, center - 2), Entity::Iron)?;
     surface.add_entity(&Position::new(center + 2, center - 2), Entity::Iron)?;
@@ -248,7 +500,7 @@ pub async fn init_starting_entities(surface: &mut Surface) -> Result<(), AddEnti
     Ok(())
 }
 
-pub async fn init_starting_agent(surface: &mut Surface) -> Result<(), AddEntityError> {
+pub fn init_starting_agent(surface: &mut Surface) -> Result<(), AddEntityError> {
     let mut fab = Fabricator::new();
 
     // push items needed to make dog
@@ -271,10 +523,8 @@ pub async fn init_starting_agent(surface: &mut Surface) -> Result<(), AddEntityE
         }
     }
 
-    let grid_center = GRID_SIZE as u16 / 2;
-    surface
-        .add_agent(&Position::new(grid_center, grid_center), Box::new(fab))
-        .await?;
+    let grid_center = surface.grid.size() / 2;
+    surface.add_agent(&Position::new(grid_center, grid_center), Box::new(fab))?;
 
     let solar_pannel = Entity::SolarPannel;
     let sp1 = Position {
@@ -303,22 +553,16 @@ pub async fn init_starting_agent(surface: &mut Surface) -> Result<(), AddEntityE
     Ok(())
 }
 
-pub async fn init_some_agents(surface: &mut Surface) -> Result<(), AddEntityError> {
+pub fn init_some_agents(surface: &mut Surface) -> Result<(), AddEntityError> {
     let dog = Dog::new();
-    surface
-        .add_agent(&Position { x: 2, y: 7 }, Box::new(dog))
-        .await?;
+    surface.add_agent(&Position { x: 2, y: 7 }, Box::new(dog))?;
 
     let mut smelter = Smelter::new();
     smelter.hearth = Some(Entity::Iron);
-    surface
-        .add_agent(&Position { x: 1, y: 8 }, Box::new(smelter))
-        .await?;
+    surface.add_agent(&Position { x: 1, y: 8 }, Box::new(smelter))?;
 
     let laser_cutter = LaserCutter::new();
-    surface
-        .add_agent(&Position { x: 9, y: 2 }, Box::new(laser_cutter))
-        .await?;
+    surface.add_agent(&Position { x: 9, y: 2 }, Box::new(laser_cutter))?;
 
     let solar_pannel = Entity::SolarPannel;
     let sp1 = Position { x: 2, y: 12 };
@@ -1,21 +1,23 @@
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+
 use ratatui::Frame;
 use ratatui::buffer::Cell;
 use ratatui::layout::{Position, Rect};
 use ratatui::style::Color;
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 use strum::EnumMessage;
 
 use crate::agents::Agent;
 use crate::entities::{Entity, PickResult};
 
-use crate::utils::checked_pos_to_idx;
-use crate::utils::pos_to_idx;
+use crate::utils::{idx_to_pos, xy_to_idx};
 
 use ratatui::buffer::Buffer;
 use ratatui::widgets::{Paragraph, WidgetRef};
 
-use super::GRID_SIZE;
+use super::generation::{self, Generator, Vein};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Gent {
@@ -54,7 +56,7 @@ impl Gent {
     }
     // used in ui/load_game.rs
     // just implement there?
-    // render pointing at the inital starting position via GRID_SIZE
+    // render pointing at the inital starting position via the grid's size
     pub fn render_surface_cell(&self, pos: &Position, cell: &mut Cell) {
         match self {
             Gent::Intmd(p) => {
@@ -109,52 +111,202 @@ impl Gent {
     }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+/// cells per side of a chunk, the grid is stored and saved a chunk at a time
+pub const CHUNK_SIZE: u16 = 32;
+pub const CHUNK_CELLS: usize = CHUNK_SIZE as usize * CHUNK_SIZE as usize;
+
+#[derive(Debug)]
+struct Chunk {
+    /// row major, cells past the edge of the world stay empty
+    cells: Vec<Gent>,
+    /// changed since it was generated, only changed chunks are saved
+    changed: bool,
+}
+
+/// what the chunks of a grid are generated from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Terrain {
+    pub seed: u64,
+    pub generator: Generator,
+}
+
+/// a grid as saved, with only the chunks changed since they were generated
+#[derive(Serialize, Deserialize)]
+struct SavedGrid<C> {
+    size: u16,
+    terrain: Option<Terrain>,
+    /// chunk index to its cells
+    chunks: BTreeMap<usize, C>,
+    /// changed chunks that are now empty
+    cleared: Vec<usize>,
+}
+
+/// the world's cells, split into chunks that are generated the first time they are used
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "SavedGrid<Vec<Gent>>")]
 pub struct Grid {
-    raw: Vec<Gent>,
+    /// cells per side
+    size: u16,
+    /// what the chunks are generated from, without it they start empty
+    terrain: Option<Terrain>,
+    /// row major, [`Grid::chunks_per_side`] wide
+    chunks: Vec<OnceCell<Chunk>>,
+    /// veins starting in each chunk, kept for generating the chunks around it
+    veins: Vec<OnceCell<Vec<Vein>>>,
+    /// the whole world of a [`Generator::Dense`] terrain, cut into chunks as they are used
+    dense: OnceCell<Vec<Gent>>,
 }
 
 impl Grid {
-    pub fn new(raw: Vec<Gent>) -> Grid {
-        Grid { raw }
+    fn with_terrain(size: u16, terrain: Option<Terrain>) -> Grid {
+        let chunks_per_side = (size as usize).div_ceil(CHUNK_SIZE as usize);
+        Grid {
+            size,
+            terrain,
+            chunks: (0..chunks_per_side * chunks_per_side)
+                .map(|_| OnceCell::new())
+                .collect(),
+            veins: (0..chunks_per_side * chunks_per_side)
+                .map(|_| OnceCell::new())
+                .collect(),
+            dense: OnceCell::new(),
+        }
+    }
+
+    /// a world `size` cells per side generated from `terrain`
+    pub fn generated(size: u16, terrain: Terrain) -> Grid {
+        Grid::with_terrain(size, Some(terrain))
+    }
+
+    /// a world `size` cells per side with nothing on it
+    pub fn empty(size: u16) -> Grid {
+        Grid::with_terrain(size, None)
+    }
+
+    pub fn size(&self) -> u16 {
+        self.size
+    }
+
+    pub fn terrain(&self) -> Option<Terrain> {
+        self.terrain
+    }
+
+    fn chunks_per_side(&self) -> usize {
+        (self.size as usize).div_ceil(CHUNK_SIZE as usize)
+    }
+
+    /// the index of the chunk `pos` is in and of its cell in the chunk
+    fn locate(&self, pos: &Position) -> Option<(usize, usize)> {
+        if self.size <= pos.x || self.size <= pos.y {
+            return None;
+        }
+        let chunk = xy_to_idx(
+            (pos.x / CHUNK_SIZE).into(),
+            (pos.y / CHUNK_SIZE).into(),
+            self.chunks_per_side(),
+        );
+        let cell = xy_to_idx(
+            (pos.x % CHUNK_SIZE).into(),
+            (pos.y % CHUNK_SIZE).into(),
+            CHUNK_SIZE.into(),
+        );
+        Some((chunk, cell))
+    }
+
+    fn generate(&self, idx: usize) -> Chunk {
+        let chunk = idx_to_pos(idx, self.chunks_per_side());
+        let origin = Position::new(chunk.x * CHUNK_SIZE, chunk.y * CHUNK_SIZE);
+        let cells = match self.terrain {
+            Some(Terrain {
+                seed,
+                generator: Generator::Chunked,
+            }) => generation::chunk(seed, self.size, origin, |origin| self.veins(seed, origin)),
+            Some(Terrain {
+                seed,
+                generator: Generator::Dense,
+            }) => {
+                let dense = self
+                    .dense
+                    .get_or_init(|| generation::dense(seed, self.size));
+                (0..CHUNK_SIZE)
+                    .flat_map(|y| (0..CHUNK_SIZE).map(move |x| (x, y)))
+                    .map(|(x, y)| {
+                        let (x, y) = (origin.x + x, origin.y + y);
+                        if self.size <= x || self.size <= y {
+                            return Gent::Empty;
+                        }
+                        match &dense[xy_to_idx(x.into(), y.into(), self.size.into())] {
+                            Gent::Intmd(entity) => Gent::Intmd(*entity),
+                            _ => Gent::Empty,
+                        }
+                    })
+                    .collect()
+            }
+            None => (0..CHUNK_CELLS).map(|_| Gent::Empty).collect(),
+        };
+        Chunk {
+            cells,
+            changed: false,
+        }
+    }
+
+    fn veins(&self, seed: u64, origin: Position) -> &[Vein] {
+        let idx = xy_to_idx(
+            (origin.x / CHUNK_SIZE).into(),
+            (origin.y / CHUNK_SIZE).into(),
+            self.chunks_per_side(),
+        );
+        self.veins[idx].get_or_init(|| generation::veins(seed, self.size, origin))
+    }
+
+    fn chunk(&self, idx: usize) -> &Chunk {
+        self.chunks[idx].get_or_init(|| self.generate(idx))
+    }
+
+    fn chunk_mut(&mut self, idx: usize) -> &mut Chunk {
+        if self.chunks[idx].get().is_none() {
+            let chunk = self.generate(idx);
+            let _ = self.chunks[idx].set(chunk);
+        }
+        let chunk = self.chunks[idx].get_mut().expect("generated above");
+        chunk.changed = true;
+        chunk
+    }
+
+    /// the cell at `pos`, none outside of the world
+    pub fn get_direct(&self, pos: &Position) -> Option<&Gent> {
+        let (chunk, cell) = self.locate(pos)?;
+        Some(&self.chunk(chunk).cells[cell])
+    }
+
+    fn get_direct_mut(&mut self, pos: &Position) -> Option<&mut Gent> {
+        let (chunk, cell) = self.locate(pos)?;
+        Some(&mut self.chunk_mut(chunk).cells[cell])
     }
 
     pub fn get(&self, pos: &Position) -> Option<&Gent> {
-        let idx = pos_to_idx(pos, GRID_SIZE);
-        match self.raw.get(idx) {
+        match self.get_direct(pos) {
             Some(Gent::Large(pos)) => self.get(pos),
             gent => gent,
         }
     }
     pub fn get_mut(&mut self, pos: &Position) -> Option<&mut Gent> {
-        let idx = pos_to_idx(pos, GRID_SIZE);
-        match self.raw.get(idx) {
-            Some(Gent::Large(pos)) => {
-                let idx = pos_to_idx(pos, GRID_SIZE);
-                self.raw.get_mut(idx)
+        match self.get_direct(pos)? {
+            Gent::Large(root) => {
+                let root = *root;
+                self.get_direct_mut(&root)
             }
-            _ => self.raw.get_mut(idx),
+            _ => self.get_direct_mut(pos),
         }
     }
     pub fn pop(&mut self, pos: &Position) -> Option<Gent> {
-        let idx = pos_to_idx(pos, GRID_SIZE);
-        let len = self.raw.len();
-        //self.raw.push(Gent::Empty);
-        self.raw.push(Gent::BeingUpdated);
-        self.raw.swap(idx, len);
-        self.raw.pop()
+        self.get_direct_mut(pos)
+            .map(|gent| std::mem::replace(gent, Gent::BeingUpdated))
     }
     pub fn insert(&mut self, pos: &Position, gent: Gent) {
-        let idx = pos_to_idx(pos, GRID_SIZE);
-        self.raw[idx] = gent;
-    }
-    pub fn get_direct(&self, pos: &Position) -> Option<&Gent> {
-        //        let idx = checked_pos_to_idx(pos, GRID_SIZE);
-        //        self.raw.get(idx)
-        if let Some(idx) = checked_pos_to_idx(pos, GRID_SIZE) {
-            self.raw.get(idx)
-        } else {
-            None
+        match self.get_direct_mut(pos) {
+            Some(cell) => *cell = gent,
+            None => tracing::error!("{pos:?} is outside of the world"),
         }
     }
     pub fn pick(&mut self, c: char, pos: &Position) -> Option<Entity> {
@@ -212,4 +364,237 @@ impl Grid {
             }
         }
     }
+
+    /// draws the grid with `offset` at the top left of the frame
+    pub fn render(&self, frame: &mut Frame, area: Rect, offset: Position) {
+        let no_offset = Position { x: 0, y: 0 };
+        let clamped_area = area.clamp(frame.area());
+        let buf = frame.buffer_mut();
+        for pos in clamped_area.positions() {
+            let cell = &mut buf[(pos.x, pos.y)];
+            let grid_pos = Position {
+                x: pos.x.saturating_add(offset.x),
+                y: pos.y.saturating_add(offset.y),
+            };
+            //tracing::info!("grid_pos: {grid_pos:?}");
+            match self.get_direct(&grid_pos) {
+                None => {
+                    cell.set_char(' ');
+                    cell.bg = Color::Red;
+                }
+                Some(Gent::Intmd(p)) => {
+                    cell.set_char(p.character());
+                    cell.bg = p.bg();
+                    cell.fg = p.fg();
+                }
+                Some(Gent::Age(agent)) => {
+                    agent.render_surface_cell(&no_offset, cell);
+                }
+                // TODO just take props and render lowercase
+                Some(Gent::Large(ent_pos)) => {
+                    if let (Some(x), Some(y)) = (
+                        grid_pos.x.checked_sub(ent_pos.x),
+                        grid_pos.y.checked_sub(ent_pos.y),
+                    ) {
+                        let offset = Position::new(x, y);
+                        match self.get_direct(ent_pos) {
+                            Some(Gent::Age(agent)) => agent.render_surface_cell(&offset, cell),
+                            Some(Gent::Intmd(p)) => {
+                                cell.set_char(p.character().to_ascii_lowercase());
+                                cell.bg = p.bg();
+                                cell.fg = p.fg();
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                Some(Gent::Empty) => {
+                    // TODO not efficent
+                    // should do once on startup, but not every render
+                    // could just paint everyting black once in main before loop?
+                    // and on resizing?
+                    cell.bg = Color::Black;
+                }
+                Some(Gent::BeingUpdated) => {
+                    tracing::error!("this code should not be reached");
+                }
+            }
+        }
+    }
+}
+
+impl Serialize for Grid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut saved = SavedGrid {
+            size: self.size,
+            terrain: self.terrain,
+            chunks: BTreeMap::new(),
+            cleared: vec![],
+        };
+        // the rest are generated again the same way when loaded
+        for (idx, chunk) in self.chunks.iter().enumerate() {
+            match chunk.get() {
+                Some(chunk) if chunk.changed => {
+                    if chunk.cells.iter().all(Gent::is_empty) {
+                        saved.cleared.push(idx);
+                    } else {
+                        saved.chunks.insert(idx, chunk.cells.as_slice());
+                    }
+                }
+                _ => (),
+            }
+        }
+        saved.serialize(serializer)
+    }
+}
+
+impl TryFrom<SavedGrid<Vec<Gent>>> for Grid {
+    type Error = String;
+
+    fn try_from(saved: SavedGrid<Vec<Gent>>) -> Result<Grid, String> {
+        let mut grid = Grid::with_terrain(saved.size, saved.terrain);
+        let cleared = saved
+            .cleared
+            .into_iter()
+            .map(|idx| (idx, (0..CHUNK_CELLS).map(|_| Gent::Empty).collect()));
+        for (idx, cells) in saved.chunks.into_iter().chain(cleared) {
+            if cells.len() != CHUNK_CELLS {
+                return Err(format!(
+                    "chunk {idx} has {} cells, not {CHUNK_CELLS}",
+                    cells.len()
+                ));
+            }
+            let chunk = Chunk {
+                cells,
+                changed: true,
+            };
+            let Some(cell) = grid.chunks.get_mut(idx) else {
+                return Err(format!(
+                    "chunk {idx} is outside of a world {} cells wide",
+                    saved.size
+                ));
+            };
+            let _ = cell.set(chunk);
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let mut grid = Grid::empty(40);
+        assert_eq!(grid.chunks.len(), 4);
+        assert!(grid.get(&Position::new(39, 39)).is_some());
+        // used to wrap around to the next row
+        assert!(grid.get(&Position::new(40, 0)).is_none());
+        assert!(grid.get(&Position::new(0, 40)).is_none());
+
+        grid.insert(&Position::new(40, 0), Gent::Intmd(Entity::Iron));
+        assert!(
+            grid.chunks
+                .iter()
+                .all(|chunk| chunk.get().is_none_or(|chunk| !chunk.changed))
+        );
+        assert_eq!(
+            grid.get(&Position::new(0, 1)).map(Gent::entity),
+            Some(Entity::Empty)
+        );
+    }
+
+    #[test]
+    fn test_generated_in_any_order() {
+        let terrain = Terrain {
+            seed: 3,
+            generator: Generator::Chunked,
+        };
+        let forward = Grid::generated(500, terrain);
+        let backward = Grid::generated(500, terrain);
+        let cells = |grid: &Grid, positions: Vec<Position>| -> Vec<Entity> {
+            positions
+                .iter()
+                .map(|pos| grid.get(pos).map(Gent::entity).unwrap())
+                .collect()
+        };
+        let positions: Vec<Position> = (0..500).map(|i| Position::new(i, (i * 7) % 500)).collect();
+        let reversed = positions.iter().rev().copied().collect();
+        let mut expected = cells(&forward, positions);
+        expected.reverse();
+        assert_eq!(expected, cells(&backward, reversed));
+        assert!(expected.contains(&Entity::Iron));
+    }
+
+    #[test]
+    fn test_saves_changed_chunks() {
+        let mut grid = Grid::generated(
+            1000,
+            Terrain {
+                seed: 3,
+                generator: Generator::Chunked,
+            },
+        );
+        // generated to be looked at, the same when generated again
+        let looked_at = grid.get(&Position::new(900, 900)).map(Gent::entity);
+        grid.insert(&Position::new(5, 5), Gent::Intmd(Entity::Gear));
+        for pos in Rect::new(64, 0, CHUNK_SIZE, CHUNK_SIZE).positions() {
+            grid.insert(&pos, Gent::Empty);
+        }
+
+        let json = serde_json::to_value(&grid).unwrap();
+        assert_eq!(json["size"], 1000);
+        assert_eq!(json["chunks"].as_object().unwrap().len(), 1);
+        assert_eq!(json["cleared"], serde_json::json!([2]));
+
+        let loaded: Grid = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            loaded.get(&Position::new(5, 5)).map(Gent::entity),
+            Some(Entity::Gear)
+        );
+        assert_eq!(
+            loaded.get(&Position::new(70, 10)).map(Gent::entity),
+            Some(Entity::Empty)
+        );
+        assert_eq!(
+            loaded.get(&Position::new(900, 900)).map(Gent::entity),
+            looked_at
+        );
+
+        let too_far =
+            serde_json::json!({"size": 40, "terrain": null, "chunks": {}, "cleared": [4]});
+        assert!(serde_json::from_value::<Grid>(too_far).is_err());
+    }
+
+    #[test]
+    fn test_saves_dense_terrain() {
+        let terrain = Terrain {
+            seed: 3,
+            generator: Generator::Dense,
+        };
+        let grid = Grid::generated(250, terrain);
+        let cells = |grid: &Grid| -> Vec<Entity> {
+            Rect::new(0, 0, 250, 250)
+                .positions()
+                .map(|pos| grid.get(&pos).map(Gent::entity).unwrap())
+                .collect()
+        };
+        let json = serde_json::to_value(&grid).unwrap();
+        assert_eq!(
+            json["terrain"],
+            serde_json::json!({"seed": 3, "generator": "Dense"})
+        );
+        // nothing changed, every chunk is generated again by the recorded generator
+        let loaded: Grid = serde_json::from_value(json).unwrap();
+        assert_eq!(cells(&loaded), cells(&grid));
+        let chunked = Grid::generated(
+            250,
+            Terrain {
+                generator: Generator::Chunked,
+                ..terrain
+            },
+        );
+        assert_ne!(cells(&chunked), cells(&grid));
+    }
 }
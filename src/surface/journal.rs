@@ -0,0 +1,257 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+use crate::app::AppResult;
+use crate::event::Event;
+use crate::surface::generation::Generator;
+use crate::surface::scenario::Scenario;
+use crate::surface::state::Seed;
+use crate::surface::{DEFAULT_WORLD_SIZE, Surface, cheat, generation};
+
+// every command applied to an agent, written next to the save as json lines:
+//   {"seed":{"Manual":1234},"world_size":250,"generator":"Dense","port_base":3333,"tick":5210}
+//   {"tick":12,"port":3335,"command":"MOVE","reply":"OKAY"}
+//   {"tick":40,"port":3333,"command":"TICK 100","reply":"OKAY","cheat":true}
+//   ...
+// replaying the commands on a new game with the same seed, size and generator rebuilds the save
+// exactly. games started from a scenario keep the scenario in the header and replay on it instead
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// tick count when the command was applied
+    pub tick: u64,
+    pub port: usize,
+    pub command: String,
+    pub reply: String,
+    /// typed into the ui rather than sent over tcp
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub manual: bool,
+    /// a cheat rather than a command for the agent on `port`, see [`super::cheat`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cheat: bool,
+}
+
+/// first line of a journal file
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    seed: Seed,
+    /// journals written before worlds could be resized are on the default size
+    #[serde(default = "default_world_size")]
+    world_size: u16,
+    /// journals written before the generator was recorded are all from the dense generator
+    #[serde(default)]
+    generator: Generator,
+    /// port of the HUD when the journal was written
+    port_base: usize,
+    /// tick count when the journal was written
+    tick: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scenario: Option<Scenario>,
+}
+
+fn default_world_size() -> u16 {
+    DEFAULT_WORLD_SIZE
+}
+
+#[derive(Debug, Default)]
+pub struct Journal {
+    pub entries: Vec<Entry>,
+}
+
+/// a journal read from disk, with what is needed to replay it
+#[derive(Debug)]
+pub struct JournalFile {
+    pub seed: Seed,
+    pub world_size: u16,
+    pub generator: Generator,
+    pub port_base: usize,
+    pub tick: u64,
+    pub scenario: Option<Scenario>,
+    pub entries: Vec<Entry>,
+}
+
+/// a replayed command whose reply differs from the recorded one
+#[derive(Debug)]
+pub struct Mismatch {
+    pub entry: Entry,
+    pub reply: String,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "tick {} port {} `{}` replied `{}`, recorded `{}`",
+            self.entry.tick, self.entry.port, self.entry.command, self.reply, self.entry.reply
+        )
+    }
+}
+
+impl Journal {
+    /// journal file that belongs to a save file
+    pub fn path(save_path: &Path) -> PathBuf {
+        save_path.with_extension("journal")
+    }
+
+    pub fn push(&mut self, entry: Entry) {
+        self.entries.push(entry);
+    }
+
+    /// moves the recorded ports along with the agents when a save is loaded on another port base
+    pub fn rebase_ports(&mut self, saved_base: usize, port_base: usize) {
+        for entry in self.entries.iter_mut() {
+            entry.port = entry.port - saved_base + port_base;
+            if entry.cheat {
+                entry.command = cheat::rebase_command(&entry.command, saved_base, port_base);
+            }
+        }
+    }
+
+    pub fn save(&self, surface: &Surface, path: &Path) -> AppResult<()> {
+        std::fs::write(path, self.encode(surface)?)?;
+        tracing::info!("saved {} journal entries to {path:?}", self.entries.len());
+        Ok(())
+    }
+
+    /// the journal file's contents, with a header taken from `surface`
+    pub fn encode(&self, surface: &Surface) -> AppResult<Vec<u8>> {
+        self.snapshot(surface).encode()
+    }
+
+    /// a copy of the journal with a header taken from `surface`, to be encoded later
+    pub fn snapshot(&self, surface: &Surface) -> JournalFile {
+        JournalFile {
+            seed: surface.game_state.stats.seed,
+            world_size: surface.grid.size(),
+            generator: surface
+                .grid
+                .terrain()
+                .map(|terrain| terrain.generator)
+                .unwrap_or_default(),
+            port_base: surface
+                .agents
+                .keys()
+                .next()
+                .copied()
+                .unwrap_or(crate::tcp::port_base()),
+            tick: surface.game_state.stats.tick_count,
+            scenario: surface
+                .scenario
+                .as_ref()
+                .map(|progress| progress.scenario.clone()),
+            entries: self.entries.clone(),
+        }
+    }
+
+    pub fn load(path: &Path) -> AppResult<JournalFile> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let header: Header = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)?,
+            None => return Err(format!("journal {path:?} is empty").into()),
+        };
+        let mut entries = vec![];
+        for line in lines {
+            let line = line?;
+            if !line.trim().is_empty() {
+                entries.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(JournalFile {
+            seed: header.seed,
+            world_size: header.world_size,
+            generator: header.generator,
+            port_base: header.port_base,
+            tick: header.tick,
+            scenario: header.scenario,
+            entries,
+        })
+    }
+}
+
+impl JournalFile {
+    /// the journal file's contents
+    pub fn encode(&self) -> AppResult<Vec<u8>> {
+        let mut writer = vec![];
+        let header = Header {
+            seed: self.seed,
+            world_size: self.world_size,
+            generator: self.generator,
+            port_base: self.port_base,
+            tick: self.tick,
+            scenario: self.scenario.clone(),
+        };
+        serde_json::to_writer(&mut writer, &header)?;
+        writeln!(writer)?;
+        for entry in self.entries.iter() {
+            serde_json::to_writer(&mut writer, entry)?;
+            writeln!(writer)?;
+        }
+        Ok(writer)
+    }
+
+    /// entries with their ports moved from the recorded port base to the current one
+    fn rebased_entries(&self) -> Vec<Entry> {
+        let mut journal = Journal {
+            entries: self.entries.clone(),
+        };
+        journal.rebase_ports(self.port_base, crate::tcp::port_base());
+        journal.entries
+    }
+}
+
+/// rebuilds the world from the journal's seed and size and re-applies every command at the tick it was
+/// applied, returning the surface and every command whose reply differed from the recording
+pub fn replay(
+    file: &JournalFile,
+    event_sender: UnboundedSender<Event>,
+) -> AppResult<(Surface, Vec<Mismatch>)> {
+    // ticks requested by replayed STEP commands are already in the journal, so they must not
+    // reach the running game
+    let (replay_sender, _replay_receiver) = mpsc::unbounded_channel();
+    let mut surface = match &file.scenario {
+        Some(scenario) => scenario.new_game(replay_sender)?,
+        None => generation::new_game_generated_by(
+            replay_sender,
+            file.seed,
+            file.world_size,
+            file.generator,
+        )?,
+    };
+    // the journal's speed commands pause the replay, not the running game
+    surface.replay_paused = Some(false);
+    let mut mismatches = vec![];
+    for entry in file.rebased_entries() {
+        while surface.game_state.stats.tick_count < entry.tick {
+            surface.tick();
+        }
+        let reply = if entry.cheat {
+            surface.cheat(&entry.command)
+        } else if entry.manual {
+            surface.update_agent_manual(&entry.port, entry.command.clone())
+        } else {
+            surface.send(entry.port, &entry.command)
+        };
+        if reply.text != entry.reply {
+            mismatches.push(Mismatch {
+                entry,
+                reply: reply.text,
+            });
+        }
+    }
+    while surface.game_state.stats.tick_count < file.tick {
+        surface.tick();
+    }
+    surface.replay_paused = None;
+    surface.event_sender = event_sender;
+    tracing::info!(
+        "replayed {} commands over {} ticks, {} mismatched",
+        file.entries.len(),
+        file.tick,
+        mismatches.len()
+    );
+    Ok((surface, mismatches))
+}
@@ -1,6 +1,5 @@
 use ratatui::Frame;
 use ratatui::layout::{Position, Rect};
-use ratatui::style::Color;
 use ratatui::widgets::Gauge;
 
 use tachyonfx::{Effect, fx};
@@ -9,32 +8,48 @@ use rand::Rng;
 
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::oneshot;
 
 use crate::agents::hud::{self, Hud};
-use crate::agents::{Agent, Comms, UpdateEnum};
+use crate::agents::{Agent, CommandError, Comms, Response, Topic, UpdateEnum};
+use crate::app::AppResult;
 use crate::entities::Entity;
 use crate::event::Event;
+use crate::speed::{self, Preset};
 use crate::tech_tree::TechTree;
 use crate::ui::render_effect_clamped;
-use crate::utils::{idx_to_pos, pos_to_idx};
 use crate::widgets::button::BorderedButton;
 
 use thiserror::Error;
 
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::net::SocketAddr;
 
 use serde_with::serde_as;
 
+pub mod cheat;
 pub mod generation;
 pub mod grid;
+pub mod journal;
+pub mod recording;
+pub mod scenario;
+pub mod schema;
 pub mod state;
 pub mod tutorial;
 use crate::surface::grid::{Gent, Grid};
+use crate::surface::journal::{Entry, Journal};
+use crate::surface::recording::Recorder;
+use crate::surface::scenario::Progress;
 use crate::surface::state::{GameState, GameStats, Seed, VERSION, Version, VictoryStats};
 use crate::surface::tutorial::Tutorial;
 
-//const GRID_SIZE: usize = 1000;
-const GRID_SIZE: usize = 250;
+/// cells per side of a new world unless another size is chosen
+pub const DEFAULT_WORLD_SIZE: u16 = 250;
+/// world sizes offered when starting a new game
+pub const WORLD_SIZES: [u16; 5] = [DEFAULT_WORLD_SIZE, 500, 1000, 2000, 4000];
+/// the starting agents need room around the center
+pub const MIN_WORLD_SIZE: u16 = 64;
+pub const MAX_WORLD_SIZE: u16 = 16_000;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Focus {
@@ -110,6 +125,8 @@ pub struct Surface {
     pub power: Power,
     pub game_state: GameState,
     pub victory_stats: Option<VictoryStats>,
+    /// the scenario the game was started from, see [`scenario`]
+    pub scenario: Option<Progress>,
 
     #[serde(skip)]
     pub current_research_button: BorderedButton<Gauge<'static>>,
@@ -123,6 +140,30 @@ pub struct Surface {
     #[serde(skip)]
     pub previous_command_counter: u8,
 
+    /// when set, remote commands are queued and applied in port order at the start of each tick
+    #[serde(skip)]
+    pub lockstep: bool,
+
+    /// whether agents are served over tcp, see [`Surface::listen`]
+    #[serde(skip)]
+    listening: bool,
+
+    #[serde(skip)]
+    command_queue: BTreeMap<usize, VecDeque<(String, oneshot::Sender<Response>)>>,
+
+    /// every command applied since the start of the game, see [`journal`]
+    #[serde(skip)]
+    pub journal: Journal,
+
+    /// pause state of a game rebuilt from its journal, `None` while playing. replayed speed
+    /// commands change it instead of the running game's [`speed`]
+    #[serde(skip)]
+    pub replay_paused: Option<bool>,
+
+    /// writes snapshots for the replay viewer, see [`recording`]
+    #[serde(skip)]
+    pub recorder: Option<Recorder>,
+
     // TODO move this back up to App and use a reference/smart pointer?
     #[serde(skip)]
     pub event_sender: UnboundedSender<Event>,
@@ -166,26 +207,44 @@ impl Surface {
     }
 
     pub fn tick(&mut self) {
-        if self.game_state.tech_tree.victory_achieved && self.victory_stats.is_none() {
+        let victory = match self.scenario.as_mut() {
+            Some(progress) if progress.has_goals() => {
+                progress.update(
+                    self.game_state.stats.tick_count,
+                    &self.agents,
+                    &self.grid,
+                    &self.game_state,
+                );
+                progress.won()
+            }
+            _ => self.game_state.tech_tree.victory_achieved,
+        };
+        if victory && self.victory_stats.is_none() {
             self.victory_stats = Some(VictoryStats {
                 stats: self.game_state.stats.clone(),
                 show_victory: true,
             });
         }
         self.game_state.stats.tick_count += 1;
-        let positions: Vec<Position> = self
+        let positions: Vec<(usize, Position)> = self
             .agents
             .values()
-            .filter_map(|comms| comms.position)
+            .filter_map(|comms| comms.position.map(|pos| (comms.port, pos)))
             .collect();
-        for pos in positions {
+        for (port, pos) in positions {
             if let Some(Gent::Age(agent)) = self.grid.get_mut(&pos) {
                 agent.tick(&mut self.power);
+                if let Some(comms) = self.agents.get(&port) {
+                    for topic in agent.notifications() {
+                        comms.notify(topic);
+                    }
+                }
             }
         }
         // TODO make solar_irradiance sinusoidal based on tick_count
         let solar_irradiance = 400;
         self.power.update(solar_irradiance);
+        self.game_state.stats.power_consumed += self.power.consumption as u64;
 
         let mut agents_to_delete = vec![];
         for comms in self.agents.values() {
@@ -242,10 +301,26 @@ impl Surface {
             tracing::info!("atd: {port}");
             self.delete_agent(&port);
         }
+        if let Some(mut recorder) = self.recorder.take() {
+            recorder.tick(self);
+            self.recorder = Some(recorder);
+        }
+    }
+
+    /// records the surface now and then every `interval` ticks to `path`
+    pub fn start_recording(&mut self, path: &std::path::Path, interval: u64) -> AppResult<()> {
+        let mut recorder = Recorder::create(path, interval)?;
+        recorder.record(self)?;
+        self.recorder = Some(recorder);
+        Ok(())
     }
 
     pub fn next_available_port(&self) -> usize {
-        *self.agents.keys().max().unwrap_or(&3332) + 1
+        self.agents
+            .keys()
+            .max()
+            .map(|port| port + 1)
+            .unwrap_or_else(crate::tcp::port_base)
     }
 
     pub fn frame_position(&self, grid_position: &Position) -> Option<Position> {
@@ -298,56 +373,8 @@ impl Surface {
     }
 
     pub fn render_grid(&self, frame: &mut Frame, area: Rect) {
-        let no_offset = Position { x: 0, y: 0 };
-        let clamped_area = area.clamp(frame.area());
-        let buf = frame.buffer_mut();
-        for pos in clamped_area.positions() {
-            let cell = &mut buf[(pos.x, pos.y)];
-            let grid_pos = self.grid_position(&pos);
-            //tracing::info!("grid_pos: {grid_pos:?}");
-            match self.grid.get_direct(&grid_pos) {
-                None => {
-                    cell.set_char(' ');
-                    cell.bg = Color::Red;
-                }
-                Some(Gent::Intmd(p)) => {
-                    cell.set_char(p.character());
-                    cell.bg = p.bg();
-                    cell.fg = p.fg();
-                }
-                Some(Gent::Age(agent)) => {
-                    agent.render_surface_cell(&no_offset, cell);
-                }
-                // TODO just take props and render lowercase
-                Some(Gent::Large(ent_pos)) => {
-                    if let (Some(x), Some(y)) = (
-                        grid_pos.x.checked_sub(ent_pos.x),
-                        grid_pos.y.checked_sub(ent_pos.y),
-                    ) {
-                        let offset = Position::new(x, y);
-                        match self.grid.get_direct(ent_pos) {
-                            Some(Gent::Age(agent)) => agent.render_surface_cell(&offset, cell),
-                            Some(Gent::Intmd(p)) => {
-                                cell.set_char(p.character().to_ascii_lowercase());
-                                cell.bg = p.bg();
-                                cell.fg = p.fg();
-                            }
-                            _ => (),
-                        }
-                    }
-                }
-                Some(Gent::Empty) => {
-                    // TODO not efficent
-                    // should do once on startup, but not every render
-                    // could just paint everyting black once in main before loop?
-                    // and on resizing?
-                    cell.bg = Color::Black;
-                }
-                Some(Gent::BeingUpdated) => {
-                    tracing::error!("this code should not be reached");
-                }
-            }
-        }
+        let offset = Position::new(self.x as u16, self.y as u16);
+        self.grid.render(frame, area, offset);
     }
 
     // TODO
@@ -360,7 +387,6 @@ impl Surface {
     }
 
     pub fn add_entity(&mut self, pos: &Position, prop: Entity) -> Result<(), AddEntityError> {
-        let idx = pos_to_idx(pos, GRID_SIZE);
         match prop.footprint() {
             Some(fp) => {
                 let rect = Rect {
@@ -372,13 +398,12 @@ impl Surface {
                 if self.grid.buildable(rect) {
                     prop.on_attach_to_power_grid(&mut self.power);
                     self.grid.insert(pos, Gent::Intmd(prop));
-                    for pos in rect.positions().skip(1) {
-                        self.grid
-                            .insert(&pos, Gent::Large(idx_to_pos(idx, GRID_SIZE)))
+                    for large_pos in rect.positions().skip(1) {
+                        self.grid.insert(&large_pos, Gent::Large(*pos))
                     }
                     Ok(())
                 } else {
-                    tracing::error!("occupied idx: {}, {}", idx, prop);
+                    tracing::error!("occupied {:?}, {}", pos, prop);
                     Err(AddEntityError::Occupied)
                 }
             }
@@ -390,19 +415,18 @@ impl Surface {
                     Ok(())
                 }
                 _ => {
-                    tracing::error!("occupied idx: {}, {}", idx, prop);
+                    tracing::error!("occupied {:?}, {}", pos, prop);
                     Err(AddEntityError::Occupied)
                 }
             },
         }
     }
 
-    pub async fn add_agent(
+    pub fn add_agent(
         &mut self,
         pos: &Position,
         agent: Box<dyn Agent + 'static>,
     ) -> Result<usize, AddEntityError> {
-        let idx = pos_to_idx(pos, GRID_SIZE);
         let entity = agent.entity();
         match agent.entity().footprint() {
             Some(fp) => {
@@ -414,7 +438,10 @@ impl Surface {
                 };
                 if self.grid.buildable(rect) {
                     self.grid.insert(pos, Gent::Age(agent));
-                    let comms = Comms::new(self, Some(rect), entity).await;
+                    let mut comms = Comms::new(self, Some(rect), entity);
+                    if self.listening {
+                        comms.listen(&self.event_sender);
+                    }
                     self.game_state
                         .stats
                         .agent_count
@@ -423,13 +450,12 @@ impl Surface {
                         .or_insert(1);
                     let port = comms.port;
                     self.agents.insert(port, comms);
-                    for pos in rect.positions().skip(1) {
-                        self.grid
-                            .insert(&pos, Gent::Large(idx_to_pos(idx, GRID_SIZE)))
+                    for large_pos in rect.positions().skip(1) {
+                        self.grid.insert(&large_pos, Gent::Large(*pos))
                     }
                     Ok(port)
                 } else {
-                    tracing::error!("occupied idx: {}, {}", idx, entity);
+                    tracing::error!("occupied {:?}, {}", pos, entity);
                     Err(AddEntityError::Occupied)
                 }
             }
@@ -445,7 +471,10 @@ impl Surface {
                             width: 1,
                             height: 1,
                         };
-                        let comms = Comms::new(self, Some(location), entity).await;
+                        let mut comms = Comms::new(self, Some(location), entity);
+                        if self.listening {
+                            comms.listen(&self.event_sender);
+                        }
                         self.game_state
                             .stats
                             .agent_count
@@ -457,7 +486,7 @@ impl Surface {
                         Ok(port)
                     }
                     _ => {
-                        tracing::error!("occupied idx: {}, {}", idx, entity);
+                        tracing::error!("occupied {:?}, {}", pos, entity);
                         Err(AddEntityError::Occupied)
                     }
                 }
@@ -526,59 +555,245 @@ impl Surface {
         self.agents.get(port).and_then(|comms| comms.position)
     }
 
-    pub async fn update_agent_remote(&mut self, port: &usize, msg: String) {
+    pub fn set_agent_address(&mut self, port: &usize, address: Option<SocketAddr>) {
+        if let Some(comms) = self.agents.get_mut(port) {
+            comms.address = address;
+        } else {
+            tracing::warn!("expected agent at port {port}");
+        }
+    }
+
+    /// applies a command from a tcp client, or queues it for the next tick in lockstep mode and
+    /// while paused
+    pub fn receive_agent_command(
+        &mut self,
+        port: usize,
+        msg: String,
+        reply: oneshot::Sender<Response>,
+    ) {
+        if (self.lockstep || speed::is_paused()) && !self.is_control_command(&port, &msg) {
+            self.command_queue
+                .entry(port)
+                .or_default()
+                .push_back((msg, reply));
+        } else {
+            self.update_agent_remote(&port, msg, reply);
+        }
+    }
+
+    /// HUD commands that change the speed, so the HUD can still resume a paused game
+    fn is_control_command(&self, port: &usize, msg: &str) -> bool {
+        self.agent_position(port).is_none() && Hud::is_control_command(msg)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.replay_paused.unwrap_or_else(speed::is_paused)
+    }
+
+    /// runs `ticks` ticks while paused, returns false if not paused
+    pub fn step(&self, ticks: u64) -> bool {
+        if !self.is_paused() {
+            return false;
+        }
+        // a replay runs the stepped ticks from the journal's tick counts
+        if self.replay_paused.is_some() {
+            return true;
+        }
+        for _ in 0..ticks {
+            if let Err(e) = self.event_sender.send(Event::Tick) {
+                tracing::error!("failed to step: {e}");
+            }
+        }
+        true
+    }
+
+    /// applies queued commands ordered by port, so runs with the same seed and scripts match
+    pub fn resolve_queued_commands(&mut self) {
+        let queue = std::mem::take(&mut self.command_queue);
+        for (port, commands) in queue {
+            for (msg, reply) in commands {
+                self.update_agent_remote(&port, msg, reply);
+            }
+        }
+    }
+
+    /// applies a command from a tcp client and sends the reply back to its connection
+    pub fn update_agent_remote(
+        &mut self,
+        port: &usize,
+        msg: String,
+        reply_sender: oneshot::Sender<Response>,
+    ) {
+        // the client disconnected before the command was applied
+        if reply_sender.is_closed() {
+            return;
+        }
+        // the client may have disconnected while waiting
+        let _ = reply_sender.send(self.send(*port, &msg));
+    }
+
+    /// applies `command` to the agent on `port` and returns its reply, the same as a command
+    /// received over tcp but without any transport
+    pub fn send(&mut self, port: usize, command: &str) -> Response {
+        // only reachable through the multiplexed port or the library
+        if !self.agents.contains_key(&port) {
+            return Response::error(CommandError::UnknownAgent(port));
+        }
         self.game_state.stats.tcp_command_count += 1;
-        let reply = if let Some(pos) = self.agent_position(port) {
+        let msg = command.to_string();
+        let reply = if let Some(pos) = self.agent_position(&port) {
             let gent = self.grid.pop(&pos);
             if let Some(Gent::Age(agent)) = gent {
-                self.update_agent(msg.clone(), pos, port, agent).await
+                self.update_agent(msg.clone(), pos, &port, agent)
             } else {
                 tracing::error!("expected agent at {pos:?}");
-                return;
+                return Response::error(CommandError::NoReply);
             }
         } else {
-            self.handle_hud_command(msg.clone()).to_string()
+            Response::new(self.handle_hud_command(msg.clone()))
         };
-        if let Some(comms) = self.agents.get_mut(port) {
-            comms.log.push((msg, reply.clone()));
-            comms.sender().send(reply).await.expect("TODO");
+        self.record(port, &msg, &reply, false);
+        if let Some(comms) = self.agents.get_mut(&port) {
+            comms.log.push((msg, reply.text.clone()));
         }
+        reply
     }
 
-    pub async fn update_agent_manual(&mut self, port: &usize, msg: String) {
+    fn record(&mut self, port: usize, command: &str, reply: &Response, manual: bool) {
+        self.journal.push(Entry {
+            tick: self.game_state.stats.tick_count,
+            port,
+            command: command.to_string(),
+            reply: reply.text.clone(),
+            manual,
+            cheat: false,
+        });
+    }
+
+    /// binds a tcp listener for every agent, including agents built later
+    pub fn listen(&mut self) {
+        self.listening = true;
+        for comms in self.agents.values_mut() {
+            comms.listen(&self.event_sender);
+        }
+    }
+
+    pub fn update_agent_manual(&mut self, port: &usize, msg: String) -> Response {
         self.game_state.stats.manual_command_count += 1;
-        let reply = self.handle_message(port, msg.clone()).await;
+        let reply = self.handle_message(port, msg.clone());
+        self.record(*port, &msg, &reply, true);
         if let Some(comms) = self.agents.get_mut(port) {
-            comms.log.push((msg, reply));
+            comms.log.push((msg, reply.text.clone()));
+        }
+        reply
+    }
+
+    fn hud_port(&self) -> Option<usize> {
+        self.agents
+            .iter()
+            .find(|(_, comms)| comms.entity == Entity::HUD)
+            .map(|(port, _)| *port)
+    }
+
+    /// a command typed for the HUD, so it is journaled like one sent to its port
+    fn update_hud_manual(&mut self, command: String) -> Response {
+        match self.hud_port() {
+            Some(port) => self.update_agent_manual(&port, command),
+            None => Response::error(CommandError::NoReply),
+        }
+    }
+
+    /// research picked in the tech tree, sent as the HUD's `RESR` so replays pick it too
+    pub fn select_research(&mut self, idx: usize) -> Response {
+        let Some(tech) = self.game_state.tech_tree.get_tech(idx) else {
+            return Response::error(CommandError::UnknownResearch);
+        };
+        self.update_hud_manual(format!("RESR {}", tech.kind))
+    }
+
+    /// speed picked from the keyboard or pause menu, sent as the HUD's `SPED` so replays know
+    /// when the game was paused
+    pub fn set_speed(&mut self, preset: Preset) -> Response {
+        self.update_hud_manual(format!("SPED {preset}"))
+    }
+
+    /// pauses, or resumes at the speed before the pause
+    pub fn toggle_pause(&mut self) {
+        if !speed::is_paused() {
+            self.set_speed(Preset::Paused);
+        } else if let Some(preset) = Preset::running() {
+            self.set_speed(preset);
+        } else {
+            // rates set on the command line have no preset to journal
+            speed::set_paused(false);
+        }
+    }
+
+    /// journals the running game's pause state when it differs from the journal's, so a game
+    /// started or loaded while paused replays paused too
+    pub fn journal_pause(&mut self) {
+        let hud_port = self.hud_port();
+        let journaled = self
+            .journal
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| Some(entry.port) == hud_port && !entry.cheat)
+            .find_map(|entry| match Hud::parse_command(&entry.command) {
+                Ok(hud::Command::SPED(preset)) => Some(preset == Preset::Paused),
+                _ => None,
+            })
+            .unwrap_or(false);
+        if journaled != speed::is_paused()
+            && let Some(preset) = Preset::current()
+        {
+            self.set_speed(preset);
         }
     }
 
-    async fn handle_message(&mut self, port: &usize, msg: String) -> String {
+    fn handle_message(&mut self, port: &usize, msg: String) -> Response {
         if let Some(pos) = self.agent_position(port)
             && let Some(Gent::Age(agent)) = self.grid.pop(&pos)
         {
-            self.update_agent(msg.clone(), pos, port, agent).await
+            self.update_agent(msg.clone(), pos, port, agent)
         } else {
-            self.handle_hud_command(msg.clone()).to_string()
+            Response::new(self.handle_hud_command(msg.clone()))
         }
     }
 
-    async fn update_agent(
+    fn update_agent(
         &mut self,
         msg: String,
         pos: Position,
         port: &usize,
         mut agent: Box<dyn Agent>,
-    ) -> String {
+    ) -> Response {
+        let research_complete = self.game_state.stats.research_complete;
         let update = agent.handle_message(&pos, &mut self.grid, &mut self.game_state, msg);
+        if let Some(comms) = self.agents.get(port) {
+            for topic in agent.notifications() {
+                comms.notify(topic);
+            }
+        }
+        if self.game_state.stats.research_complete > research_complete
+            && let Some(hud) = self
+                .agents
+                .values()
+                .find(|comms| comms.entity == Entity::HUD)
+        {
+            hud.notify(Topic::RESEARCH_DONE);
+        }
         match update {
             UpdateEnum::BuildAgent {
                 pos: build_position,
                 agent: new_agent,
             } => {
                 self.grid.insert(&pos, Gent::Age(agent));
-                match self.add_agent(&build_position, new_agent).await {
-                    Ok(new_port) => format!("PORT {new_port}"),
+                match self.add_agent(&build_position, new_agent) {
+                    Ok(new_port) => Response {
+                        text: format!("PORT {new_port}"),
+                        json: serde_json::json!({ "PORT": new_port }),
+                    },
                     Err(e) => {
                         tracing::error!("error adding agent: {e}");
                         // TODO do we want to panic?
@@ -592,7 +807,7 @@ impl Surface {
             } => {
                 self.grid.insert(&pos, Gent::Age(agent));
                 match self.add_entity(&build_position, entity) {
-                    Ok(_) => "OKAY".to_string(),
+                    Ok(_) => Response::okay(),
                     Err(e) => {
                         tracing::error!("error adding entity: {e}");
                         // TODO do we want to panic?
@@ -612,13 +827,14 @@ impl Surface {
                 });
                 self.grid.insert(&new_position, Gent::Age(agent));
                 self.grid.insert(&pos, Gent::Empty);
-                "OKAY".to_string()
+                Response::okay()
             }
             UpdateEnum::Research => {
+                self.grid.insert(&pos, Gent::Age(agent));
                 let (titles, guage) = self.game_state.tech_tree.current_research_content();
                 self.current_research_button.set_content(guage);
                 self.current_research_button.set_titles(titles);
-                "OKAY".to_string()
+                Response::okay()
             }
             UpdateEnum::Reply(reply) => {
                 self.grid.insert(&pos, Gent::Age(agent));
@@ -628,22 +844,19 @@ impl Surface {
     }
 
     pub fn move_up(&mut self, amount: usize) {
-        let res = self.y.saturating_sub(amount);
-        if res < GRID_SIZE {
-            self.y = res;
-        }
+        self.y = self.y.saturating_sub(amount);
     }
 
     pub fn move_down(&mut self, amount: usize) {
         let res = self.y.saturating_add(amount);
-        if res < GRID_SIZE {
+        if res < self.grid.size().into() {
             self.y = res;
         }
     }
 
     pub fn move_right(&mut self, amount: usize) {
         let res = self.x.saturating_add(amount);
-        if res < GRID_SIZE {
+        if res < self.grid.size().into() {
             self.x = res;
         }
     }
@@ -656,6 +869,21 @@ impl Surface {
         match Hud::parse_command(&msg) {
             Ok(command) => match command {
                 hud::Command::STAT_POWR => (&self.power).into(),
+                hud::Command::STAT_SPED => hud::Reply::speed(),
+                hud::Command::SPED(preset) => {
+                    match self.replay_paused.as_mut() {
+                        Some(paused) => *paused = preset == Preset::Paused,
+                        None => preset.apply(),
+                    }
+                    hud::Reply::SPED
+                }
+                hud::Command::STEP(ticks) => {
+                    if self.step(ticks) {
+                        hud::Reply::STEP
+                    } else {
+                        hud::Reply::ERRR(CommandError::NotPaused)
+                    }
+                }
                 hud::Command::LIST_AGNT => {
                     if let Some((port, _)) = self.agents.iter().nth(self.hud.agent_idx)
                         && let Some(agent) = self.get_agent(port)
@@ -666,7 +894,7 @@ impl Surface {
                     } else {
                         self.hud.agent_idx = 1;
                         hud::Reply::LIST_AGNT {
-                            port: 3333,
+                            port: crate::tcp::port_base(),
                             kind: "HUD".to_string(),
                         }
                     }
@@ -702,11 +930,11 @@ impl Surface {
                     }
                 }
             },
-            Err(e) => hud::Reply::ERRR(e),
+            Err(e) => hud::Reply::ERRR(CommandError::InvalidCommand(e)),
         }
     }
 
-    pub async fn new(
+    pub fn new(
         grid: Grid,
         x: usize,
         y: usize,
@@ -734,15 +962,22 @@ impl Surface {
             event_sender,
             game_state,
             victory_stats: None,
+            scenario: None,
             power: Power::default(),
             effects: vec![],
             focus: None,
             previous_command_counter: 0,
+            lockstep: false,
+            listening: false,
+            command_queue: BTreeMap::new(),
+            journal: Journal::default(),
+            replay_paused: None,
+            recorder: None,
             hud: Hud::default(),
             current_research_button,
         };
-        let comms = Comms::new(&surface, None, Entity::HUD).await;
-        surface.agents.insert(3333, comms);
+        let comms = Comms::new(&surface, None, Entity::HUD);
+        surface.agents.insert(comms.port, comms);
         surface
     }
 }
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agents::Comms;
+use crate::app::AppResult;
+use crate::surface::grid::Grid;
+use crate::surface::state::{GameStats, VERSION, Version};
+use crate::surface::{Power, Surface};
+
+// a recording is the version followed by a bincode snapshot of the surface every `interval`
+// ticks, each snapshot is flushed as it is written so a run that is killed is still viewable.
+// snapshots have no size limit, the same as saves, as they grow with the world
+
+/// the parts of a surface needed to watch it, borrowed for writing
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    grid: &'a Grid,
+    agents: &'a BTreeMap<usize, Comms>,
+    power: &'a Power,
+    stats: &'a GameStats,
+}
+
+/// the surface at one tick of a recording
+#[derive(Debug, Deserialize)]
+pub struct Snapshot {
+    pub grid: Grid,
+    pub agents: BTreeMap<usize, Comms>,
+    pub power: Power,
+    pub stats: GameStats,
+}
+
+impl Snapshot {
+    pub fn tick(&self) -> u64 {
+        self.stats.tick_count
+    }
+}
+
+/// writes snapshots of a surface while it runs
+pub struct Recorder {
+    writer: BufWriter<File>,
+    interval: u64,
+}
+
+impl std::fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Recorder every {} ticks", self.interval)
+    }
+}
+
+impl Recorder {
+    /// starts a recording at `path`, replacing any recording already there
+    pub fn create(path: &Path, interval: u64) -> AppResult<Recorder> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        bincode::serde::encode_into_std_write(VERSION, &mut writer, bincode::config::standard())?;
+        tracing::info!("recording to {path:?} every {interval} ticks");
+        Ok(Recorder {
+            writer,
+            interval: interval.max(1),
+        })
+    }
+
+    pub fn record(&mut self, surface: &Surface) -> AppResult<()> {
+        let snapshot = SnapshotRef {
+            grid: &surface.grid,
+            agents: &surface.agents,
+            power: &surface.power,
+            stats: &surface.game_state.stats,
+        };
+        bincode::serde::encode_into_std_write(
+            snapshot,
+            &mut self.writer,
+            bincode::config::standard(),
+        )?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// records the surface if a snapshot is due this tick
+    pub fn tick(&mut self, surface: &Surface) {
+        if surface
+            .game_state
+            .stats
+            .tick_count
+            .is_multiple_of(self.interval)
+            && let Err(e) = self.record(surface)
+        {
+            tracing::error!("failed to record snapshot: {e}");
+        }
+    }
+}
+
+/// something that happened between two snapshots, read from their [`GameStats`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Milestone {
+    /// the number of completed research
+    Research(usize),
+    /// an agent of this kind was built
+    Build(String),
+}
+
+impl std::fmt::Display for Milestone {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Milestone::Research(complete) => write!(f, "research #{complete}"),
+            Milestone::Build(kind) => write!(f, "built {kind}"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Recording {
+    pub snapshots: Vec<Snapshot>,
+}
+
+impl Recording {
+    pub fn load(path: &Path) -> AppResult<Recording> {
+        let bytes = std::fs::read(path)?;
+        let (version, mut offset): (Version, usize) =
+            bincode::serde::decode_from_slice(&bytes, bincode::config::standard())?;
+        if !version.compatible() {
+            return Err(format!(
+                "recording format {version} is incompatible with texaform version {VERSION}"
+            )
+            .into());
+        }
+        let config = bincode::config::standard();
+        let mut snapshots = vec![];
+        while offset < bytes.len() {
+            match bincode::serde::decode_from_slice::<Snapshot, _>(&bytes[offset..], config) {
+                Ok((snapshot, len)) => {
+                    snapshots.push(snapshot);
+                    offset += len;
+                }
+                // the recording was cut off while a snapshot was written
+                Err(e) => {
+                    tracing::warn!("ignoring the end of {path:?} after a bad snapshot: {e}");
+                    break;
+                }
+            }
+        }
+        if snapshots.is_empty() {
+            return Err(format!("recording {path:?} has no snapshots").into());
+        }
+        tracing::info!("loaded {} snapshots from {path:?}", snapshots.len());
+        Ok(Recording { snapshots })
+    }
+
+    pub fn first_tick(&self) -> u64 {
+        self.snapshots
+            .first()
+            .map(Snapshot::tick)
+            .unwrap_or_default()
+    }
+
+    pub fn last_tick(&self) -> u64 {
+        self.snapshots
+            .last()
+            .map(Snapshot::tick)
+            .unwrap_or_default()
+    }
+
+    /// the latest snapshot at or before `tick`
+    pub fn at(&self, tick: u64) -> Option<&Snapshot> {
+        let idx = self.snapshots.partition_point(|s| s.tick() <= tick);
+        self.snapshots.get(idx.saturating_sub(1))
+    }
+
+    /// research completions and agent builds, at the tick of the first snapshot they appear in
+    pub fn milestones(&self) -> Vec<(u64, Milestone)> {
+        let mut milestones = vec![];
+        for pair in self.snapshots.windows(2) {
+            let (before, after) = (&pair[0].stats, &pair[1].stats);
+            for complete in (before.research_complete + 1)..=after.research_complete {
+                milestones.push((after.tick_count, Milestone::Research(complete)));
+            }
+            for (kind, count) in after.agent_count.iter() {
+                let previous = before.agent_count.get(kind).copied().unwrap_or_default();
+                for _ in previous..*count {
+                    milestones.push((after.tick_count, Milestone::Build(kind.clone())));
+                }
+            }
+        }
+        milestones
+    }
+}
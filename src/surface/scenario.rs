@@ -0,0 +1,453 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use petgraph::graph::NodeIndex;
+use ratatui::layout::Position;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use strum::IntoEnumIterator;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::agents::Comms;
+use crate::app::AppResult;
+use crate::entities::Entity;
+use crate::event::Event;
+use crate::surface::grid::{Gent, Grid};
+use crate::surface::state::{GameState, Seed};
+use crate::surface::tutorial::Tutorial;
+use crate::surface::{
+    DEFAULT_WORLD_SIZE, Focus, MAX_WORLD_SIZE, MIN_WORLD_SIZE, Surface, generation,
+};
+use crate::tech_tree::TechKind;
+
+// a scenario is a json file describing how a game starts and how it is won:
+//   {
+//     "name": "GEAR QUOTA",
+//     "map": {"seed": 7},
+//     "world_size": 500,
+//     "agents": [{"kind": "LASER_CUTTER", "at": [0, 0], "inventory": {"BUFFER_IN": ["IRON_PLATE"]}}],
+//     "unlocked": ["DOG", "LASER_CUTTER"],
+//     "tech": [{"tech": "SMELTER", "researched": true}],
+//     "goals": [{"hold": {"entity": "GEAR", "count": 20, "container": "BUFFER_OUT"}, "by_tick": 5000}]
+//   }
+// positions are `[x, y]` offsets from the center of the surface, where seeded maps clear a
+// starting area and hand authored maps put the top left of their rows
+//
+// the bundled scenarios are in assets/scenarios, more are read from the scenarios directory in
+// the data directory
+
+const BUNDLED: [&str; 3] = [
+    include_str!("../../assets/scenarios/first_research.json"),
+    include_str!("../../assets/scenarios/gear_quota.json"),
+    include_str!("../../assets/scenarios/pack_of_dogs.json"),
+];
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub map: Map,
+    /// cells per side of the surface, defaults to [`DEFAULT_WORLD_SIZE`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub world_size: Option<u16>,
+    #[serde(default)]
+    pub agents: Vec<StartingAgent>,
+    /// entities that are not agents, e.g. SOLAR_PANNEL
+    #[serde(default)]
+    pub entities: Vec<StartingEntity>,
+    /// entities FABRICATOR can make from the start, defaults to DOG
+    #[serde_as(as = "Option<Vec<DisplayFromStr>>")]
+    #[serde(default)]
+    pub unlocked: Option<Vec<Entity>>,
+    #[serde(default)]
+    pub tech: Vec<TechOverride>,
+    /// all must be met to win, without goals the game is won by researching SELF_SUFFICIENT
+    #[serde(default)]
+    pub goals: Vec<Goal>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Map {
+    /// generated the same way as a new game with this seed
+    Seed(u64),
+    /// rows of entity characters, e.g. `I` for IRON, with `.` or ` ` for empty
+    Grid(Vec<String>),
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StartingAgent {
+    #[serde_as(as = "DisplayFromStr")]
+    pub kind: Entity,
+    pub at: (i32, i32),
+    /// container name, e.g. BUFFER_IN, to the entities it starts with
+    #[serde_as(as = "BTreeMap<_, Vec<DisplayFromStr>>")]
+    #[serde(default)]
+    pub inventory: BTreeMap<String, Vec<Entity>>,
+    /// select this agent when the game starts, otherwise the first agent is selected
+    #[serde(default)]
+    pub focus: bool,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StartingEntity {
+    #[serde_as(as = "DisplayFromStr")]
+    pub entity: Entity,
+    pub at: (i32, i32),
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TechOverride {
+    #[serde_as(as = "DisplayFromStr")]
+    pub tech: TechKind,
+    /// already researched when the game starts
+    #[serde(default)]
+    pub researched: bool,
+    /// entities consumed by each RESR
+    #[serde_as(as = "Option<HashMap<DisplayFromStr, _>>")]
+    #[serde(default)]
+    pub cost: Option<HashMap<Entity, u8>>,
+    /// number of RESR needed to complete the research
+    #[serde(default)]
+    pub steps: Option<u8>,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Predicate {
+    /// at least `count` of `entity` across agent containers, only containers named `container` if
+    /// set
+    Hold {
+        #[serde_as(as = "DisplayFromStr")]
+        entity: Entity,
+        count: usize,
+        #[serde(default)]
+        container: Option<String>,
+    },
+    /// at least `count` agents of kind `entity` on the surface
+    Build {
+        #[serde_as(as = "DisplayFromStr")]
+        entity: Entity,
+        count: usize,
+    },
+    Research {
+        #[serde_as(as = "DisplayFromStr")]
+        tech: TechKind,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Goal {
+    #[serde(flatten)]
+    pub predicate: Predicate,
+    /// the scenario is failed if the goal is not met by this tick
+    #[serde(default)]
+    pub by_tick: Option<u64>,
+}
+
+impl std::fmt::Display for Goal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.predicate {
+            Predicate::Hold {
+                entity,
+                count,
+                container: Some(container),
+            } => write!(f, "{count} {entity} in {container}")?,
+            Predicate::Hold { entity, count, .. } => write!(f, "{count} {entity} held")?,
+            Predicate::Build { entity, count } => write!(f, "{count} {entity} built")?,
+            Predicate::Research { tech } => write!(f, "research {tech}")?,
+        }
+        if let Some(tick) = self.by_tick {
+            write!(f, " by tick {tick}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Goal {
+    /// how far the surface is towards the goal, met once it reaches [`Goal::target`]
+    pub fn amount(
+        &self,
+        agents: &BTreeMap<usize, Comms>,
+        grid: &Grid,
+        game_state: &GameState,
+    ) -> usize {
+        match &self.predicate {
+            Predicate::Hold {
+                entity, container, ..
+            } => agents
+                .values()
+                .filter_map(|comms| comms.position)
+                .filter_map(|pos| match grid.get(&pos) {
+                    Some(Gent::Age(agent)) => Some(agent),
+                    _ => None,
+                })
+                .flat_map(|agent| agent.containers())
+                .filter(|c| container.as_ref().is_none_or(|name| c.name() == *name))
+                .map(|c| c.count(*entity))
+                .sum(),
+            Predicate::Build { entity, .. } => agents
+                .values()
+                .filter(|comms| comms.entity == *entity && comms.position.is_some())
+                .count(),
+            Predicate::Research { tech } => {
+                let tree = &game_state.tech_tree;
+                tree.tech_kind_idx(tech.clone())
+                    .and_then(|idx| tree.get_tech(idx))
+                    .is_some_and(|tech| tech.unlocked) as usize
+            }
+        }
+    }
+
+    pub fn target(&self) -> usize {
+        match &self.predicate {
+            Predicate::Hold { count, .. } | Predicate::Build { count, .. } => *count,
+            Predicate::Research { .. } => 1,
+        }
+    }
+}
+
+/// a scenario being played and how far along its goals are
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Progress {
+    pub scenario: Scenario,
+    /// tick each goal was first met at, indexed like `scenario.goals`
+    pub met: Vec<Option<u64>>,
+    /// tick the first goal missed its deadline at
+    pub failed: Option<u64>,
+}
+
+impl Progress {
+    pub fn new(scenario: Scenario) -> Progress {
+        let met = vec![None; scenario.goals.len()];
+        Progress {
+            scenario,
+            met,
+            failed: None,
+        }
+    }
+
+    pub fn has_goals(&self) -> bool {
+        !self.scenario.goals.is_empty()
+    }
+
+    pub fn won(&self) -> bool {
+        self.has_goals() && self.failed.is_none() && self.met.iter().all(Option::is_some)
+    }
+
+    /// checks the goals not met yet, goals stay met once they are
+    pub fn update(
+        &mut self,
+        tick: u64,
+        agents: &BTreeMap<usize, Comms>,
+        grid: &Grid,
+        game_state: &GameState,
+    ) {
+        if self.failed.is_some() || self.won() {
+            return;
+        }
+        for (goal, met) in self.scenario.goals.iter().zip(self.met.iter_mut()) {
+            if met.is_some() {
+                continue;
+            }
+            if goal.amount(agents, grid, game_state) >= goal.target() {
+                *met = Some(tick);
+            } else if goal.by_tick.is_some_and(|by_tick| tick > by_tick) {
+                tracing::info!("scenario goal `{goal}` failed at tick {tick}");
+                self.failed = Some(tick);
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Scenario {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// the grid position `at` is offset from the center of a surface `size` cells wide by
+fn position((x, y): (i32, i32), size: u16) -> AppResult<Position> {
+    let center = size as i32 / 2;
+    let grid_x = center + x;
+    let grid_y = center + y;
+    let size = size as i32;
+    if !(0..size).contains(&grid_x) || !(0..size).contains(&grid_y) {
+        return Err(format!("[{x}, {y}] is off the surface").into());
+    }
+    Ok(Position::new(grid_x as u16, grid_y as u16))
+}
+
+fn hand_authored_grid(rows: &[String], size: u16) -> AppResult<Grid> {
+    let mut grid = Grid::empty(size);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            if c == '.' || c == ' ' {
+                continue;
+            }
+            let entity = Entity::iter()
+                .find(|entity| entity.character() == c && !entity.is_agent())
+                .ok_or_else(|| format!("map has unknown entity `{c}` at [{x}, {y}]"))?;
+            if entity.footprint().is_some() {
+                return Err(format!("{entity} at [{x}, {y}] needs to be in `entities`").into());
+            }
+            let pos = position((x as i32, y as i32), size)?;
+            grid.insert(&pos, Gent::Intmd(entity));
+        }
+    }
+    Ok(grid)
+}
+
+impl Scenario {
+    pub fn parse(json: &str) -> AppResult<Scenario> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn load(path: &Path) -> AppResult<Scenario> {
+        Scenario::parse(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("scenario {}: {e}", path.display()).into())
+    }
+
+    /// scenarios that ship with texaform
+    pub fn bundled() -> Vec<Scenario> {
+        BUNDLED
+            .iter()
+            .map(|json| Scenario::parse(json).expect("bundled scenarios are valid"))
+            .collect()
+    }
+
+    /// directory players put their own scenarios in
+    pub fn dir() -> PathBuf {
+        crate::logging::get_data_dir().join("scenarios")
+    }
+
+    /// the bundled scenarios followed by the ones in [`Scenario::dir`], skipping invalid files
+    pub fn available() -> Vec<Scenario> {
+        let mut scenarios = Scenario::bundled();
+        let Ok(entries) = std::fs::read_dir(Scenario::dir()) else {
+            return scenarios;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            match Scenario::load(&path) {
+                Ok(scenario) => scenarios.push(scenario),
+                Err(e) => tracing::warn!("skipping {e}"),
+            }
+        }
+        scenarios
+    }
+
+    /// a surface set up as the scenario describes
+    pub fn new_game(&self, event_sender: UnboundedSender<Event>) -> AppResult<Surface> {
+        let size = self.world_size.unwrap_or(DEFAULT_WORLD_SIZE);
+        if !(MIN_WORLD_SIZE..=MAX_WORLD_SIZE).contains(&size) {
+            return Err(format!(
+                "world_size {size} is not between {MIN_WORLD_SIZE} and {MAX_WORLD_SIZE}"
+            )
+            .into());
+        }
+        let mut surface = match &self.map {
+            Map::Seed(seed) => generation::manual(
+                event_sender,
+                Seed::Manual(*seed),
+                size,
+                generation::Generator::for_size(size),
+            ),
+            Map::Grid(rows) => {
+                let center = size as usize / 2;
+                Surface::new(
+                    hand_authored_grid(rows, size)?,
+                    center - 30,
+                    center - 10,
+                    Seed::Manual(0),
+                    event_sender,
+                )
+            }
+        };
+        let game_state = &mut surface.game_state;
+        // the goals take the place of the tutorial
+        game_state.tutorial_state = Tutorial::Complete;
+        if let Some(unlocked) = &self.unlocked {
+            game_state.unlocked_entities = HashSet::from_iter(unlocked.iter().copied());
+        }
+        for tech_override in self.tech.iter() {
+            tech_override.apply(game_state);
+        }
+
+        for starting in self.entities.iter() {
+            surface
+                .add_entity(&position(starting.at, size)?, starting.entity)
+                .map_err(|e| format!("{} at {:?}: {e}", starting.entity, starting.at))?;
+        }
+        let mut focus = None;
+        for starting in self.agents.iter() {
+            let mut agent = starting.kind.create_agent()?;
+            for (name, entities) in starting.inventory.iter() {
+                let mut containers = agent.containers_mut();
+                let names: Vec<String> = containers.iter().map(|c| c.name()).collect();
+                let container = containers
+                    .iter_mut()
+                    .find(|c| c.name() == *name)
+                    .ok_or_else(|| format!("{} has no {name}, only {names:?}", starting.kind))?;
+                for entity in entities {
+                    if !container.placable() {
+                        return Err(format!("{name} of {} is full", starting.kind).into());
+                    }
+                    container.place(*entity);
+                }
+            }
+            let port = surface
+                .add_agent(&position(starting.at, size)?, agent)
+                .map_err(|e| format!("{} at {:?}: {e}", starting.kind, starting.at))?;
+            if focus.is_none() || starting.focus {
+                focus = Some(port);
+            }
+        }
+        surface.focus = focus.map(Focus::Agent);
+        surface.scenario = Some(Progress::new(self.clone()));
+        tracing::info!("started scenario {}", self.name);
+        Ok(surface)
+    }
+}
+
+impl TechOverride {
+    fn apply(&self, game_state: &mut GameState) {
+        let tree = &mut game_state.tech_tree;
+        let idx = tree
+            .tech_kind_idx(self.tech.clone())
+            .expect("all tech in tree");
+        let tech = tree
+            .graph
+            .node_weight_mut(NodeIndex::new(idx))
+            .expect("index from the tree");
+        if let Some(cost) = &self.cost {
+            tech.cost = cost.clone();
+        }
+        if let Some(steps) = self.steps {
+            tech.progress_denominator = steps.max(1);
+        }
+        if self.researched && !tech.unlocked {
+            tech.unlocked = true;
+            tech.progress_numerator = tech.progress_denominator;
+            if let Some(entity) = tech.unlocks {
+                game_state.unlocked_entities.insert(entity);
+            }
+            game_state.stats.research_complete += 1;
+        }
+    }
+}
@@ -0,0 +1,435 @@
+use std::io::Read;
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::app::AppResult;
+use crate::surface::grid::{CHUNK_CELLS, CHUNK_SIZE};
+use crate::surface::state::{SurfaceState, Version};
+use crate::surface::{DEFAULT_WORLD_SIZE, Surface};
+
+// saves are a gzip compressed json document `{"schema": 6, "surface": {...}}`, uncompressed
+// documents such as exports load as well. when a saved struct changes, bump SCHEMA by adding a
+// migration that rewrites the previous schema's `surface` into the new shape. loading runs every
+// migration after the save's schema in order, so any older save upgrades step by step
+//
+// schema 1 saves are the bincode files written before the schema existed, they are decoded with
+// the frozen types in `legacy` and migrated from there
+
+/// rewrites a `surface` of one schema into the next
+type Migration = fn(Value) -> AppResult<Value>;
+
+/// `MIGRATIONS[n]` upgrades schema `n + 1` to schema `n + 2`
+const MIGRATIONS: &[Migration] = &[
+    drop_comms_address,
+    add_scenario,
+    add_power_consumed,
+    add_cheated,
+    chunk_grid,
+];
+
+/// schema written by this version of texaform
+pub const SCHEMA: u32 = MIGRATIONS.len() as u32 + 1;
+
+#[derive(Serialize)]
+struct SaveDocumentRef<'a, S> {
+    schema: u32,
+    surface: &'a S,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveDocument {
+    schema: u32,
+    surface: Value,
+}
+
+/// the stats shown when listing saves, read without building the surface. every schema stores
+/// them in the same place
+#[derive(Debug, Clone, Deserialize)]
+pub struct SaveStats {
+    pub version: Version,
+    pub tick_count: u64,
+}
+
+#[derive(Deserialize)]
+struct StatsDocument {
+    surface: StatsSurface,
+}
+
+#[derive(Deserialize)]
+struct StatsSurface {
+    game_state: StatsGameState,
+}
+
+#[derive(Deserialize)]
+struct StatsGameState {
+    stats: SaveStats,
+}
+
+/// first bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+fn compress(document: &impl Serialize) -> AppResult<Vec<u8>> {
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    serde_json::to_writer(&mut encoder, document)?;
+    Ok(encoder.finish()?)
+}
+
+/// the save file's contents
+pub fn encode(surface: &Surface) -> AppResult<Vec<u8>> {
+    compress(&SaveDocumentRef {
+        schema: SCHEMA,
+        surface,
+    })
+}
+
+/// the save file's contents from a surface already converted to json, e.g. off the main loop
+pub fn encode_value(surface: &Value) -> AppResult<Vec<u8>> {
+    compress(&SaveDocumentRef {
+        schema: SCHEMA,
+        surface,
+    })
+}
+
+/// the save's schema and its surface, not yet migrated
+pub fn decode(bytes: &[u8]) -> AppResult<(u32, Value)> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut json = vec![];
+        GzDecoder::new(bytes).read_to_end(&mut json)?;
+        decode(&json)
+    } else if bytes.trim_ascii_start().first() == Some(&b'{') {
+        let document: SaveDocument = serde_json::from_slice(bytes)?;
+        Ok((document.schema, document.surface))
+    } else {
+        Ok((1, legacy::decode(bytes)?))
+    }
+}
+
+/// the save's stats, skipping over the rest of the surface
+pub fn decode_stats(bytes: &[u8]) -> AppResult<SaveStats> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut json = vec![];
+        GzDecoder::new(bytes).read_to_end(&mut json)?;
+        decode_stats(&json)
+    } else if bytes.trim_ascii_start().first() == Some(&b'{') {
+        let document: StatsDocument = serde_json::from_slice(bytes)?;
+        Ok(document.surface.game_state.stats)
+    } else {
+        let surface: StatsSurface = serde_json::from_value(legacy::decode(bytes)?)?;
+        Ok(surface.game_state.stats)
+    }
+}
+
+/// decodes a save of any schema and migrates it to the current one, checking it is a valid surface
+fn upgrade(bytes: &[u8]) -> AppResult<SaveDocument> {
+    let (schema, surface) = decode(bytes)?;
+    let surface = migrate(schema, surface)?;
+    serde_json::from_value::<SurfaceState>(surface.clone())?;
+    Ok(SaveDocument {
+        schema: SCHEMA,
+        surface,
+    })
+}
+
+/// a save as pretty printed json of the current schema, for diffing and editing by hand
+pub fn export(bytes: &[u8]) -> AppResult<Vec<u8>> {
+    Ok(serde_json::to_vec_pretty(&upgrade(bytes)?)?)
+}
+
+/// json, or a save of any format, as a save file of the current schema
+pub fn import(bytes: &[u8]) -> AppResult<Vec<u8>> {
+    compress(&upgrade(bytes)?)
+}
+
+/// upgrades a surface saved with `schema` to [`SCHEMA`]
+pub fn migrate(schema: u32, mut surface: Value) -> AppResult<Value> {
+    if schema == 0 || schema > SCHEMA {
+        return Err(format!(
+            "save schema {schema} is not supported, this texaform reads up to schema {SCHEMA}"
+        )
+        .into());
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(schema as usize - 1) {
+        tracing::info!("migrating save from schema {} to {}", from + 1, from + 2);
+        surface = migration(surface)?;
+    }
+    Ok(surface)
+}
+
+fn object_mut<'a>(
+    value: &'a mut Value,
+    name: &str,
+) -> AppResult<&'a mut serde_json::Map<String, Value>> {
+    value
+        .as_object_mut()
+        .ok_or_else(|| format!("expected `{name}` to be an object").into())
+}
+
+/// 1 -> 2: the address of the last connection to an agent is no longer saved
+fn drop_comms_address(mut surface: Value) -> AppResult<Value> {
+    let agents = object_mut(&mut surface, "surface")?
+        .get_mut("agents")
+        .ok_or("surface has no agents")?;
+    for comms in object_mut(agents, "agents")?.values_mut() {
+        object_mut(comms, "comms")?.remove("address");
+    }
+    Ok(surface)
+}
+
+/// 2 -> 3: games can be started from a scenario, older saves were not
+fn add_scenario(mut surface: Value) -> AppResult<Value> {
+    object_mut(&mut surface, "surface")?.insert("scenario".to_string(), Value::Null);
+    Ok(surface)
+}
+
+/// sets a field added to the game's stats, and to the stats kept from a victory
+fn insert_stat(mut surface: Value, name: &str, value: Value) -> AppResult<Value> {
+    let surface_object = object_mut(&mut surface, "surface")?;
+    let game_state = surface_object
+        .get_mut("game_state")
+        .ok_or("surface has no game_state")?;
+    let stats = object_mut(game_state, "game_state")?
+        .get_mut("stats")
+        .ok_or("game_state has no stats")?;
+    object_mut(stats, "stats")?.insert(name.to_string(), value.clone());
+    if let Some(victory_stats) = surface_object.get_mut("victory_stats")
+        && !victory_stats.is_null()
+    {
+        let stats = object_mut(victory_stats, "victory_stats")?
+            .get_mut("stats")
+            .ok_or("victory_stats has no stats")?;
+        object_mut(stats, "stats")?.insert(name.to_string(), value);
+    }
+    Ok(surface)
+}
+
+/// 3 -> 4: power consumed is counted in the game's stats, older saves start counting from 0
+fn add_power_consumed(surface: Value) -> AppResult<Value> {
+    insert_stat(surface, "power_consumed", 0.into())
+}
+
+/// 4 -> 5: cheats are flagged in the game's stats, older saves could not cheat
+fn add_cheated(surface: Value) -> AppResult<Value> {
+    insert_stat(surface, "cheated", false.into())
+}
+
+/// 5 -> 6: the grid is saved in chunks, only the ones that are not empty. older saves were the
+/// default world size with every cell saved, so they have no terrain to generate chunks from
+fn chunk_grid(mut surface: Value) -> AppResult<Value> {
+    let surface_object = object_mut(&mut surface, "surface")?;
+    let grid = surface_object
+        .get_mut("grid")
+        .ok_or("surface has no grid")?;
+    let raw = object_mut(grid, "grid")?
+        .remove("raw")
+        .ok_or("grid has no raw cells")?;
+    let Value::Array(raw) = raw else {
+        return Err("expected the grid's raw cells to be an array".into());
+    };
+    let size = DEFAULT_WORLD_SIZE as usize;
+    if raw.len() != size * size {
+        return Err(format!("expected {} cells, the grid has {}", size * size, raw.len()).into());
+    }
+    let chunk_size = CHUNK_SIZE as usize;
+    let chunks_per_side = size.div_ceil(chunk_size);
+    let empty = Value::String("Empty".to_string());
+    let mut chunks = serde_json::Map::new();
+    for (idx, cell) in raw.into_iter().enumerate() {
+        if cell == empty {
+            continue;
+        }
+        let (x, y) = (idx % size, idx / size);
+        let chunk = (y / chunk_size) * chunks_per_side + x / chunk_size;
+        let cells = chunks
+            .entry(chunk.to_string())
+            .or_insert_with(|| Value::Array(vec![empty.clone(); CHUNK_CELLS]));
+        if let Value::Array(cells) = cells {
+            cells[(y % chunk_size) * chunk_size + x % chunk_size] = cell;
+        }
+    }
+    *grid = serde_json::json!({
+        "size": size,
+        "terrain": null,
+        "chunks": chunks,
+        "cleared": [],
+    });
+    Ok(surface)
+}
+
+/// types frozen as they were saved in schema 1, only the types that changed since are copied,
+/// the rest are still shared with the current surface
+mod legacy {
+    use std::collections::{BTreeMap, HashSet};
+    use std::net::SocketAddr;
+
+    use ratatui::layout::{Position, Rect};
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use crate::app::AppResult;
+    use crate::entities::Entity;
+    use crate::surface::Power;
+    use crate::surface::grid::Gent;
+    use crate::surface::state::{Seed, VERSION, Version};
+    use crate::surface::tutorial::Tutorial;
+    use crate::tech_tree::TechTree;
+    use crate::widgets::text_box::TextBox;
+
+    #[derive(Serialize, Deserialize)]
+    struct Grid {
+        raw: Vec<Gent>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct SurfaceState {
+        version: Version,
+        x: usize,
+        y: usize,
+        grid: Grid,
+        agents: BTreeMap<usize, Comms>,
+        power: Power,
+        game_state: GameState,
+        victory_stats: Option<VictoryStats>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Comms {
+        port: usize,
+        entity: Entity,
+        position: Option<Position>,
+        location: Option<Rect>,
+        address: Option<SocketAddr>,
+        text_box: TextBox,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct GameState {
+        unlocked_entities: HashSet<Entity>,
+        tech_tree: TechTree,
+        tutorial_state: Tutorial,
+        stats: GameStats,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct GameStats {
+        version: Version,
+        seed: Seed,
+        tick_count: u64,
+        manual_command_count: u64,
+        tcp_command_count: u64,
+        research_complete: usize,
+        research_count: usize,
+        agent_count: BTreeMap<String, u64>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct VictoryStats {
+        stats: GameStats,
+        show_victory: bool,
+    }
+
+    pub fn decode(bytes: &[u8]) -> AppResult<Value> {
+        let config = bincode::config::standard().with_limit::<1_000_000>();
+        let (version, _): (Version, usize) = bincode::serde::decode_from_slice(bytes, config)?;
+        if !version.compatible() {
+            return Err(format!(
+                "save file format {version} is incompatible with texaform version {VERSION}"
+            )
+            .into());
+        }
+        let (state, _): (SurfaceState, usize) = bincode::serde::decode_from_slice(bytes, config)?;
+        Ok(serde_json::to_value(state)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_drops_comms_address() {
+        let surface = serde_json::json!({
+            "agents": {
+                "3333": {"port": 3333, "address": null},
+                "3335": {"port": 3335, "address": "127.0.0.1:50000"},
+            }
+        });
+        let migrated = drop_comms_address(surface).unwrap();
+        assert_eq!(
+            migrated,
+            serde_json::json!({
+                "agents": {
+                    "3333": {"port": 3333},
+                    "3335": {"port": 3335},
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_migrate_adds_scenario() {
+        let migrated = add_scenario(serde_json::json!({"agents": {}})).unwrap();
+        assert_eq!(
+            migrated,
+            serde_json::json!({"agents": {}, "scenario": null})
+        );
+    }
+
+    #[test]
+    fn test_migrate_adds_power_consumed() {
+        let surface = serde_json::json!({
+            "game_state": {"stats": {"tick_count": 5}},
+            "victory_stats": {"stats": {"tick_count": 4}, "show_victory": true},
+            "scenario": null,
+        });
+        let migrated = add_power_consumed(surface).unwrap();
+        assert_eq!(migrated["game_state"]["stats"]["power_consumed"], 0);
+        assert_eq!(migrated["victory_stats"]["stats"]["power_consumed"], 0);
+
+        let surface = serde_json::json!({"game_state": {"stats": {}}, "victory_stats": null});
+        let migrated = add_power_consumed(surface).unwrap();
+        assert_eq!(migrated["game_state"]["stats"]["power_consumed"], 0);
+        assert!(migrated["victory_stats"].is_null());
+    }
+
+    #[test]
+    fn test_migrate_adds_cheated() {
+        let surface = serde_json::json!({"game_state": {"stats": {}}, "victory_stats": null});
+        let migrated = add_cheated(surface).unwrap();
+        assert_eq!(migrated["game_state"]["stats"]["cheated"], false);
+    }
+
+    #[test]
+    fn test_migrate_chunks_grid() {
+        let size = DEFAULT_WORLD_SIZE as usize;
+        let mut raw = vec![serde_json::json!("Empty"); size * size];
+        raw[0] = serde_json::json!({"Intmd": "Iron"});
+        raw[size * 40 + 249] = serde_json::json!({"Large": {"x": 248, "y": 40}});
+        let surface = serde_json::json!({"grid": {"raw": raw}});
+        let migrated = chunk_grid(surface).unwrap();
+        let grid = &migrated["grid"];
+        assert_eq!(grid["size"], 250);
+        assert!(grid["terrain"].is_null());
+        let chunks = grid["chunks"].as_object().unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks["0"][0], serde_json::json!({"Intmd": "Iron"}));
+        // x 249 y 40 is cell 25 8 of chunk 7 1
+        assert_eq!(
+            chunks["15"][8 * 32 + 25],
+            serde_json::json!({"Large": {"x": 248, "y": 40}})
+        );
+        assert_eq!(chunks["15"].as_array().unwrap().len(), CHUNK_CELLS);
+
+        assert!(chunk_grid(serde_json::json!({"grid": {"raw": []}})).is_err());
+    }
+
+    #[test]
+    fn test_migrate_rejects_unknown_schema() {
+        assert!(migrate(0, Value::Null).is_err());
+        assert!(migrate(SCHEMA + 1, Value::Null).is_err());
+        assert_eq!(migrate(SCHEMA, Value::Null).unwrap(), Value::Null);
+    }
+}
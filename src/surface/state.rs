@@ -17,17 +17,20 @@ use petgraph::graph::NodeIndex;
 
 use std::collections::{BTreeMap, HashSet};
 use std::fs::File;
-use std::io::BufReader;
 use std::io::BufWriter;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use serde_with::serde_as;
 
 use crate::surface::grid::Grid;
+use crate::surface::journal::{Journal, JournalFile};
+use crate::surface::scenario::Progress;
+use crate::surface::schema::{self, SaveStats};
 use crate::surface::tutorial::Tutorial;
 use crate::surface::{Power, Surface};
 
-// order of fields matters for saving/loading
+// saved fields are versioned by `schema::SCHEMA`, changing them needs a migration
 #[serde_as]
 #[derive(Debug, Deserialize)]
 pub struct SurfaceState {
@@ -40,10 +43,15 @@ pub struct SurfaceState {
     pub power: Power,
     pub game_state: GameState,
     pub victory_stats: Option<VictoryStats>,
+    pub scenario: Option<Progress>,
+
+    /// read from the journal file next to the save
+    #[serde(skip)]
+    pub journal: Journal,
 }
 
 #[serde_as]
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Version {
     major: u8,
     minor: u8,
@@ -70,27 +78,19 @@ impl std::fmt::Display for Version {
 }
 
 impl Version {
-    fn load(path: &std::path::Path) -> AppResult<Version> {
-        let save_file = File::open(path)?;
-        tracing::info!("save_file {save_file:?} opened");
-        let mut reader = BufReader::new(save_file);
-        tracing::info!("reader created");
-        // std::mem::size_of::<Version>() == 3 (3 `u8`s)
-        let config = bincode::config::standard().with_limit::<3>();
-        let version: Version = bincode::serde::decode_from_std_read(&mut reader, config)?;
-        tracing::info!("loaded version: {version}");
-        Ok(version)
-    }
-
-    fn compatible(&self) -> bool {
+    pub fn compatible(&self) -> bool {
         self.major == VERSION.major
     }
 }
 
 impl SurfaceState {
+    /// path of the save called `name` in the data directory
+    pub fn path(name: &str) -> PathBuf {
+        crate::logging::get_data_dir().join(format!("{name}.texaform"))
+    }
+
     pub fn save(surface: &Surface, name: String) -> AppResult<PathBuf> {
-        let mut save_path = crate::logging::get_data_dir();
-        save_path.push(name + ".texaform");
+        let save_path = Self::path(&name);
         tracing::info!("saving to: {save_path:?}");
         Self::save_to_path(surface, &save_path)?;
 //        let save_file = File::create(save_path.clone())?;
@@ -104,38 +104,111 @@ impl SurfaceState {
 
     pub fn save_to_path(surface: &Surface, path: &PathBuf) -> AppResult<()> {
         tracing::info!("saving to: {path:?}");
-        let save_file = File::create(path)?;
-        let mut writer = BufWriter::new(save_file);
-        tracing::info!("before save");
-        let config = bincode::config::standard().with_limit::<1_000_000>();
-        bincode::serde::encode_into_std_write(surface, &mut writer, config).unwrap();
-        tracing::info!("after save");
-        Ok(())
+        EncodedSave::new(surface)?.write(path)
     }
 
     pub fn load(path: &std::path::Path) -> AppResult<SurfaceState> {
-        let save_file = File::open(path)?;
-        tracing::info!("save_file {save_file:?} opened");
-        let mut reader = BufReader::new(save_file);
-        tracing::info!("reader created");
-        let version = Version::load(path)?;
-        if !version.compatible() {
-            return Err(format!(
-                "save file format {version} is incompatible with texaform version {VERSION}"
-            )
-            .into());
+        let bytes = std::fs::read(path)?;
+        let (schema, surface) = schema::decode(&bytes)?;
+        tracing::info!("loaded {path:?} with save schema {schema}");
+        let mut surface_state: SurfaceState =
+            serde_json::from_value(schema::migrate(schema, surface)?)?;
+        let journal_path = Journal::path(path);
+        if journal_path.exists() {
+            surface_state.journal.entries = Journal::load(&journal_path)?.entries;
+        } else {
+            tracing::warn!("no journal at {journal_path:?}, replays of this game will not match");
         }
-        let config = bincode::config::standard().with_limit::<1_000_000>();
-        let surface_state = bincode::serde::decode_from_std_read(&mut reader, config)?;
-        tracing::info!("loading worked!");
         Ok(surface_state)
     }
 
-    pub async fn into_surface(mut self, event_sender: UnboundedSender<Event>) -> Surface {
-        for comms in self.agents.values_mut() {
-            comms.init(&event_sender).await;
+    /// the stats of the save at `path`, much cheaper than [`SurfaceState::load`]
+    pub fn load_stats(path: &Path) -> AppResult<SaveStats> {
+        schema::decode_stats(&std::fs::read(path)?)
+    }
+
+    /// deletes a save and the journal next to it
+    pub fn delete(path: &Path) -> AppResult<()> {
+        std::fs::remove_file(path)?;
+        let journal_path = Journal::path(path);
+        if journal_path.exists() {
+            std::fs::remove_file(journal_path)?;
+        }
+        tracing::info!("deleted {path:?}");
+        Ok(())
+    }
+
+    /// fails if `to` or the journal next to it exists, checked before any file is touched
+    fn check_unused(to: &Path) -> AppResult<()> {
+        for path in [to.to_path_buf(), Journal::path(to)] {
+            if path.exists() {
+                return Err(format!("{} already exists", path.display()).into());
+            }
         }
-        
+        Ok(())
+    }
+
+    /// moves a save and the journal next to it, never replacing another save or journal
+    pub fn rename(from: &Path, to: &Path) -> AppResult<()> {
+        SurfaceState::check_unused(to)?;
+        std::fs::rename(from, to)?;
+        let journal_path = Journal::path(from);
+        if journal_path.exists() {
+            std::fs::rename(journal_path, Journal::path(to))?;
+        }
+        tracing::info!("renamed {from:?} to {to:?}");
+        Ok(())
+    }
+
+    /// copies a save and the journal next to it, never replacing another save or journal
+    pub fn duplicate(from: &Path, to: &Path) -> AppResult<()> {
+        SurfaceState::check_unused(to)?;
+        std::fs::copy(from, to)?;
+        let journal_path = Journal::path(from);
+        if journal_path.exists() {
+            std::fs::copy(journal_path, Journal::path(to))?;
+        }
+        tracing::info!("copied {from:?} to {to:?}");
+        Ok(())
+    }
+
+    /// writes the save at `save_path` to `json_path` as pretty printed json
+    pub fn export(save_path: &Path, json_path: &Path) -> AppResult<()> {
+        let json = schema::export(&std::fs::read(save_path)?)?;
+        std::fs::write(json_path, json)?;
+        tracing::info!("exported {save_path:?} to {json_path:?}");
+        Ok(())
+    }
+
+    /// writes the json at `json_path`, e.g. from [`SurfaceState::export`], as a save
+    pub fn import(json_path: &Path, save_path: &Path) -> AppResult<()> {
+        let save = schema::import(&std::fs::read(json_path)?)?;
+        write_replacing(save_path, &save)?;
+        tracing::info!("imported {json_path:?} to {save_path:?}");
+        Ok(())
+    }
+
+    /// shifts the saved agent ports so the HUD is on `port_base`
+    fn rebase_ports(&mut self, port_base: usize) {
+        let Some(saved_base) = self.agents.keys().next().copied() else {
+            return;
+        };
+        if saved_base == port_base {
+            return;
+        }
+        tracing::info!("moving agent ports from {saved_base} to {port_base}");
+        self.agents = std::mem::take(&mut self.agents)
+            .into_values()
+            .map(|mut comms| {
+                comms.port = comms.port - saved_base + port_base;
+                (comms.port, comms)
+            })
+            .collect();
+        self.journal.rebase_ports(saved_base, port_base);
+    }
+
+    pub fn into_surface(mut self, event_sender: UnboundedSender<Event>) -> Surface {
+        self.rebase_ports(crate::tcp::port_base());
         let (titles, guage) = self.game_state.tech_tree.current_research_content();
         Surface {
             version: self.version,
@@ -146,16 +219,82 @@ impl SurfaceState {
             power: self.power,
             game_state: self.game_state,
             victory_stats: self.victory_stats,
+            scenario: self.scenario,
             event_sender: event_sender.clone(),
             effects: vec![],
             focus: None,
             previous_command_counter: 0,
+            lockstep: false,
+            listening: false,
+            command_queue: BTreeMap::new(),
+            journal: self.journal,
+            replay_paused: None,
+            recorder: None,
             hud: Hud::default(),
             current_research_button: BorderedButton::new(guage).with_titles(titles),
         }
     }
 }
 
+/// a save file and its journal encoded in memory, so the surface can keep running while they are
+/// written
+#[derive(Debug)]
+pub struct EncodedSave {
+    save: Vec<u8>,
+    journal: Vec<u8>,
+}
+
+impl EncodedSave {
+    pub fn new(surface: &Surface) -> AppResult<EncodedSave> {
+        let save = schema::encode(surface)?;
+        let journal = surface.journal.encode(surface)?;
+        Ok(EncodedSave { save, journal })
+    }
+
+    /// writes the save to `path` and the journal next to it, each through a temporary file so a
+    /// crash while writing leaves the previous save intact
+    pub fn write(&self, path: &Path) -> AppResult<()> {
+        write_replacing(path, &self.save)?;
+        write_replacing(&Journal::path(path), &self.journal)?;
+        tracing::info!("saved {path:?}");
+        Ok(())
+    }
+}
+
+/// a save copied out of the surface between ticks. taking one still walks the whole surface and
+/// clones the journal on the main loop, only encoding it into an [`EncodedSave`] can happen
+/// elsewhere
+#[derive(Debug)]
+pub struct SaveSnapshot {
+    surface: serde_json::Value,
+    journal: JournalFile,
+}
+
+impl SaveSnapshot {
+    pub fn new(surface: &Surface) -> AppResult<SaveSnapshot> {
+        Ok(SaveSnapshot {
+            surface: serde_json::to_value(surface)?,
+            journal: surface.journal.snapshot(surface),
+        })
+    }
+
+    pub fn encode(&self) -> AppResult<EncodedSave> {
+        Ok(EncodedSave {
+            save: schema::encode_value(&self.surface)?,
+            journal: self.journal.encode()?,
+        })
+    }
+}
+
+fn write_replacing(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    writer.write_all(bytes)?;
+    writer.into_inner()?.sync_all()?;
+    std::fs::rename(&tmp_path, path)
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum Seed {
     Random(u64),
@@ -217,6 +356,10 @@ pub struct GameStats {
     pub research_complete: usize,
     pub research_count: usize,
     pub agent_count: BTreeMap<String, u64>,
+    /// joules requested by agents since the start of the game
+    pub power_consumed: u64,
+    /// set by the first cheat, see [`crate::surface::cheat`]
+    pub cheated: bool,
     // TODO will require reworking Update
     // pub error_count: u64,
 }
@@ -227,12 +370,13 @@ impl std::fmt::Display for GameStats {
         let playtime = human_readable_tick_count(self.tick_count);
         write!(
             f,
-            "version: {}\n{}\nplaytime: {}\nautomated commands: {}\nmanual commands: {}\ntechnology: {}/{}\ntotal agents: {}",
+            "version: {}\n{}\nplaytime: {}\nautomated commands: {}\nmanual commands: {}\npower used: {}Kj\ntechnology: {}/{}\ntotal agents: {}",
             self.version,
             self.seed.ui_string(),
             playtime,
             self.tcp_command_count,
             self.manual_command_count,
+            self.power_consumed / 1000,
             self.research_complete,
             self.research_count,
             total_agent_count,
@@ -240,6 +384,9 @@ impl std::fmt::Display for GameStats {
         for (agent, count) in self.agent_count.iter() {
             write!(f, "\n  {agent}: {count}  ")?;
         }
+        if self.cheated {
+            write!(f, "\ncheats used")?;
+        }
         Ok(())
     }
 }
@@ -255,6 +402,8 @@ impl GameStats {
             tcp_command_count: 0,
             research_complete: 0,
             agent_count: BTreeMap::new(),
+            power_consumed: 0,
+            cheated: false,
         }
     }
 }
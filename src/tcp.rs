@@ -1,134 +1,420 @@
 use crate::AppResult;
+use crate::agents::{CommandError, Response, Topic};
 use crate::event::Event;
-use bytes::BytesMut;
-use std::fmt::Display;
-use std::str;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use crate::speed;
+use bytes::{Buf, BytesMut};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::str::{self, FromStr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
-use tokio::sync::mpsc::Receiver;
+use tokio::net::tcp::OwnedReadHalf;
 use tokio::sync::mpsc::UnboundedSender;
-use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::{broadcast, oneshot};
 
-use async_recursion::async_recursion;
 use tracing::*;
 
-// TODO update to protocall where all messages end in `;`
-pub async fn handle_socket_old<R: Display + Send>(
+/// port of the HUD unless overridden with `--port-base`
+pub const DEFAULT_PORT_BASE: usize = 3333;
+
+/// longest command accepted before the connection is dropped
+pub const MAX_COMMAND_LEN: usize = 1024;
+
+/// how long a client waits for a reply before getting `ERRR timeout`
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+static PORT_BASE: AtomicUsize = AtomicUsize::new(DEFAULT_PORT_BASE);
+
+/// port of the HUD, agents are assigned the ports after it
+pub fn port_base() -> usize {
+    PORT_BASE.load(Ordering::Relaxed)
+}
+
+pub fn set_port_base(port: usize) {
+    PORT_BASE.store(port, Ordering::Relaxed)
+}
+
+/// reply format of a connection, switched with a `PROT TEXT` or `PROT JSON` handshake
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Protocol {
+    /// commands and replies end in `;`
+    #[default]
+    Text,
+    /// one JSON object per line, e.g. `{"command": "MOVE"}` -> `"OKAY"`
+    Json,
+}
+
+/// a command read from a connection, `port` is only used by the multiplexed port
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    #[serde(default)]
+    pub port: Option<usize>,
+    pub command: String,
+}
+
+impl Protocol {
+    /// byte ending every command and reply
+    pub fn delimiter(&self) -> u8 {
+        match self {
+            Protocol::Text => b';',
+            Protocol::Json => b'\n',
+        }
+    }
+
+    /// the protocol asked for by a `PROT` command, `None` for any other command
+    pub fn handshake(command: &str) -> Option<Result<Protocol, CommandError>> {
+        let protocol = command.strip_prefix("PROT")?.trim();
+        Some(match protocol {
+            "TEXT" => Ok(Protocol::Text),
+            "JSON" => Ok(Protocol::Json),
+            _ => Err(CommandError::InvalidCommand(format!(
+                "unknown protocol {protocol}"
+            ))),
+        })
+    }
+
+    pub fn parse_request(&self, line: &str) -> Result<Request, CommandError> {
+        match self {
+            Protocol::Text => Ok(Request {
+                port: None,
+                command: line.to_string(),
+            }),
+            Protocol::Json => {
+                serde_json::from_str(line).map_err(|e| CommandError::InvalidRequest(e.to_string()))
+            }
+        }
+    }
+
+    /// frames a reply, replies on the multiplexed port are tagged with the agent's port
+    pub fn encode(&self, response: &Response, port: Option<usize>) -> String {
+        match (self, port) {
+            (Protocol::Text, None) => format!("{};", response.text),
+            (Protocol::Text, Some(port)) => format!("{port} {};", response.text),
+            (Protocol::Json, None) => format!("{}\n", response.json),
+            (Protocol::Json, Some(port)) => format!(
+                "{}\n",
+                serde_json::json!({ "port": port, "reply": response.json })
+            ),
+        }
+    }
+}
+
+/// sends `command` to the main loop and waits for the agent's reply
+pub async fn request(
     port: usize,
-    mut socket: TcpStream,
-    event_sender: UnboundedSender<Event>,
-    rx: &mut Receiver<R>,
-) -> AppResult<String> {
-    info!("hello socket");
-    let mut data = [0; 32];
-    let mut heartbeat = 5;
+    command: String,
+    event_sender: &UnboundedSender<Event>,
+) -> AppResult<Response> {
+    let (reply_sender, reply_receiver) = oneshot::channel();
+    event_sender.send(Event::AgentCommand(port, command, reply_sender))?;
+    tokio::time::sleep(speed::command_latency()).await;
+    Ok(await_reply(reply_receiver, REPLY_TIMEOUT).await)
+}
+
+// the main loop can be slow to reply, initailly happened when saving/loading game
+async fn await_reply(
+    mut reply_receiver: oneshot::Receiver<Response>,
+    timeout: Duration,
+) -> Response {
     loop {
-        info!("top of loop");
-        let n = socket.read(&mut data).await?;
-        //let n = io::read_until(socket, 59, &mut data).await?;
-        info!("post read");
-        if n == 0 {
-            info!("n == 0 heartbeat: {heartbeat}");
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-            if heartbeat < 0 {
-                return Ok("heartbeat timeout".to_string());
-            } else {
-                heartbeat -= 1;
+        match tokio::time::timeout(timeout, &mut reply_receiver).await {
+            Ok(Ok(reply)) => {
+                info!("reply: {}", reply.text);
+                return reply;
+            }
+            Ok(Err(_)) => {
+                warn!("reply dropped");
+                return Response::error(CommandError::NoReply);
+            }
+            // commands are held until the next tick while paused
+            Err(_) if speed::is_paused() => continue,
+            Err(_) => {
+                // a command still queued for a lockstep tick is dropped instead of applied after
+                // the client was told it timed out, see `Surface::update_agent_remote`
+                reply_receiver.close();
+                if let Ok(reply) = reply_receiver.try_recv() {
+                    info!("reply: {}", reply.text);
+                    return reply;
+                }
+                warn!("reply timed out");
+                return Response::error(CommandError::Timeout);
             }
-        } else {
-            let msg = str::from_utf8(&data[0..n])?;
-            info!("msg: {msg}");
-            let reply = Event::AgentCommand(port, msg.to_string());
-            info!("agent reply: {reply:?}");
-            event_sender.send(reply)?;
-            info!("post event_sender send");
-            // TODO 100 here is the AGENT_UPDATE_MILLIS, which should be configured at top level
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-            info!("post sleep");
-            let reply = retry_recv(rx, 0).await;
-            socket.write_all(reply.as_bytes()).await?;
-            info!("post socket write_all");
-            data = [0; 32];
         }
     }
 }
 
-// if for some reason the agent takes > 100 ms to reply, we retry
-// initailly happened when saving/loading game
-// moving more/all long blocking calls to a background task should remove the need for this
-#[async_recursion]
-async fn retry_recv<R>(rx: &mut Receiver<R>, try_count: u8) -> String
-where
-    R: Display + Send,
-{
-    match rx.try_recv() {
-        Ok(reply) => {
-            info!("reply: {reply}");
-            reply.to_string() + ";"
-        }
-        Err(TryRecvError::Empty) => {
-            error!("TryRecvError {try_count}");
-            // TODO excessive sleep time, test/profile lower sleep & higher try_count
-            tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-            if try_count > 10 {
-                panic!("retry_recv failed multiple times")
+/// removes the next command from `buf`, without its delimiter or surrounding whitespace
+pub fn split_frame(buf: &mut BytesMut, delimiter: u8) -> Option<BytesMut> {
+    let end = buf.iter().position(|b| *b == delimiter)?;
+    let mut frame = buf.split_to(end + 1);
+    frame.truncate(end);
+    // pipelined commands are often spaced out, `MOVE; TURN L;`
+    let start = frame
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(frame.len());
+    frame.advance(start);
+    let len = frame
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |last| last + 1);
+    frame.truncate(len);
+    Some(frame)
+}
+
+/// a `SUBS topic` or `UNSB topic` command, `None` for any other command
+pub fn subscription(command: &str) -> Option<Result<(Topic, bool), CommandError>> {
+    let (topic, subscribe) = match command.strip_prefix("SUBS") {
+        Some(topic) => (topic.trim(), true),
+        None => (command.strip_prefix("UNSB")?.trim(), false),
+    };
+    Some(
+        Topic::from_str(topic)
+            .map(|topic| (topic, subscribe))
+            .map_err(|_| CommandError::InvalidCommand(format!("unknown topic {topic}"))),
+    )
+}
+
+/// what [`Connection::read`] got from the client
+pub enum Read<E> {
+    /// bytes were added to the buffer
+    Bytes,
+    /// something for the client it did not ask for, e.g. a subscribed topic
+    Event(E),
+    Closed,
+}
+
+/// a request read from the client or an event, see [`Connection::handle`]
+pub enum Incoming<E> {
+    Request(Request),
+    Event(E),
+}
+
+/// one kind of port served by [`serve_frames`], which answers the `PROT` handshakes itself
+pub trait Connection {
+    type Event;
+
+    fn parse(&self, protocol: Protocol, line: &str) -> Result<Request, CommandError> {
+        protocol.parse_request(line)
+    }
+
+    /// waits for more bytes in `buf` or an event
+    fn read(
+        &mut self,
+        buf: &mut BytesMut,
+    ) -> impl Future<Output = AppResult<Read<Self::Event>>> + Send;
+
+    /// the reply to a request or event framed in `protocol`, `None` if there is nothing to write
+    fn handle(
+        &mut self,
+        incoming: Incoming<Self::Event>,
+        protocol: Protocol,
+    ) -> impl Future<Output = AppResult<Option<String>>> + Send;
+}
+
+/// serves a connection until it is closed or a command is too long, writing replies to `writer`
+pub async fn serve_frames<C: Connection>(
+    connection: &mut C,
+    writer: &mut (impl AsyncWrite + Unpin),
+) -> AppResult<()> {
+    let mut buf = BytesMut::with_capacity(MAX_COMMAND_LEN);
+    let mut protocol = Protocol::default();
+    // TODO do we want timeout if waiting for ';'?
+    loop {
+        // commands pipelined in a single read are answered one at a time, in order
+        while let Some(frame) = split_frame(&mut buf, protocol.delimiter()) {
+            let line = str::from_utf8(&frame)?;
+            info!("msg: {line}");
+            if line.is_empty() {
+                continue;
+            }
+            let reply = match connection.parse(protocol, line) {
+                Ok(request) => match Protocol::handshake(&request.command) {
+                    Some(Ok(requested)) => {
+                        // the handshake is answered in the protocol it was sent in
+                        let reply = protocol.encode(&Response::okay(), None);
+                        protocol = requested;
+                        Some(reply)
+                    }
+                    Some(Err(e)) => Some(protocol.encode(&Response::error(e), None)),
+                    None => {
+                        connection
+                            .handle(Incoming::Request(request), protocol)
+                            .await?
+                    }
+                },
+                Err(e) => Some(protocol.encode(&Response::error(e), None)),
+            };
+            if let Some(reply) = reply {
+                writer.write_all(reply.as_bytes()).await?;
             }
-            //"f".to_string()
-            retry_recv(rx, try_count + 1).await
         }
-        Err(TryRecvError::Disconnected) => panic!("oh no!"),
+        if buf.len() > MAX_COMMAND_LEN {
+            warn!("command too long");
+            let error = CommandError::InvalidRequest("command too long".to_string());
+            let reply = protocol.encode(&Response::error(error), None);
+            writer.write_all(reply.as_bytes()).await?;
+            return Ok(());
+        }
+        let incoming = connection.read(&mut buf).await?;
+        let reply = match incoming {
+            Read::Bytes => None,
+            Read::Event(event) => connection.handle(Incoming::Event(event), protocol).await?,
+            Read::Closed => return Ok(()),
+        };
+        if let Some(reply) = reply {
+            writer.write_all(reply.as_bytes()).await?;
+        }
     }
 }
 
-pub async fn handle_socket<R: Display + Send>(
+/// a connection to an agent's own port
+struct AgentConnection {
     port: usize,
-    mut socket: TcpStream,
+    reader: OwnedReadHalf,
     event_sender: UnboundedSender<Event>,
-    rx: &mut Receiver<R>,
-) -> AppResult<String> {
-    info!("hello socket");
-    let mut buf = BytesMut::with_capacity(32);
-    let mut heartbeat = 5;
-    // TODO do we want timeout if waiting for ';'?
-    loop {
-        info!("top of loop");
-        // TODO: move read loop to its own fuction
-        let msg = loop {
-            socket.readable().await?;
-
-            info!("top of read loop");
-            let n = socket.read_buf(&mut buf).await?;
-            info!("buf: {buf:?}");
-            if n == 0 {
-                info!("n == 0 heartbeat: {heartbeat}");
-                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                if heartbeat < 0 {
-                    return Ok("heartbeat timeout".to_string());
-                } else {
-                    heartbeat -= 1;
+    notifications: broadcast::Receiver<Topic>,
+    subscriptions: HashSet<Topic>,
+    heartbeat: i32,
+}
+
+impl Connection for AgentConnection {
+    type Event = Topic;
+
+    async fn read(&mut self, buf: &mut BytesMut) -> AppResult<Read<Topic>> {
+        info!("top of read loop");
+        loop {
+            tokio::select! {
+                n = self.reader.read_buf(buf) => {
+                    let n = n?;
+                    info!("buf: {buf:?}");
+                    if n == 0 {
+                        info!("n == 0 heartbeat: {}", self.heartbeat);
+                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                        if self.heartbeat < 0 {
+                            info!("heartbeat timeout");
+                            return Ok(Read::Closed);
+                        } else {
+                            self.heartbeat -= 1;
+                        }
+                    }
+                    return Ok(Read::Bytes);
                 }
-                continue;
-            } else if Some(&59) == buf.last() {
-                let msg = str::from_utf8(&buf[..buf.len() - 1])?;
-                info!("msg: {msg}");
-                break msg;
-            } else {
-                info!("else branch");
-                continue;
+                topic = self.notifications.recv(), if !self.subscriptions.is_empty() => match topic {
+                    Ok(topic) => return Ok(Read::Event(topic)),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("port {} missed {n} notifications", self.port);
+                    }
+                    // the agent was removed
+                    Err(broadcast::error::RecvError::Closed) => self.subscriptions.clear(),
+                },
             }
+        }
+    }
+
+    async fn handle(
+        &mut self,
+        incoming: Incoming<Topic>,
+        protocol: Protocol,
+    ) -> AppResult<Option<String>> {
+        let response = match incoming {
+            Incoming::Request(request) => match subscription(&request.command) {
+                Some(Ok((topic, true))) => {
+                    self.subscriptions.insert(topic);
+                    Response::okay()
+                }
+                Some(Ok((topic, false))) => {
+                    self.subscriptions.remove(&topic);
+                    Response::okay()
+                }
+                Some(Err(e)) => Response::error(e),
+                None => self::request(self.port, request.command, &self.event_sender).await?,
+            },
+            Incoming::Event(topic) if self.subscriptions.contains(&topic) => Response::event(topic),
+            Incoming::Event(_) => return Ok(None),
         };
-        // TODO: move write section to its own fuction
-        let reply = Event::AgentCommand(port, msg.to_string());
-        info!("agent reply: {reply:?}");
-        event_sender.send(reply)?;
-        info!("post event_sender send");
-        // TODO 100 here is the AGENT_UPDATE_MILLIS, which should be configured at top level
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        info!("post sleep");
-        let reply = retry_recv(rx, 0).await;
-        socket.writable().await?;
-        socket.write_all(reply.as_bytes()).await?;
-        info!("post socket write_all");
-        buf = BytesMut::with_capacity(32);
+        Ok(Some(protocol.encode(&response, None)))
+    }
+}
+
+pub async fn handle_socket(
+    port: usize,
+    socket: TcpStream,
+    event_sender: UnboundedSender<Event>,
+    notifications: broadcast::Receiver<Topic>,
+) -> AppResult<()> {
+    info!("hello socket");
+    let (reader, mut writer) = socket.into_split();
+    let mut connection = AgentConnection {
+        port,
+        reader,
+        event_sender,
+        notifications,
+        subscriptions: HashSet::new(),
+        heartbeat: 5,
+    };
+    serve_frames(&mut connection, &mut writer).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_frame() {
+        let mut buf = BytesMut::from("MOVE;TURN L;MO");
+        assert_eq!(split_frame(&mut buf, b';').as_deref(), Some(&b"MOVE"[..]));
+        assert_eq!(split_frame(&mut buf, b';').as_deref(), Some(&b"TURN L"[..]));
+        assert_eq!(split_frame(&mut buf, b';'), None);
+        buf.extend_from_slice(b"VE;");
+        assert_eq!(split_frame(&mut buf, b';').as_deref(), Some(&b"MOVE"[..]));
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from("MOVE; TURN L;\r\n STAT ;");
+        assert_eq!(split_frame(&mut buf, b';').as_deref(), Some(&b"MOVE"[..]));
+        assert_eq!(split_frame(&mut buf, b';').as_deref(), Some(&b"TURN L"[..]));
+        assert_eq!(split_frame(&mut buf, b';').as_deref(), Some(&b"STAT"[..]));
+        let mut buf = BytesMut::from(" {\"command\": \"MOVE\"}\r\n");
+        assert_eq!(
+            split_frame(&mut buf, b'\n').as_deref(),
+            Some(&b"{\"command\": \"MOVE\"}"[..])
+        );
+    }
+
+    #[test]
+    fn test_handshake() {
+        assert_eq!(Protocol::handshake("PROT JSON"), Some(Ok(Protocol::Json)));
+        assert_eq!(Protocol::handshake("PROT TEXT"), Some(Ok(Protocol::Text)));
+        assert!(matches!(Protocol::handshake("PROT XML"), Some(Err(_))));
+        assert_eq!(Protocol::handshake("MOVE"), None);
+    }
+
+    #[test]
+    fn test_encode() {
+        let error = Response::error(CommandError::LowBattery);
+        assert_eq!(Protocol::Text.encode(&error, None), "ERRR low battery;");
+        assert_eq!(
+            Protocol::Text.encode(&error, Some(3335)),
+            "3335 ERRR low battery;"
+        );
+        assert_eq!(
+            Protocol::Json.encode(&error, None),
+            "{\"ERRR\":{\"code\":\"LOW_BATTERY\",\"message\":\"low battery\"}}\n"
+        );
+        assert_eq!(
+            Protocol::Json.encode(&Response::okay(), Some(3335)),
+            "{\"port\":3335,\"reply\":\"OKAY\"}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reply_timeout() {
+        let (reply_sender, reply_receiver) = oneshot::channel();
+        let reply = await_reply(reply_receiver, Duration::from_millis(10)).await;
+        assert_eq!(reply.text, Response::error(CommandError::Timeout).text);
+        // the surface skips commands nobody is waiting for
+        assert!(reply_sender.is_closed());
     }
 }
@@ -13,6 +13,7 @@ use ratatui::widgets::Gauge;
 
 use strum_macros;
 
+use crate::agents::CommandError;
 use crate::entities::Entity;
 use crate::ui::tech_tree::EdgeLayout;
 
@@ -211,6 +212,7 @@ impl Tech {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TechStatus {
     Researched,
     Selected,
@@ -293,18 +295,18 @@ impl TechTree {
             .and_then(|node_idx| self.graph.node_weight_mut(NodeIndex::new(node_idx)))
     }
 
-    pub fn set_research(&mut self, node_index: usize) -> Result<(), String> {
+    pub fn set_research(&mut self, node_index: usize) -> Result<(), CommandError> {
         if let Some(tech) = self.graph.node_weight(NodeIndex::new(node_index)) {
             match (tech.unlocked, self.prerequisits_unlocked(node_index)) {
                 (false, true) => {
                     self.research_node = Some(node_index);
                     Ok(())
                 }
-                (true, _) => Err("already researched".to_string()),
-                (false, false) => Err("prerequisits not met".to_string()),
+                (true, _) => Err(CommandError::AlreadyResearched),
+                (false, false) => Err(CommandError::PrerequisitesNotMet),
             }
         } else {
-            Err("node not found".to_string())
+            Err(CommandError::UnknownResearch)
         }
     }
 
@@ -479,7 +481,10 @@ fn get_edge_layout(start: &NodeLayout, end: &NodeLayout) -> EdgeLayout {
     let mut start_position = bottom(start.area);
     loop {
         if start.ocupied_terminals.contains(&start_position) {
-            if target_is_left {
+            // terminals squeezed against the left edge of a tiny terminal share a position
+            if target_is_left && start_position.x == 0 {
+                break;
+            } else if target_is_left {
                 start_position.x -= 1;
             } else {
                 start_position.x += 1;
@@ -489,8 +494,8 @@ fn get_edge_layout(start: &NodeLayout, end: &NodeLayout) -> EdgeLayout {
             break;
         }
     }
-    let directly_down =
-        end.area.x < start_position.x && start_position.x < (end.area.x + end.area.width - 1);
+    let directly_down = end.area.x < start_position.x
+        && start_position.x < (end.area.x + end.area.width).saturating_sub(1);
     let mut end_position = if directly_down {
         Position {
             x: start_position.x,
@@ -503,6 +508,8 @@ fn get_edge_layout(start: &NodeLayout, end: &NodeLayout) -> EdgeLayout {
         if end.ocupied_terminals.contains(&end_position) {
             if target_is_left {
                 end_position.x += 1;
+            } else if end_position.x == 0 {
+                break;
             } else {
                 end_position.x -= 1;
             }
@@ -528,7 +535,7 @@ fn top(rect: Rect) -> Position {
 fn bottom(rect: Rect) -> Position {
     Position {
         x: rect.x + (rect.width / 2),
-        y: rect.y + rect.height - 1,
+        y: (rect.y + rect.height).saturating_sub(1),
     }
 }
 
@@ -0,0 +1,72 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Margin, Rect},
+    style::{Color, Style},
+    widgets::{Block, Clear, block::Title},
+};
+
+use crate::agents::CommLogs;
+use crate::app::App;
+use crate::ui::render_widget_clamped;
+use crate::widgets::text_box::TextBox;
+
+/// most rows the console covers at the bottom of the surface
+const HEIGHT: u16 = 12;
+
+/// cheats typed in game, opened with [`] on the surface, see [`crate::surface::cheat`]
+#[derive(Debug)]
+pub struct Console {
+    pub text_box: TextBox,
+    /// cheats from the console and the cheat port with their replies
+    pub log: CommLogs,
+    pub previous_command_counter: u8,
+}
+
+impl Default for Console {
+    fn default() -> Console {
+        Console {
+            text_box: TextBox::new().clear_on_enter(true),
+            log: CommLogs::default(),
+            previous_command_counter: 0,
+        }
+    }
+}
+
+pub fn render(app: &App, frame: &mut Frame) {
+    let surface = app.layout.surface.surface;
+    let height = surface.height.min(HEIGHT);
+    let area = Rect {
+        y: surface.bottom() - height,
+        height,
+        ..surface
+    };
+    let status = if app.surface.game_state.stats.cheated {
+        "cheats used─[ESC]"
+    } else {
+        "[ESC]"
+    };
+    let block = Block::bordered()
+        .title("Console")
+        .title(Title::from(status).alignment(Alignment::Right))
+        .style(Style::default().fg(Color::Yellow).bg(Color::Black));
+    render_widget_clamped(frame, Clear, area);
+    render_widget_clamped(frame, block, area);
+
+    let inner = area.inner(Margin::new(1, 1));
+    let rows = inner.height.saturating_sub(1);
+    let log = app.console.log.list(rows as u8, inner.width as usize);
+    render_widget_clamped(
+        frame,
+        log,
+        Rect {
+            height: rows,
+            ..inner
+        },
+    );
+    let text_box = Rect {
+        y: inner.y + rows,
+        height: 1,
+        ..inner
+    };
+    render_widget_clamped(frame, &app.console.text_box, text_box);
+}
@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use crate::app::App;
+use crate::ui::render_widget_clamped;
+
+/// frames, ticks and commands are measured over the last second
+const WINDOW: Duration = Duration::from_secs(1);
+
+const WIDTH: u16 = 30;
+const HEIGHT: u16 = 6;
+
+/// durations measured within [`WINDOW`]
+#[derive(Debug, Default)]
+struct Samples {
+    samples: VecDeque<(Instant, Duration)>,
+}
+
+impl Samples {
+    fn push(&mut self, now: Instant, duration: Duration) {
+        self.samples.push_back((now, duration));
+        while let Some((at, _)) = self.samples.front()
+            && now.duration_since(*at) > WINDOW
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    fn mean(&self) -> Duration {
+        let total: Duration = self.samples.iter().map(|(_, d)| *d).sum();
+        total
+            .checked_div(self.samples.len() as u32)
+            .unwrap_or_default()
+    }
+
+    fn max(&self) -> Duration {
+        self.samples
+            .iter()
+            .map(|(_, d)| *d)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// frame time, tick time, event queue depth and commands per second, toggled with [F3]
+#[derive(Debug, Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    frames: Samples,
+    ticks: Samples,
+    /// events still waiting in the channel after the last one was taken
+    pub queue_depth: usize,
+    /// commands applied so far at each tick within [`WINDOW`]
+    commands: VecDeque<(Instant, u64)>,
+}
+
+impl DebugOverlay {
+    pub fn record_frame(&mut self, duration: Duration) {
+        self.frames.push(Instant::now(), duration);
+    }
+
+    /// `commands` is the number of commands applied since the start of the game
+    pub fn record_tick(&mut self, duration: Duration, commands: u64) {
+        self.record_tick_at(Instant::now(), duration, commands);
+    }
+
+    fn record_tick_at(&mut self, now: Instant, duration: Duration, commands: u64) {
+        self.ticks.push(now, duration);
+        // a new game restarts the count
+        if self.commands.back().is_some_and(|(_, c)| commands < *c) {
+            self.commands.clear();
+        }
+        self.commands.push_back((now, commands));
+        while let Some((at, _)) = self.commands.front()
+            && now.duration_since(*at) > WINDOW
+        {
+            self.commands.pop_front();
+        }
+    }
+
+    pub fn commands_per_second(&self) -> f64 {
+        match (self.commands.front(), self.commands.back()) {
+            (Some((first_at, first)), Some((last_at, last))) if first_at < last_at => {
+                (last - first) as f64 / last_at.duration_since(*first_at).as_secs_f64()
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+pub fn render(app: &App, frame: &mut Frame) {
+    let debug = &app.debug;
+    let text = format!(
+        "frame {:>8}  max {:>8}\ntick  {:>8}  max {:>8}\nqueue {:>8}\ncmds  {:>8.1}/s",
+        millis(debug.frames.mean()),
+        millis(debug.frames.max()),
+        millis(debug.ticks.mean()),
+        millis(debug.ticks.max()),
+        debug.queue_depth,
+        debug.commands_per_second(),
+    );
+    let area = Rect {
+        x: frame.area().width.saturating_sub(WIDTH),
+        y: 0,
+        width: WIDTH,
+        height: HEIGHT,
+    };
+    let paragraph = Paragraph::new(text)
+        .block(Block::bordered().title("Debug─[F3]"))
+        .style(Style::default().fg(Color::Yellow).bg(Color::Black));
+    render_widget_clamped(frame, Clear, area);
+    render_widget_clamped(frame, paragraph, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window() {
+        let start = Instant::now();
+        let mut debug = DebugOverlay::default();
+        for (i, ms) in [4, 2, 6].into_iter().enumerate() {
+            let at = start + Duration::from_millis(500 * i as u64);
+            debug.record_tick_at(at, Duration::from_millis(ms), 10 * i as u64);
+        }
+        assert_eq!(debug.ticks.mean(), Duration::from_millis(4));
+        assert_eq!(debug.ticks.max(), Duration::from_millis(6));
+        assert_eq!(debug.commands_per_second(), 20.0);
+
+        // the first tick falls out of the window
+        debug.record_tick_at(start + Duration::from_millis(1400), Duration::ZERO, 40);
+        assert_eq!(debug.ticks.max(), Duration::from_millis(6));
+        assert_eq!(debug.ticks.samples.len(), 3);
+        assert_eq!(debug.commands_per_second(), 30.0 / 0.9);
+
+        // a new game
+        debug.record_tick_at(start + Duration::from_millis(1700), Duration::ZERO, 1);
+        assert_eq!(debug.commands_per_second(), 0.0);
+    }
+}
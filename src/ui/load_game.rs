@@ -1,18 +1,84 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
 use ratatui::{
     Frame,
     layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
-    style::{Color, Style},
-    widgets::{Block, Paragraph, block::Title},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, block::Title},
 };
 
-use crate::app::{App, LoadingState};
-use crate::ui::render_widget_clamped;
+use crate::app::{App, DisplayPathBuf, LoadingState};
+use crate::surface::state::{GameStats, Seed};
+use crate::ui::{center, render_widget_clamped};
+use crate::widgets::text_box::TextBox;
+
+/// order of the save files list
+#[derive(Debug, Default, Clone, Copy, PartialEq, strum_macros::Display)]
+pub enum SaveSort {
+    #[default]
+    Name,
+    /// most recently written first
+    Date,
+    /// longest played first
+    Playtime,
+    /// newest version first
+    Version,
+}
+
+impl SaveSort {
+    pub fn next(self) -> SaveSort {
+        match self {
+            SaveSort::Name => SaveSort::Date,
+            SaveSort::Date => SaveSort::Playtime,
+            SaveSort::Playtime => SaveSort::Version,
+            SaveSort::Version => SaveSort::Name,
+        }
+    }
+}
+
+/// a file operation on the load game screen waiting for input
+#[derive(Debug)]
+pub enum SaveFileAction {
+    ConfirmDelete(PathBuf),
+    Rename(PathBuf, TextBox),
+}
+
+#[derive(Debug)]
+pub struct SaveFileManager {
+    pub sort: SaveSort,
+    pub action: Option<SaveFileAction>,
+    /// outcome of the last file operation
+    pub message: Option<String>,
+    /// how the time a save was written is shown
+    pub format_time: fn(SystemTime) -> String,
+}
+
+impl Default for SaveFileManager {
+    fn default() -> Self {
+        SaveFileManager {
+            sort: SaveSort::default(),
+            action: None,
+            message: None,
+            format_time: local_time,
+        }
+    }
+}
+
+/// `time` in the machine's time zone
+pub fn local_time(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(time)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
 
 #[derive(Debug, Default)]
 pub struct LoadGameLayout {
     pub save_files: Rect,
     pub surface_preview: Rect,
     pub metadata: Rect,
+    pub popup: Rect,
 }
 
 impl LoadGameLayout {
@@ -38,12 +104,21 @@ impl LoadGameLayout {
             save_files: columns[1],
             surface_preview: left_column[0],
             metadata: left_column[1],
+            popup: center(columns[1], Constraint::Length(50), Constraint::Length(4)),
         }
     }
 }
 
 pub fn render(app: &App, frame: &mut Frame) {
-    let border = save_file_boarder();
+    let manager = &app.save_manager;
+    let keys = format!(
+        "delete [D]  rename [R]  copy [C]  sort [S]: {}",
+        manager.sort
+    );
+    let mut border = save_file_boarder().title_bottom(keys);
+    if let Some(message) = &manager.message {
+        border = border.title_bottom(Line::from(message.as_str()).right_aligned());
+    }
     render_widget_clamped(frame, border, app.layout.load_game.save_files);
 
     render_widget_clamped(
@@ -59,6 +134,37 @@ pub fn render(app: &App, frame: &mut Frame) {
         &app.previous_screen_button,
         app.layout.previous_screen_button,
     );
+    render_action(app, frame);
+}
+
+fn render_action(app: &App, frame: &mut Frame) {
+    let area = app.layout.load_game.popup;
+    let style = Style::default().fg(Color::Green).bg(Color::Black);
+    match &app.save_manager.action {
+        Some(SaveFileAction::ConfirmDelete(path)) => {
+            let name = DisplayPathBuf::from(path.clone()).to_string();
+            let popup = Paragraph::new(format!("delete {name} and its journal?"))
+                .centered()
+                .block(
+                    Block::bordered()
+                        .title("Delete")
+                        .title(Title::from("yes [Y]  no [N]").alignment(Alignment::Right)),
+                )
+                .style(style);
+            render_widget_clamped(frame, Clear, area);
+            render_widget_clamped(frame, popup, area);
+        }
+        Some(SaveFileAction::Rename(_, text_box)) => {
+            let block = Block::bordered()
+                .title("Rename")
+                .title(Title::from("[ENTER]  cancel [ESC]").alignment(Alignment::Right))
+                .style(style);
+            render_widget_clamped(frame, Clear, area);
+            render_widget_clamped(frame, block, area);
+            render_widget_clamped(frame, text_box, area.inner(Margin::new(1, 1)));
+        }
+        None => (),
+    }
 }
 
 pub fn save_file_boarder() -> Block<'static> {
@@ -112,11 +218,83 @@ pub fn render_save_file_preview(app: &App, frame: &mut Frame) {
     }
 }
 
+fn stat_line(label: &str, value: String) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(
+            format!("{label:<14}"),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(value),
+    ])
+}
+
+fn stat_lines(
+    stats: &GameStats,
+    path: Option<&PathBuf>,
+    format_time: fn(SystemTime) -> String,
+) -> Vec<Line<'static>> {
+    let (kind, seed) = match stats.seed {
+        Seed::Manual(x) => ("manual", x),
+        Seed::Random(x) => ("random", x),
+    };
+    // a bar of 10 cells for the research progress
+    let filled = (stats.research_complete * 10)
+        .checked_div(stats.research_count)
+        .unwrap_or(0);
+    let research = format!(
+        "{}{} {}/{}",
+        "█".repeat(filled),
+        "░".repeat(10 - filled.min(10)),
+        stats.research_complete,
+        stats.research_count
+    );
+    let total_agents: u64 = stats.agent_count.values().sum();
+    let mut lines = vec![
+        stat_line("version", stats.version.to_string()),
+        stat_line("seed", format!("{seed} ({kind})")),
+        stat_line(
+            "playtime",
+            crate::utils::human_readable_tick_count(stats.tick_count),
+        ),
+        stat_line("research", research),
+        stat_line(
+            "commands",
+            format!(
+                "{} automated, {} manual",
+                stats.tcp_command_count, stats.manual_command_count
+            ),
+        ),
+        stat_line("agents", total_agents.to_string()),
+    ];
+    for (agent, count) in stats.agent_count.iter() {
+        lines.push(stat_line(&format!("  {agent}"), count.to_string()));
+    }
+    if stats.cheated {
+        lines.push(stat_line("cheats", "used".to_string()));
+    }
+    if let Some(metadata) = path.and_then(|path| path.metadata().ok()) {
+        if let Ok(modified) = metadata.modified() {
+            lines.push(stat_line("saved", format_time(modified)));
+        }
+        lines.push(stat_line(
+            "size",
+            format!("{} KB", metadata.len().div_ceil(1000)),
+        ));
+    }
+    lines
+}
+
 pub fn render_save_file_metadata(app: &App, frame: &mut Frame) {
     let content = match app.loading_state() {
-        LoadingState::Loaded(state) => state.game_state.stats.to_string(),
-        LoadingState::Loading => "loading...".to_string(),
-        LoadingState::Failed(error) => format!("failed to load:\n{error}"),
+        LoadingState::Loaded(state) => stat_lines(
+            &state.game_state.stats,
+            app.save_files.selected().map(|path| &path.inner),
+            app.save_manager.format_time,
+        ),
+        LoadingState::Loading => vec![Line::from("loading...")],
+        LoadingState::Failed(error) => {
+            vec![Line::from("failed to load:"), Line::from(error.clone())]
+        }
     };
 
     let paragraph = Paragraph::new(content)
@@ -19,6 +19,7 @@ use std::time::Duration;
 pub struct MainMenuLayout {
     pub menu: Rect,
     pub set_seed: Rect,
+    pub world_size: Rect,
 }
 
 impl MainMenuLayout {
@@ -29,7 +30,7 @@ impl MainMenuLayout {
             width,
             height,
         };
-        let menu = center(area, Constraint::Length(25), Constraint::Length(4));
+        let menu = center(area, Constraint::Length(25), Constraint::Length(6));
 
         let set_seed = Rect {
             x: menu.x,
@@ -37,14 +38,24 @@ impl MainMenuLayout {
             width: menu.width,
             height: 3,
         };
-        MainMenuLayout { menu, set_seed }
+        let world_size = Rect {
+            y: set_seed.bottom(),
+            ..set_seed
+        };
+        MainMenuLayout {
+            menu,
+            set_seed,
+            world_size,
+        }
     }
 }
 
 #[derive(Debug, Copy, Clone, strum_macros::Display, strum_macros::EnumIter)]
 #[strum(serialize_all = "title_case")]
 pub enum MainMenu {
+    Continue,
     NewGame,
+    Scenarios,
     LoadGame,
     Settings,
     Exit,
@@ -54,7 +65,9 @@ impl MainMenu {
     pub fn list() -> ClickList<MainMenu> {
         let list = MainMenu::iter().collect();
         let lines: Vec<AlignedLine> = vec![
+            AlignedLine::from(MainMenu::Continue.to_string()),
             AlignedLine::from(MainMenu::NewGame.to_string()),
+            AlignedLine::from(MainMenu::Scenarios.to_string()),
             AlignedLine::from(MainMenu::LoadGame.to_string()),
             AlignedLine::from(MainMenu::Settings.to_string()),
             AlignedLine::left_right(MainMenu::Exit.to_string(), "[CTRL + D]".to_string()),
@@ -80,6 +93,18 @@ fn render_set_seed(app: &App, frame: &mut Frame) {
     render_widget_clamped(frame, seed_input, app.layout.main_menu.set_seed);
 }
 
+fn render_world_size(app: &App, frame: &mut Frame) {
+    let content = format!("{0} x {0}", app.world_size);
+    let world_size = Paragraph::new(content).alignment(Alignment::Center).block(
+        Block::default()
+            .title("World Size")
+            .title(Title::from("[←→]").alignment(Alignment::Right))
+            .borders(Borders::ALL),
+    );
+
+    render_widget_clamped(frame, world_size, app.layout.main_menu.world_size);
+}
+
 pub fn render(app: &App, frame: &mut Frame) {
     let logo = indoc::indoc! {"\n\n\n
         ░        ░░        ░░  ░░░░  ░░░      ░░░        ░░░      ░░░       ░░░  ░░░░  ░
@@ -95,6 +120,7 @@ pub fn render(app: &App, frame: &mut Frame) {
     render_widget_clamped(frame, logo, app.layout.whole_screen());
     render_widget_clamped(frame, &app.main_menu, app.layout.main_menu.menu);
     render_set_seed(app, frame);
+    render_world_size(app, frame);
 }
 
 pub fn render_fx(
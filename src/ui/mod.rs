@@ -1,8 +1,12 @@
+pub mod console;
+pub mod debug;
 pub mod documentation;
-mod load_game;
+pub mod load_game;
 pub mod main_menu;
 pub mod pause_menu;
+pub mod replay;
 mod save_game;
+mod scenarios;
 mod settings;
 mod surface;
 pub mod tech_tree;
@@ -60,6 +64,8 @@ pub enum Screen {
     Surface,
     Documentation,
     TechTree,
+    Replay,
+    Scenarios,
 }
 
 impl std::fmt::Display for Screen {
@@ -73,6 +79,8 @@ impl std::fmt::Display for Screen {
             Screen::Surface => write!(f, "Surface"),
             Screen::Documentation => write!(f, "Documentation"),
             Screen::TechTree => write!(f, "Technology Tree"),
+            Screen::Replay => write!(f, "Replay"),
+            Screen::Scenarios => write!(f, "Scenarios"),
         }
     }
 }
@@ -89,6 +97,8 @@ pub struct AppLayout {
     pub save_game: save_game::SaveGameLayout,
     pub documentation: documentation::DocumentationLayout,
     pub tech_tree: tech_tree::TechTreeLayout,
+    pub replay: replay::ReplayLayout,
+    pub scenarios: scenarios::ScenariosLayout,
 }
 
 impl AppLayout {
@@ -104,6 +114,8 @@ impl AppLayout {
             save_game: save_game::SaveGameLayout::new(width, height, app),
             documentation: documentation::DocumentationLayout::new(width, height, app),
             tech_tree: tech_tree::TechTreeLayout::new(width, height, app),
+            replay: replay::ReplayLayout::new(width, height),
+            scenarios: scenarios::ScenariosLayout::new(width, height),
         }
     }
 
@@ -179,5 +191,10 @@ pub fn render(app: &mut App, frame: &mut Frame) {
         Screen::TechTree => {
             tech_tree::render(app, frame);
         }
+        Screen::Replay => replay::render(app, frame),
+        Screen::Scenarios => scenarios::render(app, frame),
+    }
+    if app.debug.visible {
+        debug::render(app, frame);
     }
 }
@@ -7,6 +7,7 @@ use ratatui::{
 use strum::IntoEnumIterator;
 
 use crate::app::App;
+use crate::speed;
 use crate::ui::{center, render_widget_clamped};
 use crate::widgets::list::{AlignedLine, ClickList};
 
@@ -24,7 +25,7 @@ impl PauseMenuLayout {
             height,
         };
         PauseMenuLayout {
-            menu: center(area, Constraint::Percentage(20), Constraint::Length(6)),
+            menu: center(area, Constraint::Percentage(20), Constraint::Length(7)),
         }
     }
 }
@@ -36,6 +37,7 @@ pub enum PauseMenu {
     SaveGame,
     Documentation,
     TechnologyTree,
+    Speed,
     Settings,
     MainMenu,
 }
@@ -48,6 +50,7 @@ impl PauseMenu {
             AlignedLine::from(PauseMenu::SaveGame.to_string()),
             AlignedLine::left_right(PauseMenu::Documentation.to_string(), "[D]".to_string()),
             AlignedLine::left_right(PauseMenu::TechnologyTree.to_string(), "[T]".to_string()),
+            PauseMenu::speed_line(),
             AlignedLine::from(PauseMenu::Settings.to_string()),
             AlignedLine::from(PauseMenu::MainMenu.to_string()),
         ];
@@ -55,6 +58,18 @@ impl PauseMenu {
         list.select(0);
         list
     }
+
+    fn speed_line() -> AlignedLine {
+        AlignedLine::left_right(
+            format!("{} {}", PauseMenu::Speed, speed::describe()),
+            "[S]".to_string(),
+        )
+    }
+
+    /// speed can also be changed by the HUD agent
+    pub fn refresh_speed(list: &mut ClickList<PauseMenu>) {
+        list.set_line(PauseMenu::Speed as usize, PauseMenu::speed_line());
+    }
 }
 
 pub fn render(app: &App, frame: &mut Frame) {
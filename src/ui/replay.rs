@@ -0,0 +1,318 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Margin, Position, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, Paragraph, block::title::Title},
+};
+
+use ratatui::prelude::Alignment;
+
+use crate::app::App;
+use crate::surface::recording::{Milestone, Recording, Snapshot};
+use crate::theme::DEFAULT_STYLE;
+use crate::ui::render_widget_clamped;
+
+/// recorded ticks played per app tick
+const SPEEDS: [u64; 6] = [1, 4, 16, 64, 256, 1024];
+const DEFAULT_SPEED: usize = 3;
+
+/// playback state of a recording shown on [`crate::ui::Screen::Replay`]
+#[derive(Debug)]
+pub struct Viewer {
+    pub recording: Recording,
+    pub milestones: Vec<(u64, Milestone)>,
+    pub tick: u64,
+    pub playing: bool,
+    speed: usize,
+    /// grid position shown in the top left corner
+    pub offset: Position,
+    /// digits typed so far for a seek
+    pub seek: String,
+}
+
+impl Viewer {
+    pub fn new(recording: Recording) -> Viewer {
+        let milestones = recording.milestones();
+        let tick = recording.first_tick();
+        Viewer {
+            recording,
+            milestones,
+            tick,
+            playing: true,
+            speed: DEFAULT_SPEED,
+            offset: Position::ORIGIN,
+            seek: String::new(),
+        }
+    }
+
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        self.recording.at(self.tick)
+    }
+
+    pub fn speed(&self) -> u64 {
+        SPEEDS[self.speed]
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    /// moves playback forward one app tick, stopping at the end of the recording
+    pub fn advance(&mut self) {
+        if !self.playing {
+            return;
+        }
+        self.seek(self.tick + self.speed());
+        if self.tick == self.recording.last_tick() {
+            self.playing = false;
+        }
+    }
+
+    pub fn seek(&mut self, tick: u64) {
+        self.tick = tick.clamp(self.recording.first_tick(), self.recording.last_tick());
+    }
+
+    /// seeks to the tick typed with the number keys
+    pub fn seek_typed(&mut self) {
+        if let Ok(tick) = self.seek.parse() {
+            self.seek(tick);
+        }
+        self.seek.clear();
+    }
+
+    /// seeks to the snapshot before the one being shown
+    pub fn previous_snapshot(&mut self) {
+        let current = self.snapshot().map(Snapshot::tick).unwrap_or_default();
+        if let Some(snapshot) = self
+            .recording
+            .snapshots
+            .iter()
+            .rev()
+            .find(|s| s.tick() < current)
+        {
+            self.tick = snapshot.tick();
+        }
+    }
+
+    pub fn next_snapshot(&mut self) {
+        if let Some(snapshot) = self
+            .recording
+            .snapshots
+            .iter()
+            .find(|s| s.tick() > self.tick)
+        {
+            self.tick = snapshot.tick();
+        }
+    }
+
+    pub fn previous_milestone(&mut self) {
+        if let Some((tick, _)) = self.milestones.iter().rev().find(|(t, _)| *t < self.tick) {
+            self.tick = *tick;
+        }
+    }
+
+    pub fn next_milestone(&mut self) {
+        if let Some((tick, _)) = self.milestones.iter().find(|(t, _)| *t > self.tick) {
+            self.tick = *tick;
+        }
+    }
+
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        self.offset.x = (self.offset.x as i32 + dx).max(0) as u16;
+        self.offset.y = (self.offset.y as i32 + dy).max(0) as u16;
+    }
+
+    /// tick at column `x` of the timeline bar
+    pub fn tick_at(&self, bar: Rect, x: u16) -> u64 {
+        let first = self.recording.first_tick();
+        let span = self.recording.last_tick() - first;
+        let width = bar.width.saturating_sub(1).max(1) as u64;
+        first + x.saturating_sub(bar.x).min(bar.width) as u64 * span / width
+    }
+
+    /// column of the timeline bar that `tick` is drawn at
+    fn column(&self, bar: Rect, tick: u64) -> u16 {
+        let first = self.recording.first_tick();
+        let span = (self.recording.last_tick() - first).max(1);
+        let width = bar.width.saturating_sub(1) as u64;
+        bar.x + (tick.saturating_sub(first) * width / span) as u16
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayLayout {
+    pub grid: Rect,
+    pub timeline: Rect,
+    /// the inner row of the timeline that milestones are drawn on and clicks seek along
+    pub bar: Rect,
+    pub stats: Rect,
+    pub milestones: Rect,
+    pub help: Rect,
+}
+
+impl ReplayLayout {
+    pub fn new(width: u16, height: u16) -> ReplayLayout {
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        };
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(75), Constraint::Min(30)])
+            .split(area);
+        // the grid is drawn relative to the frame origin so it stays in the top left
+        let left_col = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Length(4)])
+            .split(chunks[0]);
+        let right_col = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(12),
+                Constraint::Fill(1),
+                Constraint::Length(8),
+            ])
+            .split(chunks[1]);
+        let timeline = left_col[1];
+        let inner = timeline.inner(Margin::new(1, 1));
+        ReplayLayout {
+            grid: left_col[0],
+            timeline,
+            bar: Rect { height: 2, ..inner },
+            stats: right_col[0],
+            milestones: right_col[1],
+            help: right_col[2],
+        }
+    }
+}
+
+fn render_timeline(viewer: &Viewer, layout: &ReplayLayout, frame: &mut Frame) {
+    let status = if !viewer.seek.is_empty() {
+        format!("seek to tick: {}_", viewer.seek)
+    } else if viewer.playing {
+        format!("playing {}x", viewer.speed())
+    } else {
+        "paused".to_string()
+    };
+    let block = Block::bordered()
+        .title(format!(
+            "Tick {} / {}",
+            viewer.tick,
+            viewer.recording.last_tick()
+        ))
+        .title(Title::from(status).alignment(Alignment::Right))
+        .style(DEFAULT_STYLE);
+    render_widget_clamped(frame, block, layout.timeline);
+
+    let bar = layout.bar;
+    let mut markers = vec![' '; bar.width as usize];
+    for (tick, milestone) in viewer.milestones.iter() {
+        let idx = (viewer.column(bar, *tick) - bar.x) as usize;
+        if let Some(marker) = markers.get_mut(idx) {
+            *marker = match (milestone, *marker) {
+                (Milestone::Research(_), ' ') => 'R',
+                (Milestone::Build(_), ' ') => 'B',
+                (_, _) => '*',
+            };
+        }
+    }
+    let played = viewer.column(bar, viewer.tick) - bar.x + 1;
+    let lines = vec![
+        Line::from(markers.into_iter().collect::<String>()),
+        Line::from(vec![
+            Span::styled(
+                "━".repeat(played as usize),
+                Style::default().fg(Color::Green),
+            ),
+            Span::styled(
+                "─".repeat(bar.width.saturating_sub(played) as usize),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+    ];
+    render_widget_clamped(frame, Paragraph::new(lines).style(DEFAULT_STYLE), bar);
+}
+
+fn render_stats(snapshot: &Snapshot, layout: &ReplayLayout, frame: &mut Frame) {
+    let power = &snapshot.power;
+    let text = format!(
+        "{}\n\npower: +{}j -{}j {}Kj/{}Kj",
+        snapshot.stats,
+        power.generation,
+        power.consumption,
+        power.stored / 1000,
+        power.capacity / 1000
+    );
+    let stats = Paragraph::new(text)
+        .block(Block::bordered().title("Stats"))
+        .style(DEFAULT_STYLE);
+    render_widget_clamped(frame, stats, layout.stats);
+}
+
+fn render_milestones(viewer: &Viewer, layout: &ReplayLayout, frame: &mut Frame) {
+    let reached = viewer
+        .milestones
+        .iter()
+        .filter(|(tick, _)| *tick <= viewer.tick)
+        .count();
+    // keep the latest reached milestone in view
+    let height = layout.milestones.height.saturating_sub(2) as usize;
+    let skip = reached.saturating_sub(height);
+    let items: Vec<ListItem> = viewer
+        .milestones
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(idx, (tick, milestone))| {
+            let color = if idx < reached {
+                Color::Green
+            } else {
+                Color::DarkGray
+            };
+            ListItem::new(format!("{tick:>8} {milestone}")).style(Style::default().fg(color))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::bordered().title("Milestones"))
+        .style(DEFAULT_STYLE);
+    render_widget_clamped(frame, list, layout.milestones);
+}
+
+fn render_help(layout: &ReplayLayout, frame: &mut Frame) {
+    let help = Paragraph::new(
+        "play/pause [SPACE]\n\
+         speed [+] [-]\n\
+         snapshot [,] [.]\n\
+         milestone [[] []]\n\
+         seek [0-9] [ENTER]\n\
+         pan [ARROWS]",
+    )
+    .block(
+        Block::bordered()
+            .title("Replay")
+            .title(Title::from("[ESC]").alignment(Alignment::Right)),
+    )
+    .style(DEFAULT_STYLE);
+    render_widget_clamped(frame, help, layout.help);
+}
+
+pub fn render(app: &App, frame: &mut Frame) {
+    let layout = &app.layout.replay;
+    let Some(viewer) = &app.viewer else {
+        return;
+    };
+    if let Some(snapshot) = viewer.snapshot() {
+        snapshot.grid.render(frame, layout.grid, viewer.offset);
+        render_stats(snapshot, layout, frame);
+    }
+    render_timeline(viewer, layout, frame);
+    render_milestones(viewer, layout, frame);
+    render_help(layout, frame);
+}
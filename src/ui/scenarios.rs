@@ -0,0 +1,124 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Wrap, block::Title},
+};
+
+use crate::app::App;
+use crate::surface::scenario::{Map, Scenario};
+use crate::ui::render_widget_clamped;
+
+#[derive(Debug, Default)]
+pub struct ScenariosLayout {
+    pub scenarios: Rect,
+    pub details: Rect,
+}
+
+impl ScenariosLayout {
+    pub fn new(width: u16, height: u16) -> ScenariosLayout {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            });
+        ScenariosLayout {
+            details: columns[0],
+            scenarios: columns[1],
+        }
+    }
+}
+
+fn detail_line(label: &str, value: String) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(
+            format!("{label:<10}"),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(value),
+    ])
+}
+
+fn detail_lines(scenario: &Scenario) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(scenario.description.clone()), Line::from("")];
+    let map = match &scenario.map {
+        Map::Seed(seed) => format!("seed {seed}"),
+        Map::Grid(rows) => format!(
+            "hand authored {}x{}",
+            rows.iter()
+                .map(|row| row.chars().count())
+                .max()
+                .unwrap_or(0),
+            rows.len()
+        ),
+    };
+    lines.push(detail_line("map", map));
+    let agents: Vec<String> = scenario
+        .agents
+        .iter()
+        .map(|agent| agent.kind.to_string())
+        .collect();
+    lines.push(detail_line("agents", agents.join(", ")));
+    if let Some(unlocked) = &scenario.unlocked {
+        let unlocked: Vec<String> = unlocked.iter().map(|e| e.to_string()).collect();
+        lines.push(detail_line("unlocked", unlocked.join(", ")));
+    }
+    let researched: Vec<String> = scenario
+        .tech
+        .iter()
+        .filter(|tech| tech.researched)
+        .map(|tech| tech.tech.to_string())
+        .collect();
+    if !researched.is_empty() {
+        lines.push(detail_line("research", researched.join(", ")));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::styled(
+        "Goals",
+        Style::default().add_modifier(Modifier::BOLD),
+    ));
+    if scenario.goals.is_empty() {
+        lines.push(Line::from("  research SELF_SUFFICIENT"));
+    }
+    for goal in scenario.goals.iter() {
+        lines.push(Line::from(format!("  {goal}")));
+    }
+    lines
+}
+
+pub fn render(app: &App, frame: &mut Frame) {
+    let style = Style::default().fg(Color::Green).bg(Color::Black);
+    let dir = Scenario::dir().to_string_lossy().into_owned();
+    let border = Block::bordered()
+        .title("Scenarios─")
+        .title(Title::from(dir).alignment(Alignment::Right))
+        .title_bottom("start [ENTER]")
+        .style(style);
+    render_widget_clamped(frame, border, app.layout.scenarios.scenarios);
+    render_widget_clamped(
+        frame,
+        &app.scenarios,
+        app.layout.scenarios.scenarios.inner(Margin::new(1, 1)),
+    );
+
+    let (title, content) = match app.scenarios.selected() {
+        Some(scenario) => (scenario.name.clone(), detail_lines(scenario)),
+        None => ("Scenario".to_string(), vec![]),
+    };
+    let details = Paragraph::new(content)
+        .wrap(Wrap { trim: false })
+        .block(Block::bordered().title(title))
+        .style(style);
+    render_widget_clamped(frame, details, app.layout.scenarios.details);
+
+    render_widget_clamped(
+        frame,
+        &app.previous_screen_button,
+        app.layout.previous_screen_button,
+    );
+}
@@ -3,19 +3,26 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Position, Rect},
     style::{Color, Style},
     widgets::block::title::Title,
-    widgets::{Block, Borders, Gauge, List, ListState, Paragraph, Sparkline, Wrap},
+    widgets::{
+        Bar, BarChart, BarGroup, Block, Borders, Clear, Gauge, List, ListState, Paragraph,
+        Sparkline, Wrap,
+    },
 };
 use strum::IntoEnumIterator;
 
+use crate::results::{BINS, Histogram};
+use crate::surface::scenario::Progress;
+use crate::surface::state::VictoryStats;
 use crate::surface::tutorial::Tutorial;
 use crate::theme::DEFAULT_STYLE;
 
 use ratatui::prelude::*;
 
 use crate::app::{App, InputMode};
+use crate::speed;
 use crate::surface::grid::Gent;
 use crate::surface::{Focus, Surface};
-use crate::ui::{render_stateful_widget_clamped, render_widget_clamped};
+use crate::ui::{console, render_stateful_widget_clamped, render_widget_clamped};
 
 #[derive(Debug, Default)]
 pub struct SurfaceLayout {
@@ -195,7 +202,7 @@ fn render_agent_log(app: &App, frame: &mut Frame) {
 
                 if let Some(comman_line_rect) = agent_layout.text_box {
                     let (color, shortcut) = match app.input_mode {
-                        InputMode::Normal => (Color::Red, "[C]"),
+                        InputMode::Normal | InputMode::Console => (Color::Red, "[C]"),
                         InputMode::Editing => (Color::Green, "[ESC]"),
                     };
                     let block = Block::default()
@@ -326,7 +333,7 @@ fn render_surface_overlay(app: &App, frame: &mut Frame) {
     let buf = frame.buffer_mut();
     match &app.surface.focus {
         Some(Focus::Agent(port)) => {
-            if *port == 3333 {
+            if *port == crate::tcp::port_base() {
                 return;
             }
             let grid_pos = app.surface.agent_position(port);
@@ -357,15 +364,6 @@ fn render_surface_overlay(app: &App, frame: &mut Frame) {
         //        },
         _ => (),
     }
-    if let Some(stats) = &app.surface.victory_stats
-        && stats.show_victory
-    {
-        Paragraph::new(stats.stats.to_string())
-            .centered()
-            .block(Block::bordered().title("!!! VICTORY !!!"))
-            .style(DEFAULT_STYLE)
-            .render(app.layout.surface.victory_popup, buf);
-    }
 }
 
 fn highlight(
@@ -453,11 +451,7 @@ fn render_power(app: &App, frame: &mut Frame) {
 }
 
 fn render_tutorial(app: &App, frame: &mut Frame) {
-    // better way to clear?
-    let buf = &mut frame.buffer_mut();
-    for pos in app.layout.surface.tutorial.area.positions() {
-        buf[(pos.x, pos.y)].reset();
-    }
+    render_widget_clamped(frame, Clear, app.layout.surface.tutorial.area);
 
     let tutorial_state = &app.surface.game_state.tutorial_state;
 
@@ -483,6 +477,156 @@ fn render_tutorial(app: &App, frame: &mut Frame) {
     );
 }
 
+fn render_goals(app: &App, progress: &Progress, frame: &mut Frame) {
+    let surface = &app.surface;
+    let mut lines: Vec<Line> = vec![];
+    for (goal, met) in progress.scenario.goals.iter().zip(progress.met.iter()) {
+        let line = match met {
+            Some(tick) => Line::styled(
+                format!("[x] {goal}, met at tick {tick}"),
+                Style::default().fg(Color::DarkGray),
+            ),
+            None => {
+                let amount = goal.amount(&surface.agents, &surface.grid, &surface.game_state);
+                Line::from(format!("[ ] {goal} ({}/{})", amount, goal.target()))
+            }
+        };
+        lines.push(line);
+    }
+    if !progress.has_goals() {
+        lines.push(Line::from("[ ] research SELF_SUFFICIENT"));
+    }
+    let status = match progress.failed {
+        Some(tick) => Span::styled(
+            format!("FAILED at tick {tick}"),
+            Style::default().fg(Color::Red),
+        ),
+        None => Span::raw(format!(
+            "{}/{}",
+            progress.met.iter().flatten().count(),
+            progress.met.len()
+        )),
+    };
+    let area = Rect {
+        height: lines.len() as u16 + 2,
+        ..app.layout.surface.tutorial.area
+    };
+    let goals = Paragraph::new(lines)
+        .block(
+            Block::bordered()
+                .title(progress.scenario.name.as_str())
+                .title(Title::from(status).alignment(Alignment::Right)),
+        )
+        .style(DEFAULT_STYLE);
+    render_widget_clamped(frame, Clear, area);
+    render_widget_clamped(frame, goals, area);
+}
+
+fn render_victory(app: &App, stats: &VictoryStats, frame: &mut Frame) {
+    let area = app.layout.surface.victory_popup;
+    let block = Block::bordered()
+        .title("!!! VICTORY !!!")
+        .style(DEFAULT_STYLE);
+    let Some(scores) = &app.scores else {
+        let popup = Paragraph::new(stats.stats.to_string())
+            .centered()
+            .block(block);
+        render_widget_clamped(frame, Clear, area);
+        render_widget_clamped(frame, popup, area);
+        return;
+    };
+    let inner = block.inner(area);
+    render_widget_clamped(frame, Clear, area);
+    render_widget_clamped(frame, block, area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(inner);
+    let text = format!(
+        "{}, {} runs\n\n{}",
+        scores.run.puzzle, scores.runs, stats.stats
+    );
+    let popup = Paragraph::new(text).centered().style(DEFAULT_STYLE);
+    render_widget_clamped(frame, popup, columns[0]);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+        .split(columns[1]);
+    let areas = rows.iter().flat_map(|row| {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+            .split(*row)
+            .to_vec()
+    });
+    for (histogram, area) in scores.histograms.iter().zip(areas) {
+        render_histogram(histogram, frame, area);
+    }
+}
+
+/// the bins of previous runs, with the bin of this run highlighted
+fn render_histogram(histogram: &Histogram, frame: &mut Frame, area: Rect) {
+    let metric = histogram.metric;
+    let block = Block::bordered()
+        .title(format!("{metric}: {}", metric.format(histogram.value)))
+        .title(
+            Title::from(format!("#{} of {}", histogram.rank, histogram.runs))
+                .alignment(Alignment::Right),
+        )
+        .title_bottom(metric.format(histogram.min))
+        .title_bottom(Line::from(metric.format(histogram.max)).right_aligned());
+    let current = histogram.bin(histogram.value);
+    let bars: Vec<Bar> = histogram
+        .bins
+        .iter()
+        .enumerate()
+        .map(|(idx, count)| {
+            let color = if idx == current {
+                Color::Yellow
+            } else {
+                Color::Green
+            };
+            Bar::default()
+                .value(*count)
+                .text_value(String::new())
+                .style(Style::default().fg(color))
+        })
+        .collect();
+    let bar_width = (block.inner(area).width / BINS as u16)
+        .saturating_sub(1)
+        .max(1);
+    let chart = BarChart::default()
+        .block(block)
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .style(DEFAULT_STYLE);
+    render_widget_clamped(frame, chart, area);
+}
+
+fn render_speed(frame: &mut Frame, area: Rect) {
+    let text = if speed::is_paused() {
+        "PAUSED [SPACE]  STEP [.] [>]".to_string()
+    } else if speed::Preset::current() == Some(speed::Preset::Normal) {
+        return;
+    } else {
+        format!("{} [SPACE]", speed::describe())
+    };
+    let width = text.len() as u16 + 2;
+    let area = Rect {
+        x: (area.x + area.width).saturating_sub(width),
+        y: (area.y + area.height).saturating_sub(1),
+        width: width.min(area.width),
+        height: 1,
+    };
+    let speed = Paragraph::new(text)
+        .centered()
+        .style(Style::default().fg(Color::Black).bg(Color::Green));
+    render_widget_clamped(frame, speed, area);
+}
+
 // TODO remove the mut here
 pub fn render(app: &mut App, frame: &mut Frame) {
     render_surface_grid(app, frame);
@@ -491,7 +635,10 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     render_agent_log(app, frame);
     render_agents_list(app, frame);
     render_power(app, frame);
-    if app.surface.game_state.tutorial_state != Tutorial::Complete {
+    render_speed(frame, app.layout.surface.surface);
+    if let Some(progress) = &app.surface.scenario {
+        render_goals(app, progress, frame);
+    } else if app.surface.game_state.tutorial_state != Tutorial::Complete {
         render_tutorial(app, frame)
     }
 
@@ -508,4 +655,12 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     );
     // TODO move elsewhere so we can render without mut (&App)
     render_surface_grid_fx(app, frame);
+    if let Some(stats) = &app.surface.victory_stats
+        && stats.show_victory
+    {
+        render_victory(app, stats, frame);
+    }
+    if matches!(app.input_mode, InputMode::Console) {
+        console::render(app, frame);
+    }
 }
@@ -174,73 +174,65 @@ fn render_edges(tech_tree: &TechTree, app: &App, frame: &mut Frame) {
     }
 }
 
-fn render_edge(edge: &EdgeLayout, color: Color, buf: &mut Buffer) {
-    let y_midpoint = edge.start.y + ((edge.end.y - edge.start.y) / 2);
+/// adds to the line drawn in the cell at `x` `y`, cells outside a tiny terminal are skipped
+fn join(
+    buf: &mut Buffer,
+    (x, y): (u16, u16),
+    color: Option<Color>,
+    add: impl Fn(Directions) -> Directions,
+) {
+    if let Some(cell) = buf.cell_mut((x, y)) {
+        let d: Directions = cell.symbol().into();
+        cell.set_char(add(d).into());
+        if let Some(color) = color {
+            cell.set_fg(color);
+        }
+    }
+}
 
-    let cell = &mut buf[(edge.start.x, edge.start.y)];
-    let d: Directions = cell.symbol().into();
-    cell.set_char(d.add_south().into());
-    // intentionally not coloring start
-    // cell.set_fg(color);
+fn render_edge(edge: &EdgeLayout, color: Color, buf: &mut Buffer) {
+    let y_midpoint = edge.start.y + (edge.end.y.saturating_sub(edge.start.y) / 2);
 
-    let cell = &mut buf[(edge.end.x, edge.end.y)];
-    let d: Directions = cell.symbol().into();
-    cell.set_char(d.add_north().into());
-    // intentionally not coloring end
-    //cell.set_fg(color);
+    // intentionally not coloring start and end
+    join(buf, (edge.start.x, edge.start.y), None, |d| d.add_south());
+    join(buf, (edge.end.x, edge.end.y), None, |d| d.add_north());
 
     for y in (edge.start.y + 1)..y_midpoint {
-        let cell = &mut buf[(edge.start.x, y)];
-        let d: Directions = cell.symbol().into();
-        cell.set_char(d.add_north().add_south().into());
-        cell.set_fg(color);
+        join(buf, (edge.start.x, y), Some(color), |d| {
+            d.add_north().add_south()
+        });
     }
     for y in (y_midpoint + 1)..edge.end.y {
-        let cell = &mut buf[(edge.end.x, y)];
-        let d: Directions = cell.symbol().into();
-        cell.set_char(d.add_north().add_south().into());
-        cell.set_fg(color);
+        join(buf, (edge.end.x, y), Some(color), |d| {
+            d.add_north().add_south()
+        });
     }
 
     // TODO use cmp() and match?
+    let color = Some(color);
     if edge.start.x == edge.end.x {
-        let cell = &mut buf[(edge.start.x, y_midpoint)];
-        let d: Directions = cell.symbol().into();
-        cell.set_char(d.add_north().add_south().into());
-        cell.set_fg(color);
+        join(buf, (edge.start.x, y_midpoint), color, |d| {
+            d.add_north().add_south()
+        });
     } else if edge.start.x < edge.end.x {
-        let cell = &mut buf[(edge.start.x, y_midpoint)];
-        let d: Directions = cell.symbol().into();
-        cell.set_char(d.add_north().add_east().into());
-        cell.set_fg(color);
-
-        let cell = &mut buf[(edge.end.x, y_midpoint)];
-        let d: Directions = cell.symbol().into();
-        cell.set_char(d.add_south().add_west().into());
-        cell.set_fg(color);
-
+        join(buf, (edge.start.x, y_midpoint), color, |d| {
+            d.add_north().add_east()
+        });
+        join(buf, (edge.end.x, y_midpoint), color, |d| {
+            d.add_south().add_west()
+        });
         for x in (edge.start.x + 1)..edge.end.x {
-            let cell = &mut buf[(x, y_midpoint)];
-            let d: Directions = cell.symbol().into();
-            cell.set_char(d.add_east().add_west().into());
-            cell.set_fg(color);
+            join(buf, (x, y_midpoint), color, |d| d.add_east().add_west());
         }
     } else if edge.end.x < edge.start.x {
-        let cell = &mut buf[(edge.start.x, y_midpoint)];
-        let d: Directions = cell.symbol().into();
-        cell.set_char(d.add_north().add_west().into());
-        cell.set_fg(color);
-
-        let cell = &mut buf[(edge.end.x, y_midpoint)];
-        let d: Directions = cell.symbol().into();
-        cell.set_char(d.add_south().add_east().into());
-        cell.set_fg(color);
-
+        join(buf, (edge.start.x, y_midpoint), color, |d| {
+            d.add_north().add_west()
+        });
+        join(buf, (edge.end.x, y_midpoint), color, |d| {
+            d.add_south().add_east()
+        });
         for x in (edge.end.x + 1)..edge.start.x {
-            let cell = &mut buf[(x, y_midpoint)];
-            let d: Directions = cell.symbol().into();
-            cell.set_char(d.add_east().add_west().into());
-            cell.set_fg(color);
+            join(buf, (x, y_midpoint), color, |d| d.add_east().add_west());
         }
     }
 }
@@ -138,14 +138,14 @@ impl BorderAttachedButton {
     pub fn resize(&self, width: u16, height: u16) -> Rect {
         match self.attached_direction {
             Location::SouthEast => Rect {
-                x: width - (self.n_chars + 2),
-                y: height - 3,
+                x: width.saturating_sub(self.n_chars + 2),
+                y: height.saturating_sub(3),
                 width: self.n_chars + 2,
                 height: 3,
             },
             Location::East(i) => Rect {
-                x: width - (self.n_chars + 2),
-                y: ((height as i16) - i) as u16,
+                x: width.saturating_sub(self.n_chars + 2),
+                y: ((height as i16) - i).max(0) as u16,
                 width: self.n_chars + 2,
                 height: 3,
             },
@@ -176,26 +176,29 @@ impl BorderAttachedButton {
 impl WidgetRef for BorderAttachedButton {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
         self.button.render_ref(area, buf);
+        // the area is clamped to the terminal, which can leave no room for the joins
+        if area.width < 3 || area.height < 3 {
+            return;
+        }
+        let mut set_char = |x: u16, y: u16, c: char| {
+            if let Some(cell) = buf.cell_mut((x, y)) {
+                cell.set_char(c);
+            }
+        };
         match self.attached_direction {
             Location::East(_) => {
-                let cell = &mut buf[(area.right() - 1, area.top())];
-                cell.set_char('┪');
-                let cell = &mut buf[(area.right() - 1, area.bottom() - 1)];
-                cell.set_char('┩');
+                set_char(area.right() - 1, area.top(), '┪');
+                set_char(area.right() - 1, area.bottom() - 1, '┩');
             }
             Location::SouthEast => {
-                let cell = &mut buf[(area.right() - 1, area.top())];
-                cell.set_char('┪');
-                let cell = &mut buf[(area.left(), area.bottom() - 1)];
-                cell.set_char('┺');
+                set_char(area.right() - 1, area.top(), '┪');
+                set_char(area.left(), area.bottom() - 1, '┺');
             }
         }
         // Not sure why adding whitespace to button.content does not do this for us
         // possibly a ratatui optimization leading to a "bug" or unexpected behavior?
         // use ratatui Clear widget instead?
-        let cell = &mut buf[(area.left() + 1, area.bottom() - 2)];
-        cell.set_char(' ');
-        let cell = &mut buf[(area.right() - 2, area.top() + 1)];
-        cell.set_char(' ');
+        set_char(area.left() + 1, area.bottom() - 2, ' ');
+        set_char(area.right() - 2, area.top() + 1, ' ');
     }
 }
@@ -317,6 +317,13 @@ impl<I, T: Display + Debug> TextList<I, T> {
         }
     }
 
+    /// replaces the text of a line, keeping its current style
+    pub fn set_line(&mut self, idx: usize, line: AlignedLine) {
+        if let Some(old) = self.lines.get_mut(idx) {
+            *old = line.style(old.style);
+        }
+    }
+
     pub fn selected(&self) -> Option<&T> {
         if let Some(selected) = self.selected {
             Some(&self.items[selected])
@@ -325,6 +332,10 @@ impl<I, T: Display + Debug> TextList<I, T> {
         }
     }
 
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
     pub fn selected_unchecked(&self) -> &T {
         &self.items[self.selected.unwrap()]
     }
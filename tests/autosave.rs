@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use texaform::autosave::{self, Autosaver, SLOTS};
+use texaform::surface::generation;
+use texaform::surface::state::{Seed, SurfaceState};
+
+#[test]
+fn test_autosave_slots_and_emergency_save() {
+    let data_dir = std::env::temp_dir().join(format!("texaform-autosave-{}", std::process::id()));
+    std::fs::create_dir_all(&data_dir).unwrap();
+    texaform::logging::set_data_dir(data_dir.clone());
+
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let mut surface = generation::new_game(event_sender, Seed::Manual(7)).unwrap();
+    let mut autosaver = Autosaver::default();
+    autosaver.autosave_interval = Duration::ZERO;
+    autosaver.emergency_interval = Duration::ZERO;
+    assert_eq!(autosave::write_emergency_save().unwrap(), None);
+
+    let mut written = vec![];
+    while written.len() < SLOTS + 1 {
+        surface.tick();
+        // the first call only starts the clocks
+        if let Some(path) = autosaver.tick(&surface) {
+            written.push(path);
+            while autosaver.is_writing() {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            // modification times need to differ for the oldest slot to be found
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        // a paused game is not snapshotted again
+        assert_eq!(autosaver.tick(&surface), None);
+    }
+    // every slot is used once before the first is overwritten
+    assert_eq!(written[SLOTS], written[0]);
+    let state = SurfaceState::load(&written[0]).unwrap();
+    assert_eq!(
+        state.game_state.stats.tick_count,
+        surface.game_state.stats.tick_count
+    );
+    assert_eq!(autosave::newest_save(), Some(written[0].clone()));
+
+    let emergency = autosave::write_emergency_save().unwrap().unwrap();
+    assert!(SurfaceState::load(&emergency).is_ok());
+    assert_eq!(autosave::newest_save(), Some(emergency));
+
+    std::fs::remove_dir_all(&data_dir).unwrap();
+}
@@ -0,0 +1,208 @@
+use ratatui::layout::Position;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use texaform::entities::Entity;
+use texaform::event::{Event, EventHandler};
+use texaform::results::Run;
+use texaform::surface::Surface;
+use texaform::surface::journal::{self, Journal};
+use texaform::surface::scenario::Scenario;
+use texaform::surface::state::Seed;
+use texaform::surface::{cheat, generation};
+use texaform::tcp::port_base;
+
+mod common;
+
+// the scenario's grid starts at 125 125
+const WORKSHOP: &str = r#"{
+    "name": "WORKSHOP",
+    "map": {"grid": ["....."]},
+    "agents": [
+        {"kind": "DOG", "at": [0, 0]},
+        {"kind": "FABRICATOR", "at": [2, 0]}
+    ],
+    "goals": [{"build": {"entity": "DOG", "count": 1}}]
+}"#;
+
+fn new_game() -> Surface {
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    Scenario::parse(WORKSHOP)
+        .unwrap()
+        .new_game(event_sender)
+        .expect("scenario fits")
+}
+
+#[test]
+fn test_cheats() {
+    let mut surface = new_game();
+    let dog = port_base() + 1;
+    let fabricator = port_base() + 2;
+    let iron = Position { x: 120, y: 120 };
+
+    assert_eq!(surface.cheat("SPAWN IRON 120 120").text, "OKAY");
+    assert_eq!(
+        surface.grid.get(&iron).map(|g| g.entity()),
+        Some(Entity::Iron)
+    );
+    assert_eq!(
+        surface.cheat("SPAWN IRON 120 120").text,
+        "ERRR location not buildable"
+    );
+    assert_eq!(surface.cheat("SPAWN DOG 121 120").text, "OKAY");
+    assert_eq!(surface.agents.len(), 4);
+    // the scenario's world is the default 250 cells wide
+    assert_eq!(
+        surface.cheat("SPAWN IRON 1000 1").text,
+        "ERRR out of bounds"
+    );
+
+    assert_eq!(surface.cheat("GIVE 3335 IRON_PLATE 3").text, "OKAY");
+    let agent = surface.get_agent(&fabricator).unwrap();
+    assert_eq!(agent.containers()[0].count(Entity::IronPlate), 3);
+    assert_eq!(
+        surface.cheat("GIVE 3335 NUT 1000").text,
+        "ERRR already full"
+    );
+    assert_eq!(
+        surface.cheat("GIVE 3333 NUT").text,
+        "ERRR unknown agent 3333"
+    );
+
+    assert!(
+        !surface
+            .game_state
+            .unlocked_entities
+            .contains(&Entity::Motor)
+    );
+    assert_eq!(surface.cheat("UNLOCK ALL").text, "OKAY");
+    assert!(
+        surface
+            .game_state
+            .unlocked_entities
+            .contains(&Entity::Motor)
+    );
+
+    assert_eq!(surface.cheat("TP 3334 100 100").text, "OKAY");
+    assert_eq!(
+        surface.agent_position(&dog),
+        Some(Position { x: 100, y: 100 })
+    );
+    assert!(
+        surface
+            .grid
+            .get(&Position { x: 125, y: 125 })
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        surface.cheat("TP 3334 120 120").text,
+        "ERRR location not buildable"
+    );
+    assert_eq!(
+        surface.agent_position(&dog),
+        Some(Position { x: 100, y: 100 })
+    );
+    assert_eq!(
+        surface.send(dog, "STAT").text.split(' ').nth(1),
+        Some("100")
+    );
+
+    assert_eq!(surface.cheat("SETPOWER STORED 50000").text, "OKAY");
+    assert_eq!(surface.power.stored, 50_000);
+    assert!(surface.power.capacity >= 50_000);
+
+    let tick = surface.game_state.stats.tick_count;
+    assert_eq!(surface.cheat("TICK 10").text, "OKAY");
+    assert_eq!(surface.game_state.stats.tick_count, tick + 10);
+
+    assert!(surface.game_state.stats.cheated);
+    assert!(surface.victory_stats.is_some());
+    assert_eq!(Run::new(&surface), None);
+
+    // a victory reached by the first cheat is flagged
+    let mut surface = new_game();
+    assert!(surface.cheat("FLY").text.starts_with("ERRR"));
+    assert!(!surface.game_state.stats.cheated);
+    assert_eq!(surface.cheat("TICK 2").text, "OKAY");
+    assert!(surface.victory_stats.as_ref().unwrap().stats.cheated);
+}
+
+#[test]
+fn test_cheats_replayed() {
+    let mut surface =
+        generation::new_game(tokio::sync::mpsc::unbounded_channel().0, Seed::Manual(1)).unwrap();
+    let dog = port_base() + 2;
+    surface.tick();
+    assert_eq!(surface.cheat("TP 3335 100 100").text, "OKAY");
+    assert_eq!(surface.cheat("TICK 5").text, "OKAY");
+    surface.send(dog, "MOVE");
+    assert!(surface.cheat("JUMP").text.starts_with("ERRR"));
+
+    let journal = surface.journal.encode(&surface).unwrap();
+    let path = std::env::temp_dir().join(format!("texaform-cheat-{}.journal", std::process::id()));
+    std::fs::write(&path, journal).unwrap();
+    let file = Journal::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(file.entries.iter().filter(|entry| entry.cheat).count(), 3);
+
+    // as if recorded on another port base, the ports in the cheats move with the agents
+    let mut file = file;
+    file.port_base += 1000;
+    for entry in file.entries.iter_mut() {
+        entry.port += 1000;
+        entry.command = entry.command.replace("3335", "4335");
+    }
+    assert!(
+        file.entries
+            .iter()
+            .any(|entry| entry.command == "TP 4335 100 100")
+    );
+
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let (mut replayed, mismatches) = journal::replay(&file, event_sender).unwrap();
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+    assert!(replayed.game_state.stats.cheated);
+    assert_eq!(
+        replayed.game_state.stats.tick_count,
+        surface.game_state.stats.tick_count
+    );
+    assert_eq!(
+        replayed.send(dog, "STAT").text,
+        surface.send(dog, "STAT").text
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_cheat_port() {
+    let cheat_port = common::port_base(module_path!());
+    let mut events = EventHandler::headless();
+    let mut surface = new_game();
+    cheat::listen(cheat_port, events.sender.clone())
+        .await
+        .unwrap();
+    let mut client = tokio::spawn(async move {
+        let mut socket = TcpStream::connect(format!("127.0.0.1:{cheat_port}"))
+            .await
+            .unwrap();
+        socket.write_all(b"TICK 5;FLY;").await.unwrap();
+        let mut replies = String::new();
+        let mut buf = [0; 256];
+        while replies.matches(';').count() < 2 {
+            let n = socket.read(&mut buf).await.unwrap();
+            replies.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+        }
+        replies
+    });
+    let replies = loop {
+        let event = tokio::select! {
+            replies = &mut client => break replies.unwrap(),
+            event = events.next() => event.unwrap(),
+        };
+        if let Event::Cheat(command, reply) = event {
+            let _ = reply.send(surface.cheat(&command));
+        }
+    };
+    assert_eq!(replies, "OKAY;ERRR unknown cheat FLY;");
+    assert_eq!(surface.game_state.stats.tick_count, 5);
+}
@@ -0,0 +1,70 @@
+use texaform::agents::dog::Direction;
+use texaform::client::{ClientError, DogClient, HudClient, blocking};
+use texaform::entities::Entity;
+use texaform::event::{Event, EventHandler};
+use texaform::surface::generation;
+use texaform::surface::state::Seed;
+use texaform::tcp;
+
+mod common;
+
+/// runs a headless game until the clients finish
+async fn serve<F>(clients: F) -> F::Output
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tcp::set_port_base(common::port_base(module_path!()));
+    let mut events = EventHandler::headless();
+    let mut surface = generation::new_game(events.sender.clone(), Seed::Manual(1))
+        .expect("starting entities fit");
+    surface.listen();
+    let mut clients = tokio::spawn(clients);
+    loop {
+        let event = tokio::select! {
+            result = &mut clients => return result.expect("clients finish"),
+            event = events.next() => event.expect("event handler is running"),
+        };
+        match event {
+            Event::Tick => {
+                surface.resolve_queued_commands();
+                surface.tick();
+            }
+            Event::AgentCommand(port, command, reply) => {
+                surface.receive_agent_command(port, command, reply);
+            }
+            _ => (),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_clients() {
+    serve(async {
+        let port_base = tcp::port_base();
+        let mut hud = HudClient::connect(port_base).await.unwrap();
+        let agents = hud.agents().await.unwrap();
+        let kinds: Vec<&str> = agents.iter().map(|a| a.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["HUD", "FABRICATOR", "DOG", "DOG"]);
+
+        let mut dog = DogClient::connect(port_base + 2).await.unwrap();
+        let before = dog.stat().await.unwrap();
+        dog.turn(Direction::L).await.unwrap();
+        dog.turn(Direction::L).await.unwrap();
+        let after = dog.stat().await.unwrap();
+        assert_eq!(before.position, after.position);
+        assert_ne!(before.facing.to_string(), after.facing.to_string());
+
+        let error = dog.pick(Entity::Iron).await.unwrap_err();
+        assert!(matches!(error, ClientError::Agent(_)));
+
+        let stat = tokio::task::spawn_blocking(move || {
+            blocking::DogClient::connect(port_base + 3)?.stat()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(stat.battery, 100);
+    })
+    .await;
+}
@@ -0,0 +1,15 @@
+// helpers shared by the integration tests
+
+/// test files listening on ports, each gets its own range
+const FILES: [&str; 6] = ["client", "cheat", "harness", "mux", "tcp", "subscriptions"];
+
+/// first of the ports the tests in `file` may listen on, away from the defaults so a running game
+/// does not interfere and from the other test files since they run at the same time
+///   tcp::set_port_base(common::port_base(module_path!()));
+pub fn port_base(file: &str) -> usize {
+    let idx = FILES
+        .iter()
+        .position(|f| *f == file)
+        .unwrap_or_else(|| panic!("add {file} to FILES in tests/common/mod.rs"));
+    45_333 + 1000 * idx
+}
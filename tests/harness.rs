@@ -0,0 +1,95 @@
+use texaform::agents::dog::Direction;
+use texaform::client::DogClient;
+use texaform::entities::Entity;
+use texaform::harness::Harness;
+use texaform::tcp;
+use texaform::tech_tree::{TechKind, TechStatus};
+
+mod common;
+
+// the scenario's grid starts at 125 125
+const FOUNDRY: &str = r#"{
+    "name": "FOUNDRY",
+    "map": {"grid": ["I....", ".....", "....."]},
+    "agents": [
+        {"kind": "DOG", "at": [1, 0]},
+        {"kind": "FABRICATOR", "at": [0, 2], "inventory": {"BUFFER_IN": ["IRON", "IRON"]}},
+        {"kind": "SMELTER", "at": [3, 0], "inventory": {"BUFFER_IN": ["IRON"]}}
+    ]
+}"#;
+
+#[test]
+fn test_script() {
+    let mut harness = Harness::scenario(FOUNDRY).unwrap();
+    let fabricator = harness.port_base() + 2;
+    assert_eq!(harness.tech(TechKind::Smelter), Some(TechStatus::Unlocked));
+    let replies = harness
+        .run(
+            "
+            # research the smelter with the fabricator's iron
+            +0 RESR SMELTER => OKAY
+            +2 RESR => OKAY
+            tick
+            +2 RESR => OKAY
+            +2 RESR => ERRR no active research
+            ",
+        )
+        .unwrap();
+    assert_eq!(replies.len(), 4);
+    assert_eq!(
+        harness.tech(TechKind::Smelter),
+        Some(TechStatus::Researched)
+    );
+    assert_eq!(harness.buffer(fabricator, "BUFFER_IN"), Some(vec![]));
+    assert_eq!(harness.buffer(fabricator, "HOPPER"), None);
+    // researching kept the fabricator on the grid
+    assert_eq!(harness.cell(125, 127), Some(Entity::Fabricator));
+    assert_eq!(harness.surface.game_state.stats.tick_count, 1);
+
+    let error = harness.run("+1 TURN L\n+1 JUMP => OKAY").unwrap_err();
+    assert!(error.to_string().starts_with("line 2: `JUMP`"), "{error}");
+    assert!(harness.run("tick ten").is_err());
+}
+
+#[test]
+fn test_world_state() {
+    let mut harness = Harness::scenario(FOUNDRY).unwrap();
+    let smelter = harness.port_base() + 3;
+    assert_eq!(harness.cell(125, 125), Some(Entity::Iron));
+    assert_eq!(harness.cell(126, 125), Some(Entity::Dog));
+    assert_eq!(harness.cell(127, 126), Some(Entity::Empty));
+    assert_eq!(harness.cell(1000, 1000), None);
+
+    // a hand authored map has no solar panels
+    harness.surface.power.capacity = 100_000;
+    harness.surface.power.stored = 100_000;
+    harness.run("+3 POWR => OKAY\ntick 150").unwrap();
+    assert_eq!(
+        harness.buffer(smelter, "BUFFER_OUT"),
+        Some(vec![Entity::IronPlate])
+    );
+    assert!(harness.power().stored < 100_000);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_bot() {
+    tcp::set_port_base(common::port_base(module_path!()));
+    let mut harness = Harness::seed(1).unwrap();
+    let dog = harness.port_base() + 2;
+    let before = harness.surface.agent_position(&dog).unwrap();
+    let stat = harness
+        .serve(async move {
+            let mut bot = DogClient::connect(dog).await.unwrap();
+            bot.turn(Direction::L).await.unwrap();
+            bot.turn(Direction::L).await.unwrap();
+            bot.forward().await.unwrap();
+            bot.stat().await.unwrap()
+        })
+        .await;
+    let after = harness.surface.agent_position(&dog).unwrap();
+    assert_ne!(before, after);
+    assert_eq!((stat.position.x, stat.position.y), (after.x, after.y));
+    assert_eq!(harness.cell(after.x, after.y), Some(Entity::Dog));
+    assert_eq!(harness.cell(before.x, before.y), Some(Entity::Empty));
+    assert!(harness.surface.game_state.stats.tick_count > 0);
+}
@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use texaform::harness::Harness;
+use texaform::{mux, tcp};
+
+mod common;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mux() {
+    let port_base = common::port_base(module_path!());
+    tcp::set_port_base(port_base);
+    // after the agents' ports
+    let mux_port = port_base + 500;
+    let mut harness = Harness::seed(1).unwrap();
+    mux::listen(mux_port, harness.surface.event_sender.clone())
+        .await
+        .unwrap();
+    let (dog, other) = (harness.port_base() + 2, harness.port_base() + 3);
+    let replies = harness
+        .serve(async move {
+            let mut socket = TcpStream::connect(format!("127.0.0.1:{mux_port}"))
+                .await
+                .unwrap();
+            let commands = format!(
+                "{dog} TURN L;{other} STAT; {dog} STAT;{dog} SUBS SMELT_DONE;MOVE;PROT JSON;"
+            );
+            socket.write_all(commands.as_bytes()).await.unwrap();
+            let mut replies = String::new();
+            let mut buf = [0; 256];
+            while replies.matches(';').count() < 6 {
+                let n = socket.read(&mut buf).await.unwrap();
+                replies.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+            }
+
+            socket
+                .write_all(format!("{{\"port\": {other}, \"command\": \"TURN R\"}}\n").as_bytes())
+                .await
+                .unwrap();
+            let mut json = String::new();
+            while !json.ends_with('\n') {
+                let n = socket.read(&mut buf).await.unwrap();
+                json.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+            }
+            (replies, json)
+        })
+        .await;
+
+    // replies for different agents may arrive in any order, each agent's are in order
+    let (replies, json) = replies;
+    let mut by_port: BTreeMap<Option<usize>, Vec<String>> = BTreeMap::new();
+    for reply in replies.split_terminator(';') {
+        match reply.split_once(' ') {
+            Some((port, reply)) if port.parse::<usize>().is_ok() => by_port
+                .entry(port.parse().ok())
+                .or_default()
+                .push(reply.to_string()),
+            _ => by_port.entry(None).or_default().push(reply.to_string()),
+        }
+    }
+    let dog_replies = &by_port[&Some(dog)];
+    assert_eq!(dog_replies.len(), 3);
+    assert_eq!(dog_replies[0], "OKAY");
+    assert!(dog_replies[1].ends_with(" N 100% _"), "{dog_replies:?}");
+    assert!(dog_replies[2].starts_with("ERRR"), "{dog_replies:?}");
+    let other_replies = &by_port[&Some(other)];
+    assert_eq!(other_replies.len(), 1);
+    assert!(other_replies[0].ends_with(" E 100% _"), "{other_replies:?}");
+    // without a port only the handshake is answered
+    let unported = &by_port[&None];
+    assert_eq!(unported.len(), 2);
+    assert!(unported[0].starts_with("ERRR"), "{unported:?}");
+    assert_eq!(unported[1], "OKAY");
+
+    assert_eq!(json, format!("{{\"port\":{other},\"reply\":\"OKAY\"}}\n"));
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use texaform::app::App;
+use texaform::input;
+use texaform::surface::journal::Journal;
+use texaform::surface::state::{Seed, SurfaceState};
+use texaform::surface::{generation, schema};
+use texaform::ui::Screen;
+use texaform::ui::load_game::SaveFileAction;
+use texaform::widgets::text_box::TextBox;
+
+#[test]
+fn test_rename_duplicate_delete() {
+    let dir = std::env::temp_dir().join(format!("texaform-save-files-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let mut surface = generation::new_game(event_sender, Seed::Manual(4)).unwrap();
+    surface.send(3335, "MOVE");
+    let save = dir.join("A.texaform");
+    SurfaceState::save_to_path(&surface, &save).unwrap();
+
+    // the journal moves with its save
+    let renamed = dir.join("B.texaform");
+    SurfaceState::rename(&save, &renamed).unwrap();
+    assert!(!save.exists() && !Journal::path(&save).exists());
+    assert_eq!(
+        SurfaceState::load(&renamed).unwrap().journal.entries.len(),
+        1
+    );
+
+    let copy = dir.join("C.texaform");
+    SurfaceState::duplicate(&renamed, &copy).unwrap();
+    assert_eq!(SurfaceState::load(&copy).unwrap().journal.entries.len(), 1);
+    // neither replaces an existing save
+    assert!(SurfaceState::duplicate(&renamed, &copy).is_err());
+    assert!(SurfaceState::rename(&renamed, &copy).is_err());
+    // or a journal left without its save, and nothing is moved when one is refused
+    let stray = dir.join("D.texaform");
+    std::fs::write(Journal::path(&stray), "").unwrap();
+    assert!(SurfaceState::rename(&renamed, &stray).is_err());
+    assert!(SurfaceState::duplicate(&renamed, &stray).is_err());
+    assert!(!stray.exists());
+    assert!(renamed.exists() && Journal::path(&renamed).exists());
+    assert_eq!(std::fs::read(Journal::path(&stray)).unwrap(), b"");
+
+    SurfaceState::delete(&copy).unwrap();
+    assert!(!copy.exists() && !Journal::path(&copy).exists());
+    assert!(renamed.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+fn names(app: &App) -> Vec<String> {
+    app.save_files
+        .items()
+        .iter()
+        .map(|path| path.to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_sort_keys_and_rename_checks() {
+    let dir = std::env::temp_dir().join(format!("texaform-sort-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    texaform::logging::set_data_dir(dir.clone());
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let surface = generation::new_game(event_sender.clone(), Seed::Manual(4)).unwrap();
+    let save = dir.join("SAVE.texaform");
+    SurfaceState::save_to_path(&surface, &save).unwrap();
+    let export: serde_json::Value =
+        serde_json::from_slice(&schema::export(&std::fs::read(&save).unwrap()).unwrap()).unwrap();
+    std::fs::remove_file(&save).unwrap();
+    std::fs::remove_file(Journal::path(&save)).unwrap();
+    // written in this order, with these tick counts and minor versions
+    for (name, ticks, minor) in [("A", 30, 1), ("B", 10, 2), ("C", 20, 0)] {
+        let mut document = export.clone();
+        let stats = &mut document["surface"]["game_state"]["stats"];
+        stats["tick_count"] = ticks.into();
+        stats["version"]["minor"] = minor.into();
+        let path = SurfaceState::path(name);
+        std::fs::write(&path, serde_json::to_vec(&document).unwrap()).unwrap();
+        // modification times need to differ for the date sort
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    let mut app = App::new(event_sender, 120, 36);
+    app.set_screen(Screen::LoadGame);
+    assert_eq!(names(&app), ["A", "B", "C"]);
+    let sort = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE);
+    input::handle_key_events(sort, &mut app).await.unwrap();
+    assert_eq!(names(&app), ["C", "B", "A"]);
+    input::handle_key_events(sort, &mut app).await.unwrap();
+    assert_eq!(names(&app), ["A", "C", "B"]);
+    input::handle_key_events(sort, &mut app).await.unwrap();
+    assert_eq!(names(&app), ["B", "A", "C"]);
+    // only the selected save is loaded, the others only had their stats read
+    assert_eq!(app.save_file_cache.len(), 1);
+
+    let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+    for name in ["", "../A", "sub/A"] {
+        let mut text_box = TextBox::new();
+        text_box.set_content(name.to_string());
+        app.save_manager.action = Some(SaveFileAction::Rename(SurfaceState::path("A"), text_box));
+        input::handle_key_events(enter, &mut app).await.unwrap();
+        assert!(
+            matches!(app.save_manager.action, Some(SaveFileAction::Rename(..))),
+            "{name:?} was accepted"
+        );
+    }
+    assert!(SurfaceState::path("A").exists());
+    assert_eq!(names(&app).len(), 3);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
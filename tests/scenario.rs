@@ -0,0 +1,189 @@
+use texaform::entities::Entity;
+use texaform::results::{self, Metric, Puzzle, Run, Scores};
+use texaform::surface::Surface;
+use texaform::surface::journal::{self, Journal};
+use texaform::surface::scenario::Scenario;
+use texaform::surface::state::SurfaceState;
+use texaform::tcp::port_base;
+
+const GEARS: &str = r#"{
+    "name": "TEST",
+    "map": {"grid": ["I.I", "...", "U.L"]},
+    "agents": [
+        {"kind": "DOG", "at": [0, 4]},
+        {"kind": "FABRICATOR", "at": [4, 0], "inventory": {"BUFFER_IN": ["NUT", "NUT"]}, "focus": true}
+    ],
+    "unlocked": ["NUT"],
+    "tech": [{"tech": "SMELTER", "researched": true}],
+    "goals": [
+        {"hold": {"entity": "GEAR", "count": 2, "container": "BUFFER_OUT"}, "by_tick": 10},
+        {"research": {"tech": "SMELTER"}}
+    ]
+}"#;
+
+fn new_game(scenario: &Scenario) -> Surface {
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    scenario.new_game(event_sender).expect("scenario fits")
+}
+
+#[test]
+fn test_bundled_scenarios_start() {
+    for scenario in Scenario::bundled() {
+        let surface = new_game(&scenario);
+        // the HUD is not listed in a scenario
+        assert_eq!(surface.agents.len(), scenario.agents.len() + 1);
+        assert!(surface.focused_agent().is_some(), "{}", scenario.name);
+        assert!(surface.scenario.is_some());
+    }
+}
+
+#[test]
+fn test_scenario_start_state() {
+    let scenario = Scenario::parse(GEARS).unwrap();
+    let surface = new_game(&scenario);
+    let fabricator = port_base() + 2;
+    assert_eq!(surface.focused_agent_port(), Some(fabricator));
+    let agent = surface.get_agent(&fabricator).unwrap();
+    assert_eq!(
+        agent.containers()[0].content,
+        vec![Entity::Nut, Entity::Nut]
+    );
+    let state = &surface.game_state;
+    assert!(state.unlocked_entities.contains(&Entity::Nut));
+    assert!(state.unlocked_entities.contains(&Entity::Smelter));
+    assert!(!state.unlocked_entities.contains(&Entity::Dog));
+    assert_eq!(state.stats.research_complete, 1);
+
+    let error = |from: &str, to: &str| {
+        let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+        Scenario::parse(&GEARS.replace(from, to))
+            .unwrap()
+            .new_game(event_sender)
+            .unwrap_err()
+            .to_string()
+    };
+    assert!(error("BUFFER_IN", "HOPPER").contains("no HOPPER"));
+    assert!(error("[0, 4]", "[0, 400]").contains("off the surface"));
+    assert!(error("I.I", "I?I").contains("unknown entity `?`"));
+    assert!(error("[0, 4]", "[4, 1]").contains("Occupied"));
+    assert!(error("\"unlocked\"", "\"world_size\": 10, \"unlocked\"").contains("world_size 10"));
+
+    // positions are offset from the center of larger worlds too
+    let scenario =
+        Scenario::parse(&GEARS.replace("\"unlocked\"", "\"world_size\": 1000, \"unlocked\""))
+            .unwrap();
+    let surface = new_game(&scenario);
+    assert_eq!(surface.grid.size(), 1000);
+    assert_eq!(
+        surface
+            .agent_position(&(port_base() + 2))
+            .map(|pos| (pos.x, pos.y)),
+        Some((504, 500))
+    );
+}
+
+#[test]
+fn test_scenario_won() {
+    let scenario = Scenario::parse(GEARS).unwrap();
+    let mut surface = new_game(&scenario);
+    let fabricator = port_base() + 2;
+    surface.tick();
+    assert!(surface.victory_stats.is_none());
+    let progress = surface.scenario.as_ref().unwrap();
+    assert_eq!(progress.met, vec![None, Some(0)]);
+
+    let agent = surface.get_mut_agent(&fabricator).unwrap();
+    for _ in 0..2 {
+        agent.containers_mut()[1].place(Entity::Gear);
+    }
+    surface.tick();
+    let progress = surface.scenario.as_ref().unwrap();
+    assert_eq!(progress.met, vec![Some(1), Some(0)]);
+    assert!(progress.won());
+    surface.tick();
+    assert!(surface.victory_stats.is_some());
+}
+
+#[test]
+fn test_scenario_failed() {
+    let scenario = Scenario::parse(GEARS).unwrap();
+    let mut surface = new_game(&scenario);
+    for _ in 0..12 {
+        surface.tick();
+    }
+    let progress = surface.scenario.as_ref().unwrap();
+    assert_eq!(progress.failed, Some(11));
+    assert!(!progress.won());
+    assert!(surface.victory_stats.is_none());
+}
+
+#[test]
+fn test_scenario_saved_and_replayed() {
+    let scenario = Scenario::parse(GEARS).unwrap();
+    let mut surface = new_game(&scenario);
+    let dog = port_base() + 1;
+    surface.tick();
+    surface.send(dog, "MOVE");
+    surface.tick();
+
+    let path =
+        std::env::temp_dir().join(format!("texaform-scenario-{}.texaform", std::process::id()));
+    SurfaceState::save_to_path(&surface, &path).unwrap();
+    let state = SurfaceState::load(&path).unwrap();
+    let file = Journal::load(&path.with_extension("journal")).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(path.with_extension("journal")).unwrap();
+    let saved = state.scenario.unwrap();
+    assert_eq!(saved.scenario, scenario);
+    assert_eq!(saved.met, vec![None, Some(0)]);
+    assert_eq!(file.scenario, Some(scenario));
+
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let (mut replayed, mismatches) = journal::replay(&file, event_sender).unwrap();
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+    assert_eq!(
+        replayed.send(dog, "STAT").text,
+        surface.send(dog, "STAT").text
+    );
+}
+
+#[test]
+fn test_scenario_results() {
+    let scenario = Scenario::parse(
+        r#"{
+            "name": "HOT",
+            "map": {"seed": 1},
+            "agents": [{"kind": "SMELTER", "at": [0, 0]}],
+            "goals": [{"build": {"entity": "SMELTER", "count": 1}, "by_tick": 5}]
+        }"#,
+    )
+    .unwrap();
+    let mut surface = new_game(&scenario);
+    let smelter = port_base() + 1;
+    assert_eq!(surface.send(smelter, "POWR").text, "OKAY");
+    assert!(Run::new(&surface).is_none());
+    for _ in 0..3 {
+        surface.tick();
+    }
+    assert_eq!(surface.game_state.stats.power_consumed, 3_000);
+
+    let run = Run::new(&surface).unwrap();
+    assert_eq!(run.puzzle, Puzzle::Scenario("HOT".to_string()));
+    assert_eq!(
+        (run.ticks, run.commands, run.power, run.agents),
+        (0, 1, 0, 1)
+    );
+
+    let path = std::env::temp_dir().join(format!("texaform-results-{}.jsonl", std::process::id()));
+    Scores::record(&path, run.clone()).unwrap();
+    let mut slower = run.clone();
+    slower.ticks = 10;
+    let scores = Scores::record(&path, slower).unwrap();
+    let runs = results::load(&path, &run.puzzle).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(scores.runs, 2);
+    let ticks = &scores.histograms[0];
+    assert_eq!(ticks.metric, Metric::Ticks);
+    assert_eq!((ticks.rank, ticks.bins[0], ticks.bins[9]), (2, 1, 1));
+}
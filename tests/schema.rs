@@ -0,0 +1,186 @@
+use std::path::{Path, PathBuf};
+
+use ratatui::layout::Position;
+use texaform::entities::Entity;
+use texaform::surface::generation::{self, Generator};
+use texaform::surface::grid::{Gent, Grid};
+use texaform::surface::journal::{self, Journal};
+use texaform::surface::schema::{self, SCHEMA};
+use texaform::surface::state::{Seed, SurfaceState};
+
+// each fixture is a save written by the texaform of its schema, seed 1 after 20 ticks with the
+// dog on 3335 moved once and a connection recorded on 3336. later schemas are schema 1 imported by
+// the texaform of that schema
+fn fixture(schema: u32) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("schema_{schema}.texaform"))
+}
+
+#[test]
+fn test_load_schema_1() {
+    let bytes = std::fs::read(fixture(1)).unwrap();
+    assert_eq!(schema::decode(&bytes).unwrap().0, 1);
+
+    let state = SurfaceState::load(&fixture(1)).unwrap();
+    assert_eq!(state.game_state.stats.tick_count, 20);
+    assert_eq!(state.game_state.stats.seed.value(), 1);
+    assert_eq!(state.agents.len(), 4);
+    assert!(state.agents.values().all(|comms| comms.address.is_none()));
+    assert_eq!(state.journal.entries.len(), 1);
+
+    // the migrated save plays on like a fresh game that took the same steps
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let mut fresh = generation::new_game(event_sender.clone(), Seed::Manual(1)).unwrap();
+    for _ in 0..10 {
+        fresh.tick();
+    }
+    fresh.send(3335, "MOVE");
+    for _ in 0..10 {
+        fresh.tick();
+    }
+    let mut loaded = state.into_surface(event_sender);
+    assert_eq!(
+        loaded.send(3335, "STAT").text,
+        fresh.send(3335, "STAT").text
+    );
+}
+
+#[test]
+fn test_load_schema_2() {
+    let bytes = std::fs::read(fixture(2)).unwrap();
+    assert_eq!(schema::decode(&bytes).unwrap().0, 2);
+
+    let state = SurfaceState::load(&fixture(2)).unwrap();
+    assert_eq!(state.game_state.stats.tick_count, 20);
+    assert_eq!(state.agents.len(), 4);
+    assert!(state.scenario.is_none());
+    assert_eq!(state.journal.entries.len(), 1);
+}
+
+#[test]
+fn test_load_schema_3() {
+    let bytes = std::fs::read(fixture(3)).unwrap();
+    assert_eq!(schema::decode(&bytes).unwrap().0, 3);
+
+    let state = SurfaceState::load(&fixture(3)).unwrap();
+    assert_eq!(state.game_state.stats.tick_count, 20);
+    assert_eq!(state.game_state.stats.power_consumed, 0);
+    assert_eq!(state.journal.entries.len(), 1);
+}
+
+#[test]
+fn test_load_schema_4() {
+    let bytes = std::fs::read(fixture(4)).unwrap();
+    assert_eq!(schema::decode(&bytes).unwrap().0, 4);
+
+    let state = SurfaceState::load(&fixture(4)).unwrap();
+    assert_eq!(state.game_state.stats.tick_count, 20);
+    assert!(!state.game_state.stats.cheated);
+    assert_eq!(state.journal.entries.len(), 1);
+}
+
+#[test]
+fn test_load_schema_5() {
+    let bytes = std::fs::read(fixture(5)).unwrap();
+    assert_eq!(schema::decode(&bytes).unwrap().0, 5);
+
+    let state = SurfaceState::load(&fixture(5)).unwrap();
+    assert_eq!(state.game_state.stats.tick_count, 20);
+    assert_eq!(state.journal.entries.len(), 1);
+    // every cell was saved, the chunks are kept as they were
+    assert_eq!(state.grid.size(), 250);
+    assert_eq!(
+        state
+            .grid
+            .get(&Position::new(125, 125))
+            .map(|gent| gent.entity()),
+        Some(Entity::Fabricator)
+    );
+    let iron = (0..250)
+        .flat_map(|y| (0..250).map(move |x| Position::new(x, y)))
+        .filter(|pos| state.grid.get(pos).map(|gent| gent.entity()) == Some(Entity::Iron))
+        .count();
+    assert!(iron > 4000, "{iron}");
+}
+
+/// the entity on every cell of the default world that no agent is on
+fn terrain(grid: &Grid) -> Vec<Option<Entity>> {
+    (0..250)
+        .flat_map(|y| (0..250).map(move |x| Position::new(x, y)))
+        .map(|pos| match grid.get_direct(&pos) {
+            Some(Gent::Age(_) | Gent::Large(_)) => None,
+            gent => gent.map(Gent::entity),
+        })
+        .collect()
+}
+
+#[test]
+fn test_default_world_keeps_its_map() {
+    // the schema 1 fixture saved every cell of a world generated before worlds could be resized
+    let state = SurfaceState::load(&fixture(1)).unwrap();
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let fresh = generation::new_game(event_sender.clone(), Seed::Manual(1)).unwrap();
+    assert_eq!(
+        fresh.grid.terrain().map(|terrain| terrain.generator),
+        Some(Generator::Dense)
+    );
+    let (saved, fresh) = (terrain(&state.grid), terrain(&fresh.grid));
+    let differing = saved
+        .iter()
+        .zip(fresh.iter())
+        .filter(|(a, b)| a != b)
+        .count();
+    // only the cells the dog left and moved onto
+    assert!(differing <= 2, "{differing} cells differ");
+
+    // a journal from before the world size and generator were recorded replays on the same map
+    let journal = Journal::load(&fixture(5).with_extension("journal")).unwrap();
+    assert_eq!(journal.generator, Generator::Dense);
+    let (replayed, mismatches) = journal::replay(&journal, event_sender).unwrap();
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+    let state = SurfaceState::load(&fixture(5)).unwrap();
+    assert_eq!(terrain(&replayed.grid), terrain(&state.grid));
+}
+
+#[test]
+fn test_save_current_schema() {
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let mut surface = generation::new_game(event_sender, Seed::Manual(2)).unwrap();
+    surface.tick();
+    let path =
+        std::env::temp_dir().join(format!("texaform-schema-{}.texaform", std::process::id()));
+    SurfaceState::save_to_path(&surface, &path).unwrap();
+
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(schema::decode(&bytes).unwrap().0, SCHEMA);
+    let state = SurfaceState::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(path.with_extension("journal")).unwrap();
+    assert_eq!(state.game_state.stats.seed.value(), 2);
+    assert_eq!(state.game_state.stats.tick_count, 1);
+    assert_eq!(state.agents.len(), surface.agents.len());
+}
+
+#[test]
+fn test_export_import() {
+    let dir = std::env::temp_dir();
+    let json = dir.join(format!("texaform-export-{}.json", std::process::id()));
+    let save = dir.join(format!("texaform-import-{}.texaform", std::process::id()));
+    SurfaceState::export(&fixture(1), &json).unwrap();
+    SurfaceState::import(&json, &save).unwrap();
+
+    let text = std::fs::read_to_string(&json).unwrap();
+    assert!(text.starts_with(&format!("{{\n  \"schema\": {SCHEMA},")));
+    let bytes = std::fs::read(&save).unwrap();
+    // saves are gzip compressed
+    assert_eq!(bytes[..2], [0x1f, 0x8b]);
+    assert!(bytes.len() < text.len() / 10);
+
+    let state = SurfaceState::load(&save).unwrap();
+    let exported = SurfaceState::load(&json).unwrap();
+    std::fs::remove_file(&json).unwrap();
+    std::fs::remove_file(&save).unwrap();
+    assert_eq!(state.game_state.stats.tick_count, 20);
+    assert_eq!(exported.game_state.stats.tick_count, 20);
+}
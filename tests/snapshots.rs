@@ -0,0 +1,263 @@
+use std::path::PathBuf;
+use std::sync::Once;
+use std::time::{Duration, SystemTime};
+
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::widgets::{Block, Paragraph};
+use tachyonfx::{Interpolation, fx};
+
+use texaform::app::App;
+use texaform::surface::generation;
+use texaform::surface::recording::Recording;
+use texaform::surface::state::{Seed, SurfaceState};
+use texaform::ui::{self, AppLayout, Screen};
+
+// every screen is rendered for a fixed app state at several terminal sizes and compared to the
+// golden text in tests/snapshots. after an intended change to the ui rewrite them with
+//   UPDATE_SNAPSHOTS=1 cargo test --test snapshots
+// and review the diff
+
+const SIZES: [(u16, u16); 3] = [(80, 24), (120, 36), (200, 50)];
+
+/// smaller than any screen is laid out for, rendering only has to not panic
+const TINY_SIZES: [(u16, u16); 6] = [(0, 0), (1, 1), (10, 3), (20, 8), (40, 12), (300, 4)];
+
+/// a data directory with one save, shared by every test in this file
+fn data_dir() {
+    static DATA_DIR: Once = Once::new();
+    DATA_DIR.call_once(|| {
+        // relative so the paths shown on the screens are the same on every machine
+        let data_dir = PathBuf::from("target").join("snapshots-data");
+        let _ = std::fs::remove_dir_all(&data_dir);
+        std::fs::create_dir_all(&data_dir).unwrap();
+        texaform::logging::set_data_dir(data_dir.clone());
+
+        let mut surface = new_game();
+        for _ in 0..30 {
+            surface.tick();
+        }
+        let path = data_dir.join("alpha.texaform");
+        SurfaceState::save_to_path(&surface, &path).unwrap();
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_750_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    });
+}
+
+fn new_game() -> texaform::surface::Surface {
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    generation::new_game(event_sender, Seed::Manual(1)).expect("starting entities fit")
+}
+
+/// an app on `screen` in the middle of a game, without listening on the agents' ports
+fn app(screen: Screen) -> App {
+    data_dir();
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let mut app = App::new(event_sender, SIZES[0].0, SIZES[0].1);
+    app.seed = Seed::Manual(1);
+    // save times are otherwise shown in the machine's local time
+    app.save_manager.format_time = |time| {
+        chrono::DateTime::<chrono::Utc>::from(time)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+    // every effect finishes on the first frame, leaving the screen as it settles
+    app.prev_tick = Duration::from_secs(60);
+    if screen != Screen::MainMenu {
+        app.surface = new_game();
+        for _ in 0..10 {
+            app.surface.tick();
+        }
+        app.set_screen(Screen::Surface);
+    }
+    app.set_screen(screen);
+    app
+}
+
+fn render(app: &mut App, width: u16, height: u16) -> String {
+    app.layout = AppLayout::update(width, height, app);
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|frame| ui::render(app, frame)).unwrap();
+    text(terminal.backend().buffer())
+}
+
+fn text(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        let row: String = (0..buffer.area.width)
+            .map(|x| buffer[(x, y)].symbol())
+            .collect();
+        text.push_str(row.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from("tests")
+        .join("snapshots")
+        .join(format!("{name}.txt"));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!("{path:?}: {e}, write it with UPDATE_SNAPSHOTS=1 cargo test --test snapshots")
+    });
+    if expected == actual {
+        return;
+    }
+    let (line, (expected_line, actual_line)) = expected
+        .lines()
+        .chain(std::iter::repeat(""))
+        .zip(actual.lines().chain(std::iter::repeat("")))
+        .enumerate()
+        .find(|(_, (expected, actual))| expected != actual)
+        .expect("texts differ");
+    panic!(
+        "{name} differs from {path:?} first on line {}\nexpected: {expected_line}\n  actual: {actual_line}\n\n{actual}",
+        line + 1
+    );
+}
+
+/// renders `app` at every size, checking each against its golden text
+fn assert_screen(name: &str, app: &mut App) {
+    for (width, height) in SIZES {
+        let actual = render(app, width, height);
+        assert_snapshot(&format!("{name}_{width}x{height}"), &actual);
+    }
+    for (width, height) in TINY_SIZES {
+        render(app, width, height);
+    }
+}
+
+#[test]
+fn test_main_menu() {
+    assert_screen("main_menu", &mut app(Screen::MainMenu));
+}
+
+#[test]
+fn test_pause_menu() {
+    assert_screen("pause_menu", &mut app(Screen::PauseMenu));
+}
+
+#[test]
+fn test_settings() {
+    assert_screen("settings", &mut app(Screen::Settings));
+}
+
+#[test]
+fn test_load_game() {
+    assert_screen("load_game", &mut app(Screen::LoadGame));
+}
+
+#[test]
+fn test_save_game() {
+    assert_screen("save_game", &mut app(Screen::SaveGame));
+}
+
+#[test]
+fn test_surface() {
+    assert_screen("surface", &mut app(Screen::Surface));
+}
+
+#[test]
+fn test_surface_console() {
+    let mut app = app(Screen::Surface);
+    app.cheat("GIVE 3334 IRON 2");
+    app.cheat("FLY");
+    app.input_mode = texaform::app::InputMode::Console;
+    assert_screen("surface_console", &mut app);
+}
+
+#[test]
+fn test_surface_won() {
+    let mut app = app(Screen::Surface);
+    app.cheat("UNLOCK ALL");
+    app.surface.game_state.tech_tree.victory_achieved = true;
+    app.tick();
+    assert!(app.surface.victory_stats.is_some());
+    assert_screen("surface_won", &mut app);
+}
+
+#[test]
+fn test_documentation() {
+    assert_screen("documentation", &mut app(Screen::Documentation));
+}
+
+#[test]
+fn test_tech_tree() {
+    assert_screen("tech_tree", &mut app(Screen::TechTree));
+}
+
+#[test]
+fn test_replay() {
+    let mut app = app(Screen::MainMenu);
+    let path = std::env::temp_dir().join(format!(
+        "texaform-snapshots-{}.recording",
+        std::process::id()
+    ));
+    let mut surface = new_game();
+    surface.start_recording(&path, 5).unwrap();
+    for _ in 0..20 {
+        surface.tick();
+    }
+    let recording = Recording::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    app.view_recording(recording);
+    assert_screen("replay", &mut app);
+}
+
+#[test]
+fn test_scenarios() {
+    assert_screen("scenarios", &mut app(Screen::Scenarios));
+}
+
+#[test]
+fn test_render_clamped() {
+    let mut terminal = Terminal::new(TestBackend::new(20, 5)).unwrap();
+    let areas = [
+        Rect::new(15, 2, 10, 10),
+        Rect::new(30, 30, 5, 5),
+        Rect::new(0, 0, u16::MAX, u16::MAX),
+        Rect::new(u16::MAX - 1, u16::MAX - 1, 1, 1),
+        Rect::new(19, 4, 0, 0),
+    ];
+    for area in areas {
+        terminal
+            .draw(|frame| {
+                ui::render_widget_clamped(
+                    frame,
+                    Paragraph::new("clamped").block(Block::bordered()),
+                    area,
+                );
+                let mut effect = fx::fade_to_fg(Color::Red, (100, Interpolation::Linear));
+                ui::render_effect_clamped(frame, &mut effect, area, Duration::from_millis(50));
+            })
+            .unwrap();
+    }
+    // areas hanging off the terminal are moved back onto it, shrinking only when too large
+    terminal
+        .draw(|frame| ui::render_widget_clamped(frame, Block::bordered(), Rect::new(15, 2, 10, 10)))
+        .unwrap();
+    assert_eq!(
+        text(terminal.backend().buffer()),
+        [
+            "          ┌────────┐",
+            "          │        │",
+            "          │        │",
+            "          │        │",
+            "          └────────┘\n",
+        ]
+        .join("\n")
+    );
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use texaform::harness::Harness;
+use texaform::{speed, tcp};
+
+mod common;
+
+// the scenario's grid starts at 125 125
+const SMELTERY: &str = r#"{
+    "name": "SMELTERY",
+    "map": {"grid": ["....."]},
+    "agents": [
+        {"kind": "SMELTER", "at": [0, 0], "inventory": {"BUFFER_IN": ["IRON", "IRON"]}}
+    ]
+}"#;
+
+/// the next `;` terminated frame, replies and events share the connection
+async fn frame(socket: &mut TcpStream, read: &mut String) -> String {
+    let mut buf = [0; 256];
+    while !read.contains(';') {
+        let n = socket.read(&mut buf).await.unwrap();
+        assert!(n > 0, "connection closed");
+        read.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+    }
+    let (frame, rest) = read.split_once(';').unwrap();
+    let frame = frame.to_string();
+    *read = rest.to_string();
+    frame
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_subscriptions() {
+    tcp::set_port_base(common::port_base(module_path!()));
+    let mut harness = Harness::scenario(SMELTERY).unwrap();
+    harness.tick_rate = Duration::from_millis(1);
+    speed::set_command_latency_millis(0);
+    // a hand authored map has no solar panels
+    harness.surface.power.capacity = 10_000_000;
+    harness.surface.power.stored = 10_000_000;
+    let smelter = harness.port_base() + 1;
+    let (subscribed, unsubscribed) = harness
+        .serve(async move {
+            let mut socket = TcpStream::connect(format!("127.0.0.1:{smelter}"))
+                .await
+                .unwrap();
+            let mut read = String::new();
+            socket.write_all(b"SUBS SMELT_DONE;POWR;").await.unwrap();
+            let mut subscribed = vec![];
+            while subscribed.last().is_none_or(|f| f != "EVNT SMELT_DONE") {
+                subscribed.push(frame(&mut socket, &mut read).await);
+            }
+
+            socket.write_all(b"UNSB SMELT_DONE;").await.unwrap();
+            let mut unsubscribed = vec![frame(&mut socket, &mut read).await];
+            // keeps the smelter between 500 and 1500 degrees until the second plate is smelted
+            let mut powered = true;
+            loop {
+                socket.write_all(b"STAT;").await.unwrap();
+                let stat = frame(&mut socket, &mut read).await;
+                unsubscribed.push(stat.clone());
+                // IRON_PLATE is shown as R
+                if stat.matches('R').count() == 2 {
+                    break;
+                }
+                let temprature: usize = stat.split(' ').nth(1).unwrap().parse().unwrap();
+                if powered == (1000 < temprature) {
+                    socket.write_all(b"POWR;").await.unwrap();
+                    unsubscribed.push(frame(&mut socket, &mut read).await);
+                    powered = !powered;
+                }
+            }
+            (subscribed, unsubscribed)
+        })
+        .await;
+
+    // BUFFER_OUT was published with each plate but never subscribed to
+    assert_eq!(subscribed, vec!["OKAY", "OKAY", "EVNT SMELT_DONE"]);
+    assert_eq!(unsubscribed[0], "OKAY");
+    assert!(
+        unsubscribed.iter().all(|f| !f.starts_with("EVNT")),
+        "{unsubscribed:?}"
+    );
+}
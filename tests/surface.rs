@@ -0,0 +1,316 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Position;
+use texaform::app::App;
+use texaform::entities::Entity;
+use texaform::input;
+use texaform::speed;
+use texaform::surface::Surface;
+use texaform::surface::generation;
+use texaform::surface::grid::{CHUNK_SIZE, Gent, Grid};
+use texaform::surface::journal::{self, Entry, Journal};
+use texaform::surface::recording::{Recording, Snapshot};
+use texaform::surface::state::{Seed, SurfaceState};
+use texaform::tcp::port_base;
+use texaform::tech_tree::TechKind;
+use texaform::ui::Screen;
+
+fn new_game(seed: u64) -> Surface {
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    generation::new_game(event_sender, Seed::Manual(seed)).expect("starting entities fit")
+}
+
+#[test]
+fn test_hud_lists_agents() {
+    let mut surface = new_game(1);
+    let hud = port_base();
+    let replies: Vec<String> = (0..4)
+        .map(|_| surface.send(hud, "LIST AGNT").text)
+        .collect();
+    assert_eq!(
+        replies,
+        vec![
+            format!("{hud} HUD"),
+            format!("{} FABRICATOR", hud + 1),
+            format!("{} DOG", hud + 2),
+            format!("{} DOG", hud + 3),
+        ]
+    );
+}
+
+#[test]
+fn test_dog_moves() {
+    let mut surface = new_game(1);
+    let dog = port_base() + 2;
+    let before = surface.send(dog, "STAT").text;
+    assert_eq!(surface.send(dog, "TURN L").text, "OKAY");
+    assert_eq!(surface.send(dog, "TURN L").text, "OKAY");
+    assert_eq!(surface.send(dog, "MOVE").text, "OKAY");
+    let after = surface.send(dog, "STAT").text;
+    assert_ne!(before, after);
+    assert!(after.ends_with(" W 100% _"), "{after}");
+}
+
+#[test]
+fn test_errors() {
+    let mut surface = new_game(1);
+    let reply = surface.send(port_base() + 2, "JUMP");
+    // the text clients matched on before replies had codes
+    assert!(reply.text.starts_with("ERRR: "), "{}", reply.text);
+    assert_eq!(reply.json["ERRR"]["code"], "INVALID_COMMAND");
+    let reply = surface.send(port_base() + 2, "PICK IRON");
+    assert_eq!(reply.text, "ERRR no IRON to pick");
+
+    let reply = surface.send(1, "MOVE");
+    assert_eq!(reply.text, "ERRR unknown agent 1");
+    assert_eq!(reply.json["ERRR"]["code"], "UNKNOWN_AGENT");
+}
+
+#[test]
+fn test_lockstep_skips_abandoned_commands() {
+    let mut surface = new_game(1);
+    surface.lockstep = true;
+    let dog = port_base() + 2;
+    let before = surface.send(dog, "STAT").text;
+
+    let (reply_sender, reply_receiver) = tokio::sync::oneshot::channel();
+    surface.receive_agent_command(dog, "MOVE".to_string(), reply_sender);
+    // the client timed out while the command was queued
+    drop(reply_receiver);
+    surface.resolve_queued_commands();
+    assert_eq!(surface.send(dog, "STAT").text, before);
+
+    let (reply_sender, mut reply_receiver) = tokio::sync::oneshot::channel();
+    surface.receive_agent_command(dog, "TURN L".to_string(), reply_sender);
+    assert!(reply_receiver.try_recv().is_err());
+    surface.resolve_queued_commands();
+    assert_eq!(reply_receiver.try_recv().unwrap().text, "OKAY");
+}
+
+#[test]
+fn test_same_seed_same_surface() {
+    let mut a = new_game(42);
+    let mut b = new_game(42);
+    for _ in 0..10 {
+        a.tick();
+        b.tick();
+    }
+    let dog = port_base() + 2;
+    assert_eq!(a.send(dog, "SCAN").text, b.send(dog, "SCAN").text);
+    assert_eq!(
+        a.send(port_base(), "STAT POWR").text,
+        b.send(port_base(), "STAT POWR").text
+    );
+}
+
+#[test]
+fn test_replay_journal() {
+    let mut surface = new_game(7);
+    let dog = port_base() + 2;
+    surface.send(dog, "TURN L");
+    surface.tick();
+    surface.send(dog, "MOVE");
+    surface.update_agent_manual(&dog, "SCAN".to_string());
+    for _ in 0..5 {
+        surface.tick();
+    }
+    surface.send(dog, "PICK I");
+
+    let path = std::env::temp_dir().join(format!("texaform-{}.journal", std::process::id()));
+    surface.journal.save(&surface, &path).unwrap();
+    let file = Journal::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(file.entries, surface.journal.entries);
+    assert_eq!(file.tick, 6);
+
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let (mut replayed, mismatches) = journal::replay(&file, event_sender).unwrap();
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+    assert_eq!(replayed.game_state.stats.tick_count, 6);
+    assert_eq!(
+        replayed.send(dog, "STAT").text,
+        surface.send(dog, "STAT").text
+    );
+    assert_eq!(
+        replayed.send(dog, "SCAN").text,
+        surface.send(dog, "SCAN").text
+    );
+}
+
+#[test]
+fn test_replay_speed_commands() {
+    let surface = new_game(7);
+    let hud = port_base();
+    let speed = speed::describe();
+    let paused = speed::is_paused();
+
+    let mut file = surface.journal.snapshot(&surface);
+    let entry = |tick, command: &str, reply: &str| Entry {
+        tick,
+        port: hud,
+        command: command.to_string(),
+        reply: reply.to_string(),
+        manual: true,
+        cheat: false,
+    };
+    file.entries = vec![
+        entry(0, "SPED PAUSED", "OKAY"),
+        entry(0, "STEP 3", "OKAY"),
+        entry(3, "SPED 1X", "OKAY"),
+        entry(3, "STEP 1", "ERRR not paused"),
+    ];
+    file.tick = 5;
+
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let (replayed, mismatches) = journal::replay(&file, event_sender).unwrap();
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+    assert_eq!(replayed.game_state.stats.tick_count, 5);
+    assert_eq!(speed::describe(), speed);
+    assert_eq!(speed::is_paused(), paused);
+}
+
+#[tokio::test]
+async fn test_replay_research_from_tech_tree() {
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let mut app = App::new(event_sender, 120, 36);
+    app.surface = new_game(7);
+    app.set_screen(Screen::Surface);
+    app.set_screen(Screen::TechTree);
+    let tech_tree = &mut app.surface.game_state.tech_tree;
+    tech_tree.selected_node = tech_tree.tech_kind_idx(TechKind::Smelter).unwrap();
+    let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+    input::handle_key_events(enter, &mut app).await.unwrap();
+    app.surface.tick();
+    let fabricator = port_base() + 1;
+    let reply = app.surface.send(fabricator, "RESR").text;
+    assert_ne!(reply, "ERRR no active research");
+
+    let journal = app.surface.journal.encode(&app.surface).unwrap();
+    let path =
+        std::env::temp_dir().join(format!("texaform-research-{}.journal", std::process::id()));
+    std::fs::write(&path, journal).unwrap();
+    let file = Journal::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let (replayed, mismatches) = journal::replay(&file, event_sender).unwrap();
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+    assert_eq!(
+        replayed.game_state.tech_tree.research_node,
+        app.surface.game_state.tech_tree.research_node
+    );
+}
+
+#[test]
+fn test_agent_kept_after_research() {
+    let mut surface = new_game(7);
+    let fabricator = port_base() + 1;
+    let position = surface.agent_position(&fabricator).unwrap();
+    let smelter = surface
+        .game_state
+        .tech_tree
+        .tech_kind_idx(TechKind::Smelter)
+        .unwrap();
+    assert_eq!(surface.select_research(smelter).text, "OKAY");
+    let cost = surface.game_state.current_tech().unwrap().cost.clone();
+    for (entity, count) in cost {
+        let give = format!("GIVE {fabricator} {entity} {count}");
+        assert_eq!(surface.cheat(&give).text, "OKAY");
+    }
+    assert_eq!(surface.send(fabricator, "RESR").text, "OKAY");
+    // the fabricator is put back on the grid after a research step, it used to be dropped
+    assert!(matches!(surface.grid.get(&position), Some(Gent::Age(_))));
+    assert_eq!(surface.agent_position(&fabricator), Some(position));
+    assert!(surface.send(fabricator, "STAT").text.starts_with("STAT"));
+}
+
+#[test]
+fn test_recording() {
+    let mut surface = new_game(3);
+    let path = std::env::temp_dir().join(format!("texaform-{}.recording", std::process::id()));
+    surface.start_recording(&path, 5).unwrap();
+    for _ in 0..12 {
+        surface.tick();
+    }
+    let recording = Recording::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let ticks: Vec<u64> = recording.snapshots.iter().map(Snapshot::tick).collect();
+    assert_eq!(ticks, vec![0, 5, 10]);
+    assert_eq!(recording.at(7).map(Snapshot::tick), Some(5));
+    assert_eq!(recording.at(100).map(Snapshot::tick), Some(10));
+    assert_eq!(recording.snapshots[0].agents.len(), surface.agents.len());
+}
+
+#[test]
+fn test_recording_large_world() {
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let grid = Grid::empty(2000);
+    let mut surface = Surface::new(grid, 1000, 1000, Seed::Manual(1), event_sender);
+    // iron in every chunk so each is saved whole
+    for y in (0..2000).step_by(CHUNK_SIZE.into()) {
+        for x in (0..2000).step_by(CHUNK_SIZE.into()) {
+            surface
+                .grid
+                .insert(&Position::new(x, y), Gent::Intmd(Entity::Iron));
+        }
+    }
+    let path =
+        std::env::temp_dir().join(format!("texaform-large-{}.recording", std::process::id()));
+    surface.start_recording(&path, 1).unwrap();
+    surface.tick();
+    let bytes = std::fs::metadata(&path).unwrap().len();
+    let recording = Recording::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // each snapshot is more than the 1MB saves were once limited to
+    assert!(bytes > 2_000_000, "{bytes}");
+    assert_eq!(recording.snapshots.len(), 2);
+    let last = recording.snapshots[1].grid.get(&Position::new(1984, 1984));
+    assert_eq!(last.map(|g| g.entity()), Some(Entity::Iron));
+}
+
+#[test]
+fn test_world_size() {
+    let (event_sender, _) = tokio::sync::mpsc::unbounded_channel();
+    let mut surface =
+        generation::new_game_of_size(event_sender.clone(), Seed::Manual(5), 4000).unwrap();
+    assert_eq!(surface.grid.size(), 4000);
+    // the starting agents are around the center
+    assert_eq!(
+        surface
+            .grid
+            .get(&Position::new(2000, 2000))
+            .map(|g| g.entity()),
+        Some(Entity::Fabricator)
+    );
+    let dog = port_base() + 2;
+    assert_eq!(surface.send(dog, "MOVE").text, "OKAY");
+    let far = Position::new(3999, 3999);
+    let far_entity = surface.grid.get(&far).map(|g| g.entity());
+    assert!(surface.grid.get(&Position::new(4000, 0)).is_none());
+
+    let path = std::env::temp_dir().join(format!("texaform-size-{}.texaform", std::process::id()));
+    SurfaceState::save_to_path(&surface, &path).unwrap();
+    // only the chunks around the starting agents are saved
+    assert!(std::fs::metadata(&path).unwrap().len() < 20_000);
+    let state = SurfaceState::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let file = Journal::load(&Journal::path(&path)).unwrap();
+    std::fs::remove_file(Journal::path(&path)).unwrap();
+    assert_eq!(file.world_size, 4000);
+
+    let mut loaded = state.into_surface(event_sender.clone());
+    assert_eq!(loaded.grid.size(), 4000);
+    assert_eq!(loaded.grid.get(&far).map(|g| g.entity()), far_entity);
+    assert_eq!(
+        loaded.send(dog, "STAT").text,
+        surface.send(dog, "STAT").text
+    );
+
+    let (mut replayed, mismatches) = journal::replay(&file, event_sender).unwrap();
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+    assert_eq!(replayed.grid.size(), 4000);
+    assert_eq!(
+        replayed.send(dog, "STAT").text,
+        surface.send(dog, "STAT").text
+    );
+}
@@ -0,0 +1,65 @@
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use texaform::harness::Harness;
+use texaform::tcp;
+
+mod common;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_protocols() {
+    tcp::set_port_base(common::port_base(module_path!()));
+    let mut harness = Harness::seed(1).unwrap();
+    let dog = harness.port_base() + 2;
+    let other = harness.port_base() + 3;
+    let (replies, pipelined) = harness
+        .serve(async move {
+            // pipelined and spaced out
+            let mut socket = TcpStream::connect(format!("127.0.0.1:{other}"))
+                .await
+                .unwrap();
+            socket.write_all(b"TURN L; TURN L;\nSTAT ;").await.unwrap();
+            let mut pipelined = String::new();
+            let mut buf = [0; 256];
+            while pipelined.matches(';').count() < 3 {
+                let n = socket.read(&mut buf).await.unwrap();
+                pipelined.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+            }
+
+            let mut socket = TcpStream::connect(format!("127.0.0.1:{dog}"))
+                .await
+                .unwrap();
+            let commands = concat!(
+                "PROT JSON;",
+                "{\"command\": \"TURN L\"}\n",
+                "{\"command\": \"JUMP\"}\n",
+                "MOVE\n",
+                "{\"command\": \"PROT TEXT\"}\n",
+                "STAT;",
+            );
+            socket.write_all(commands.as_bytes()).await.unwrap();
+            let mut replies = String::new();
+            while replies.matches('\n').count() < 4 || !replies.ends_with(';') {
+                let n = socket.read(&mut buf).await.unwrap();
+                replies.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+            }
+            (replies, pipelined)
+        })
+        .await;
+    assert!(pipelined.starts_with("OKAY;OKAY;STAT "), "{pipelined}");
+    assert!(pipelined.ends_with(" W 100% _;"), "{pipelined}");
+
+    // each handshake is answered in the protocol it was sent in
+    let replies = replies.strip_prefix("OKAY;").expect(&replies);
+    let (json, text) = replies.rsplit_once('\n').unwrap();
+    let json: Vec<serde_json::Value> = json
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(json[0], "OKAY");
+    assert_eq!(json[1]["ERRR"]["code"], "INVALID_COMMAND");
+    assert_eq!(json[2]["ERRR"]["code"], "INVALID_REQUEST");
+    assert_eq!(json[3], "OKAY");
+    assert_eq!(json.len(), 4);
+    assert!(text.ends_with(" N 100% _;"), "{text}");
+}